@@ -3,15 +3,20 @@ use dialogue::Dialogue;
 use map_setup::MapSetup;
 use midi::Midi;
 use model::Model;
+use n64_sprite::N64Sprite;
 use question::Question;
 use sprite::Sprite;
 use unknown::Unknown;
 
 pub mod animation;
+pub mod db360;
 pub mod dialogue;
+pub mod dump;
 pub mod map_setup;
 pub mod midi;
 pub mod model;
+pub mod n64_sprite;
+pub mod obj;
 pub mod question;
 pub mod sprite;
 pub mod unknown;
@@ -23,6 +28,7 @@ pub enum Asset {
     Question(Question),
     Unknown(Unknown),
     Sprite(Sprite),
+    N64Sprite(N64Sprite),
     Model(Model),
     Midi(Midi),
     Empty,
@@ -1,4 +1,5 @@
 use crate::enums::Transform;
+use crate::error::expect_value;
 use crate::utils::fixed_to_float;
 use crate::utils::float_to_fixed;
 use byteorder::BigEndian;
@@ -33,7 +34,7 @@ impl Animation {
         let end_frame = reader.read_u16::<BigEndian>()?;
         let elem_count = reader.read_u16::<BigEndian>()?;
         let padding = reader.read_u16::<BigEndian>()?;
-        assert_eq!(padding, 0);
+        expect_value("padding", 0, padding)?;
 
         let mut transforms = vec![];
         for _ in 0..elem_count {
@@ -47,7 +48,7 @@ impl Animation {
             for _ in 0..data_count {
                 let header = reader.read_u16::<BigEndian>()?;
                 let factor = reader.read_u16::<BigEndian>()?;
-                let factor = fixed_to_float(factor);
+                let factor = fixed_to_float(factor)?;
 
                 frames.push(FrameData {
                     unk1: (header >> 15) != 0,
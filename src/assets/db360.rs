@@ -0,0 +1,371 @@
+use super::Asset;
+use super::animation::Animation;
+use super::dialogue::Dialogue;
+use super::map_setup::MapSetup;
+use super::midi::Midi;
+use super::model::Model;
+use super::n64_sprite::N64Sprite;
+use super::question::Question;
+use super::sprite::Sprite;
+use super::unknown::Unknown;
+use crate::enums::AssetId;
+use crate::enums::SpriteId;
+use crate::error::RandoError;
+use crate::utils::align_writer;
+use byteorder::BigEndian;
+use byteorder::ReadBytesExt;
+use byteorder::WriteBytesExt;
+use std::error::Error;
+use std::io::Cursor;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+use std::io::Write;
+
+pub const DB360_FILENAME: &str = "db360.cmp";
+
+// what each entry of db360.cmp is, in order
+pub use crate::data::db360::ASSETS;
+
+pub struct AssetData {
+    pub asset: Asset,
+    pub flag: u32,
+}
+
+struct Entry {
+    // from the start of the file
+    offset: u64,
+    size: usize,
+    flag: u32,
+}
+
+/// An asset that doesn't come out of its writer byte for byte as it was read.
+pub struct Mismatch {
+    pub id: usize,
+    pub asset: String,
+    /// first byte that differs, from the start of the asset
+    pub offset: usize,
+    pub original_size: usize,
+    pub written_size: usize,
+}
+
+/// An asset kept as raw bytes because its reader failed on it.
+pub struct Unread {
+    pub id: usize,
+    pub asset: String,
+    pub error: String,
+}
+
+/// What `verify_roundtrip` found.
+pub struct Roundtrip {
+    pub mismatches: Vec<Mismatch>,
+    pub unread: Vec<Unread>,
+}
+
+pub fn read_db360<R: Read + Seek>(file: &mut R) -> Result<Vec<AssetData>, Box<dyn Error>> {
+    let entries = read_entries(file)?;
+
+    let mut loaded_assets = vec![];
+
+    for (id, asset_id) in ASSETS.iter().enumerate() {
+        let Entry { offset, size, flag } = entries[id];
+        file.seek(SeekFrom::Start(offset))?;
+
+        let asset = read_asset(file, asset_id, size).map_err(|source| RandoError::Asset {
+            id,
+            asset: asset_id.to_string(),
+            start: offset,
+            offset: file.stream_position().unwrap_or(offset),
+            source,
+        })?;
+
+        loaded_assets.push(AssetData { asset, flag });
+    }
+
+    Ok(loaded_assets)
+}
+
+fn read_entries<R: Read + Seek>(file: &mut R) -> Result<Vec<Entry>, Box<dyn Error>> {
+    let start = file.stream_position()?;
+    let file_size = file.seek(SeekFrom::End(0))? - start;
+    file.seek(SeekFrom::Start(start))?;
+
+    let entry_count = file.read_u32::<BigEndian>()?;
+    check_entry_count(DB360_FILENAME, ASSETS.len() as u32, entry_count)?;
+    let _padding = file.read_u32::<BigEndian>()?;
+
+    let mut offsets = vec![];
+    let mut flags = vec![];
+    for _ in 0..entry_count {
+        offsets.push(file.read_u32::<BigEndian>()? as u64);
+        flags.push(file.read_u32::<BigEndian>()?);
+    }
+
+    // offsets are relative to the end of the table
+    let header = file.stream_position()?;
+    offsets.push(file_size - (header - start));
+
+    let mut entries = vec![];
+    for (i, window) in offsets.windows(2).enumerate() {
+        let (curr, next) = (window[0], window[1]);
+        if next < curr {
+            return Err(RandoError::unexpected(
+                "asset offset",
+                format!("at least 0x{curr:X}"),
+                format!("0x{next:X}"),
+            )
+            .into());
+        }
+
+        entries.push(Entry {
+            offset: header + curr,
+            size: (next - curr) as usize,
+            flag: flags[i],
+        });
+    }
+
+    Ok(entries)
+}
+
+pub(crate) fn read_asset<R: Read + Seek>(
+    file: &mut R,
+    asset: &AssetId,
+    size: usize,
+) -> Result<Asset, Box<dyn Error>> {
+    Ok(match asset {
+        AssetId::Empty => Asset::Empty,
+        AssetId::Animation(_animation_id) => {
+            let data = Animation::new(file)?;
+            Asset::Animation(data)
+        }
+        AssetId::Midi(_midi_id) => {
+            let data = Midi::new(file, size)?;
+            Asset::Midi(data)
+        }
+        AssetId::Model(_model_id) => {
+            let mut buffer = vec![0u8; size];
+            file.read_exact(&mut buffer)?;
+
+            // kept as is if the header isn't understood, verify_roundtrip reports it
+            match Model::new(&mut &buffer[..], size) {
+                Ok(data) => Asset::Model(data),
+                Err(_) => Asset::Unknown(Unknown::new(&mut &buffer[..], size)?),
+            }
+        }
+        AssetId::MapSetup(_map_setup_id) => {
+            let map = MapSetup::new(file)?;
+            Asset::MapSetup(map)
+        }
+        AssetId::Dialogue(_dialogue_id) => {
+            let data = Dialogue::new(file)?;
+            Asset::Dialogue(data)
+        }
+        AssetId::Credits(_credits_id) => {
+            let data = Dialogue::new(file)?;
+            Asset::Dialogue(data)
+        }
+        AssetId::Sprite(sprite_id) => {
+            if is_n64_sprite(*sprite_id) {
+                let mut buffer = vec![0u8; size];
+                file.read_exact(&mut buffer)?;
+
+                // kept as is if it isn't what the N64 layout says, verify_roundtrip reports it
+                match N64Sprite::new(&mut &buffer[..], size) {
+                    Ok(data) => Asset::N64Sprite(data),
+                    Err(_) => Asset::Unknown(Unknown::new(&mut &buffer[..], size)?),
+                }
+            } else {
+                let data = Sprite::new(file)?;
+                Asset::Sprite(data)
+            }
+        }
+        AssetId::Question(_question_id) => {
+            let data = Question::new(file)?;
+            Asset::Question(data)
+        }
+        AssetId::Xbox(_xbox_id) => {
+            let data = Dialogue::new(file)?;
+            Asset::Dialogue(data)
+        }
+        AssetId::Unknown(_unknown_id) => {
+            let data = Unknown::new(file, size)?;
+            Asset::Unknown(data)
+        }
+    })
+}
+
+// these sprites have a "strange" format (closer to N64 or something else)
+fn is_n64_sprite(sprite_id: SpriteId) -> bool {
+    matches!(
+        sprite_id,
+        SpriteId::Sprite0064D520
+            | SpriteId::Sprite0064E8D8
+            | SpriteId::Sprite0064EB58
+            | SpriteId::Sprite006536C8
+            | SpriteId::Sprite006546D8
+            | SpriteId::Sprite0064ECC8
+    )
+}
+
+// why read_asset kept `bytes` raw, none if it didn't
+fn fallback_error(asset: &AssetId, bytes: &[u8]) -> Option<String> {
+    match asset {
+        AssetId::Model(_) => Model::new(&mut &bytes[..], bytes.len())
+            .err()
+            .map(|e| e.to_string()),
+        AssetId::Sprite(sprite_id) if is_n64_sprite(*sprite_id) => {
+            N64Sprite::new(&mut &bytes[..], bytes.len())
+                .err()
+                .map(|e| e.to_string())
+        }
+        _ => None,
+    }
+}
+
+pub fn write_db360<W: Write + Seek>(
+    assets: &[AssetData],
+    patched: &mut W,
+) -> Result<(), Box<dyn Error>> {
+    let entry_count = assets.len();
+
+    patched.write_u32::<BigEndian>(entry_count as u32)?;
+    patched.write_u32::<BigEndian>(0xCDCDCDCD)?;
+    for _ in 0..entry_count {
+        patched.write_u32::<BigEndian>(0)?;
+        patched.write_u32::<BigEndian>(0)?;
+    }
+
+    let mut offsets = vec![];
+    let header = patched.stream_position()?;
+
+    for data in assets {
+        let current_offset = patched.stream_position()? - header;
+        offsets.push(current_offset as u32);
+
+        write_asset(&data.asset, patched)?;
+        align_writer(patched)?;
+    }
+
+    patched.seek(SeekFrom::Start(8))?;
+
+    for (offset, data) in offsets.iter().zip(assets) {
+        patched.write_u32::<BigEndian>(*offset)?;
+        patched.write_u32::<BigEndian>(data.flag)?;
+    }
+
+    Ok(())
+}
+
+pub fn write_asset<W: Write + Seek>(asset: &Asset, writer: &mut W) -> Result<(), Box<dyn Error>> {
+    match asset {
+        Asset::Animation(animation) => {
+            animation.write(writer)?;
+        }
+        Asset::Dialogue(dialogue) => {
+            dialogue.write(writer)?;
+        }
+        Asset::MapSetup(map_setup) => {
+            map_setup.write(writer)?;
+        }
+        Asset::Question(question) => {
+            question.write(writer)?;
+        }
+        Asset::Sprite(sprite) => {
+            sprite.write(writer)?;
+        }
+        Asset::N64Sprite(sprite) => {
+            sprite.write(writer)?;
+        }
+        Asset::Model(model) => {
+            model.write(writer)?;
+        }
+        Asset::Midi(midi) => {
+            midi.write(writer)?;
+        }
+        Asset::Unknown(unknown) => {
+            unknown.write(writer)?;
+        }
+        Asset::Empty => {}
+    }
+
+    Ok(())
+}
+
+/// Reads every asset of db360.cmp, writes it back and compares it with the original bytes. Also
+/// lists the assets kept raw because they couldn't be read as what they are.
+pub fn verify_roundtrip<R: Read + Seek>(file: &mut R) -> Result<Roundtrip, Box<dyn Error>> {
+    let start = file.stream_position()?;
+    let assets = read_db360(file)?;
+    file.seek(SeekFrom::Start(start))?;
+    let entries = read_entries(file)?;
+
+    let mut mismatches = vec![];
+    let mut unread = vec![];
+    for (id, (data, entry)) in assets.iter().zip(&entries).enumerate() {
+        let mut original = vec![];
+        file.seek(SeekFrom::Start(entry.offset))?;
+        file.by_ref()
+            .take(entry.size as u64)
+            .read_to_end(&mut original)?;
+
+        if let Asset::Unknown(_) = data.asset
+            && let Some(error) = fallback_error(&ASSETS[id], &original)
+        {
+            unread.push(Unread {
+                id,
+                asset: ASSETS[id].to_string(),
+                error,
+            });
+        }
+
+        let mut written = Cursor::new(vec![]);
+        write_asset(&data.asset, &mut written)?;
+        let written = written.into_inner();
+
+        // what's after the asset is the 0xCD alignment
+        let padding = original.len().saturating_sub(written.len());
+        let unpadded = original.len() - padding;
+        let padding_ok = original[unpadded..].iter().all(|&b| b == 0xCD) && padding < 8;
+
+        let first_diff = original.iter().zip(&written).position(|(a, b)| a != b).or(
+            if written.len() > original.len() || !padding_ok {
+                Some(original.len().min(written.len()))
+            } else {
+                None
+            },
+        );
+
+        if let Some(offset) = first_diff {
+            mismatches.push(Mismatch {
+                id,
+                asset: ASSETS[id].to_string(),
+                offset,
+                original_size: unpadded,
+                written_size: written.len(),
+            });
+        }
+    }
+
+    Ok(Roundtrip { mismatches, unread })
+}
+
+// the first u32 of a file compressed with XMemCompress
+pub(crate) const XMEM_MAGIC: [u32; 2] = [0x0FF512ED, 0x0FF512EE];
+
+pub(crate) fn check_entry_count(
+    name: &'static str,
+    expected: u32,
+    found: u32,
+) -> Result<(), RandoError> {
+    if XMEM_MAGIC.contains(&found) {
+        Err(RandoError::Compressed { name })
+    } else if found != expected {
+        Err(RandoError::EntryCount {
+            name,
+            expected,
+            found,
+        })
+    } else {
+        Ok(())
+    }
+}
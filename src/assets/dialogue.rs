@@ -5,6 +5,8 @@ use byteorder::BigEndian;
 use byteorder::LittleEndian;
 use byteorder::ReadBytesExt;
 use byteorder::WriteBytesExt;
+use serde::Deserialize;
+use serde::Serialize;
 use std::collections::HashMap;
 use std::error::Error;
 use std::io::Read;
@@ -13,9 +15,12 @@ use std::io::SeekFrom;
 use std::io::Write;
 
 use crate::enums::Language;
+use crate::error::RandoError;
+use crate::error::expect_value;
 
 enum_builder! {
     #[repr(u8)]
+    #[derive(Serialize, Deserialize)]
     pub enum Speaker {
         Banjo = 128,
         Kazooie = 129,
@@ -112,7 +117,7 @@ enum_builder! {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum DialogueCommand {
     MrVileCheck,
     BottlesCheck,
@@ -141,11 +146,13 @@ impl DialogueCommand {
     }
 }
 
+#[derive(Serialize, Deserialize)]
 pub struct DialogueData {
     pub top: Vec<DialogueCommand>,
     pub bottom: Vec<DialogueCommand>,
 }
 
+#[derive(Serialize, Deserialize)]
 pub struct Dialogue {
     pub translations: HashMap<Language, DialogueData>,
 }
@@ -153,7 +160,7 @@ pub struct Dialogue {
 impl Dialogue {
     pub fn new<R: Read>(reader: &mut R) -> Result<Self, Box<dyn Error>> {
         let languages = reader.read_u8()?;
-        assert_eq!(languages, 4);
+        expect_value("language count", 4, languages)?;
 
         let mut translations = HashMap::new();
 
@@ -191,10 +198,10 @@ impl Dialogue {
     }
 
     pub fn write<W: Write + Seek>(&self, writer: &mut W) -> Result<(), Box<dyn Error>> {
-        let header_offset = writer.seek(SeekFrom::Current(0))?;
+        let header_offset = writer.stream_position()?;
         writer.write_u8(self.translations.len() as u8)?;
 
-        let offsets_position = writer.seek(SeekFrom::Current(0))?;
+        let offsets_position = writer.stream_position()?;
 
         for _ in 0..self.translations.len() {
             writer.write_u16::<BigEndian>(0)?;
@@ -202,7 +209,7 @@ impl Dialogue {
 
         let mut offsets = vec![];
         for lang in 0..(self.translations.len() as u8) {
-            let offset = (writer.seek(SeekFrom::Current(0))? - header_offset) as u16;
+            let offset = (writer.stream_position()? - header_offset) as u16;
             offsets.push(offset);
 
             let data = &self.translations[&lang.into()];
@@ -218,7 +225,7 @@ impl Dialogue {
             }
         }
 
-        let end_of_file = writer.seek(SeekFrom::Current(0))?;
+        let end_of_file = writer.stream_position()?;
 
         writer.seek(SeekFrom::Start(offsets_position))?;
         for offset in offsets {
@@ -239,36 +246,36 @@ impl DialogueCommand {
         Ok(match command_id {
             1 => {
                 let string = read_string(reader)?;
-                assert_eq!(string.len(), 0);
+                expect_value("empty string length", 0, string.len())?;
                 DialogueCommand::MrVileCheck
             }
             2 => {
                 let string = read_string(reader)?;
-                assert_eq!(string.len(), 0);
+                expect_value("empty string length", 0, string.len())?;
                 DialogueCommand::BottlesCheck
             }
             3 => {
                 let string = read_string(reader)?;
-                assert_eq!(string.len(), 0);
+                expect_value("empty string length", 0, string.len())?;
                 DialogueCommand::BoggyAndThirdCheatCheck
             }
             4 => {
                 let string = read_string(reader)?;
-                assert_eq!(string.len(), 0);
+                expect_value("empty string length", 0, string.len())?;
                 DialogueCommand::EndOfSection
             }
             6 => {
                 let string = read_string(reader)?;
-                assert_eq!(string.len(), 0);
+                expect_value("empty string length", 0, string.len())?;
                 DialogueCommand::SwitchBox
             }
             7 => {
                 let len = reader.read_u8()?;
-                assert_eq!(len, 2);
+                expect_value("trigger length", 2, len)?;
                 let value = reader.read_u8()?;
 
                 let null = reader.read_u8()?;
-                assert_eq!(null, 0);
+                expect_value("null terminator", 0, null)?;
                 DialogueCommand::Trigger(value)
             }
             8 => {
@@ -277,14 +284,21 @@ impl DialogueCommand {
             }
             9 => {
                 let string = read_string(reader)?;
-                assert_eq!(string.len(), 0);
+                expect_value("empty string length", 0, string.len())?;
                 DialogueCommand::ItemCount
             }
             128..=219 => {
                 let string = read_string(reader)?;
                 DialogueCommand::Speak(command_id.into(), string)
             }
-            _ => panic!("todo: command {command_id:X} ({command_id})"),
+            _ => {
+                return Err(RandoError::unexpected(
+                    "dialogue command",
+                    "1 to 9 or 0x80 to 0xDB",
+                    format!("0x{command_id:X}"),
+                )
+                .into());
+            }
         })
     }
 
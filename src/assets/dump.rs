@@ -0,0 +1,235 @@
+use super::Asset;
+use super::db360::AssetData;
+use super::db360::read_asset;
+use super::db360::write_asset;
+use super::dialogue::Dialogue;
+use super::question::Question;
+use crate::data::db360::ASSETS;
+use crate::enums::AssetId;
+use crate::enums::Language;
+use crate::enums::TextureId;
+use crate::error::RandoError;
+use crate::textures::Image;
+use crate::textures::Textures;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::fs::File;
+use std::io::BufWriter;
+use std::io::Cursor;
+use std::path::Path;
+
+// flag of every entry of db360.cmp, empty ones included
+pub const FLAGS_FILENAME: &str = "flags.json";
+
+/// Writes every asset of db360.cmp in `directory`, one file each, named after its `AssetId`.
+/// Returns the number of files written.
+pub fn dump_db360(assets: &[AssetData], directory: &Path) -> Result<usize, Box<dyn Error>> {
+    fs::create_dir_all(directory)?;
+
+    let mut count = 0;
+    for (asset_id, data) in ASSETS.iter().zip(assets) {
+        // there's nothing to look at
+        if let AssetId::Empty = asset_id {
+            continue;
+        }
+
+        let path = directory.join(file_name(asset_id));
+        let mut writer = BufWriter::new(File::create(path)?);
+
+        match &data.asset {
+            Asset::MapSetup(map_setup) => write_json(&mut writer, map_setup)?,
+            Asset::Dialogue(dialogue) => write_json(&mut writer, dialogue)?,
+            Asset::Question(question) => write_json(&mut writer, question)?,
+            // the pixels are in the sprite, they can be looked at too
+            Asset::N64Sprite(sprite) => {
+                write_asset(&data.asset, &mut writer)?;
+
+                let frames = (0..sprite.frames.len())
+                    .map(|i| sprite.frame_image(i))
+                    .collect::<Vec<_>>();
+                let sheet = Image::sheet(&frames);
+                if sheet.width > 0 && sheet.height > 0 {
+                    let file = File::create(directory.join(format!("{asset_id}.png")))?;
+                    sheet.write_png(BufWriter::new(file))?;
+                }
+            }
+            // the rest isn't understood well enough, it's kept as the game has it
+            asset => write_asset(asset, &mut writer)?,
+        }
+
+        count += 1;
+    }
+
+    let flags = assets.iter().map(|data| data.flag).collect::<Vec<_>>();
+    let mut writer = BufWriter::new(File::create(directory.join(FLAGS_FILENAME))?);
+    write_json(&mut writer, &flags)?;
+
+    Ok(count)
+}
+
+/// Writes every texture of db360.textures.cmp in `directory` as a PNG, or as the raw `.bin` the
+/// GPU reads when it can't be decoded. Returns the number of PNG and of `.bin` written.
+pub fn dump_textures(
+    textures: &Textures,
+    directory: &Path,
+) -> Result<(usize, usize), Box<dyn Error>> {
+    fs::create_dir_all(directory)?;
+
+    let (mut decoded, mut raw) = (0, 0);
+    for id in 0..textures.records.len() {
+        let name = match TextureId::from(id) {
+            TextureId::Unknown(_) => format!("Texture({id})"),
+            texture_id => format!("Texture({texture_id})"),
+        };
+
+        match textures.decode(id) {
+            Ok(image) => {
+                let file = File::create(directory.join(format!("{name}.png")))?;
+                image.write_png(BufWriter::new(file))?;
+                decoded += 1;
+            }
+            Err(_) => {
+                // nothing to see, not even raw
+                let Ok(bytes) = textures.raw(id) else {
+                    continue;
+                };
+                fs::write(directory.join(format!("{name}.bin")), bytes)?;
+                raw += 1;
+            }
+        }
+    }
+
+    Ok((decoded, raw))
+}
+
+/// Writes the frames of every sprite of db360.cmp side by side in a PNG, the six with the N64
+/// layout aside (`dump_db360` does those). Returns the number of PNG written.
+pub fn dump_sprites(
+    assets: &[AssetData],
+    textures: &Textures,
+    directory: &Path,
+) -> Result<usize, Box<dyn Error>> {
+    fs::create_dir_all(directory)?;
+
+    let mut count = 0;
+    for (asset_id, data) in ASSETS.iter().zip(assets) {
+        let Asset::Sprite(sprite) = &data.asset else {
+            continue;
+        };
+        // a frame without a texture that can be decoded, the .bin will have to do
+        let Ok(frames) = sprite.frame_images(textures) else {
+            continue;
+        };
+
+        let sheet = Image::sheet(&frames);
+        if sheet.width > 0 && sheet.height > 0 {
+            let file = File::create(directory.join(format!("{asset_id}.png")))?;
+            sheet.write_png(BufWriter::new(file))?;
+            count += 1;
+        }
+    }
+
+    Ok(count)
+}
+
+/// Reads back a directory written by `dump_db360`, edited or not, ready for `write_db360`.
+pub fn read_dump(directory: &Path) -> Result<Vec<AssetData>, Box<dyn Error>> {
+    let path = directory.join(FLAGS_FILENAME);
+    let flags: Vec<u32> = read_json(&path).map_err(|source| RandoError::Dumped {
+        file: path.display().to_string(),
+        source,
+    })?;
+    if flags.len() != ASSETS.len() {
+        return Err(RandoError::unexpected("flag count", ASSETS.len(), flags.len()).into());
+    }
+
+    let mut assets = vec![];
+    for (asset_id, flag) in ASSETS.iter().zip(flags) {
+        let asset = match asset_id {
+            AssetId::Empty => Asset::Empty,
+            _ => {
+                let path = directory.join(file_name(asset_id));
+                read_dumped_asset(asset_id, &path).map_err(|source| RandoError::Dumped {
+                    file: path.display().to_string(),
+                    source,
+                })?
+            }
+        };
+
+        assets.push(AssetData { asset, flag });
+    }
+
+    Ok(assets)
+}
+
+fn read_dumped_asset(asset_id: &AssetId, path: &Path) -> Result<Asset, Box<dyn Error>> {
+    let asset = match asset_id {
+        AssetId::MapSetup(_) => Asset::MapSetup(read_json(path)?),
+        AssetId::Dialogue(_) | AssetId::Credits(_) | AssetId::Xbox(_) => {
+            let dialogue: Dialogue = read_json(path)?;
+            check_languages(&dialogue.translations)?;
+            Asset::Dialogue(dialogue)
+        }
+        AssetId::Question(_) => {
+            let question: Question = read_json(path)?;
+            check_languages(&question.translations)?;
+            Asset::Question(question)
+        }
+        _ => {
+            let bytes = fs::read(path)?;
+            let size = bytes.len();
+            read_asset(&mut Cursor::new(bytes), asset_id, size)?
+        }
+    };
+
+    // strings that can't be encoded and such, found now rather than halfway through db360.cmp
+    write_asset(&asset, &mut Cursor::new(vec![]))?;
+
+    Ok(asset)
+}
+
+// the writers expect every language, in order
+fn check_languages<T>(translations: &HashMap<Language, T>) -> Result<(), RandoError> {
+    for lang in 0..4u8 {
+        let lang = Language::from(lang);
+        if !translations.contains_key(&lang) {
+            return Err(RandoError::unexpected("translation", lang, "nothing"));
+        }
+    }
+    if translations.len() != 4 {
+        return Err(RandoError::unexpected(
+            "translation count",
+            4,
+            translations.len(),
+        ));
+    }
+
+    Ok(())
+}
+
+fn file_name(asset_id: &AssetId) -> String {
+    let extension = match asset_id {
+        AssetId::MapSetup(_)
+        | AssetId::Dialogue(_)
+        | AssetId::Credits(_)
+        | AssetId::Xbox(_)
+        | AssetId::Question(_) => "json",
+        AssetId::Midi(_) => "mid",
+        _ => "bin",
+    };
+
+    format!("{asset_id}.{extension}")
+}
+
+fn write_json<T: Serialize>(writer: &mut BufWriter<File>, value: &T) -> Result<(), Box<dyn Error>> {
+    serde_json::to_writer_pretty(writer, value)?;
+    Ok(())
+}
+
+fn read_json<T: DeserializeOwned>(path: &Path) -> Result<T, Box<dyn Error>> {
+    let strbuf = fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&strbuf)?)
+}
@@ -1,6 +1,9 @@
+use crate::error::expect_value;
 use byteorder::BigEndian;
 use byteorder::ReadBytesExt;
 use byteorder::WriteBytesExt;
+use serde::Deserialize;
+use serde::Serialize;
 use std::cmp;
 use std::error::Error;
 use std::io::Read;
@@ -18,6 +21,7 @@ pub use prop_1::Prop1;
 mod prop_2;
 pub use prop_2::Prop2;
 
+#[derive(Serialize, Deserialize)]
 pub struct MapSetup {
     pub cubes: Vec<Cube>,
     pub cameras: Vec<Camera>,
@@ -27,7 +31,7 @@ pub struct MapSetup {
 impl MapSetup {
     pub fn new<R: Read>(reader: &mut R) -> Result<Self, Box<dyn Error>> {
         let header = reader.read_u16::<BigEndian>()?;
-        assert_eq!(header, 0x0101);
+        expect_value("map setup header", 0x0101, header)?;
         let negative_x_cube_count = reader.read_i32::<BigEndian>()?;
         let negative_y_cube_count = reader.read_i32::<BigEndian>()?;
         let negative_z_cube_count = reader.read_i32::<BigEndian>()?;
@@ -50,10 +54,10 @@ impl MapSetup {
         }
 
         let end = reader.read_u8()?;
-        assert_eq!(end, 0);
+        expect_value("end of cubes", 0, end)?;
 
         let camera_header = reader.read_u8()?;
-        assert_eq!(camera_header, 3);
+        expect_value("camera header", 3, camera_header)?;
 
         let mut cameras = vec![];
 
@@ -63,10 +67,10 @@ impl MapSetup {
 
             camera_header = reader.read_u8()?;
         }
-        assert_eq!(camera_header, 0);
+        expect_value("end of cameras", 0, camera_header)?;
 
         let lighting_header = reader.read_u8()?;
-        assert_eq!(lighting_header, 4);
+        expect_value("lighting header", 4, lighting_header)?;
 
         let mut lightings = vec![];
 
@@ -76,10 +80,10 @@ impl MapSetup {
 
             section_id = reader.read_u8()?;
         }
-        assert_eq!(section_id, 0);
+        expect_value("end of lightings", 0, section_id)?;
 
         let end = reader.read_u8()?;
-        assert_eq!(end, 0);
+        expect_value("end of map setup", 0, end)?;
 
         Ok(Self {
             cubes,
@@ -1,11 +1,16 @@
+use crate::error::RandoError;
+use crate::error::expect_value;
 use crate::utils::*;
 use byteorder::BigEndian;
 use byteorder::ReadBytesExt;
 use byteorder::WriteBytesExt;
+use serde::Deserialize;
+use serde::Serialize;
 use std::error::Error;
 use std::io::Read;
 use std::io::Write;
 
+#[derive(Serialize, Deserialize)]
 pub enum Camera {
     Empty {
         id: u16,
@@ -44,7 +49,7 @@ impl Camera {
     pub fn new<R: Read>(reader: &mut R) -> Result<Self, Box<dyn Error>> {
         let id = reader.read_u16::<BigEndian>()?;
         let camera_two = reader.read_u8()?;
-        assert_eq!(camera_two, 2);
+        expect_value("camera type marker", 2, camera_two)?;
         let camera_type = reader.read_u8()?;
 
         Ok(match camera_type {
@@ -53,7 +58,9 @@ impl Camera {
             2 => Self::read_static_camera(reader, id)?,
             3 => Self::read_pivot_or_zoom_camera(reader, 3, id)?,
             4 => Self::read_random_camera(reader, id)?,
-            _ => todo!(),
+            _ => {
+                return Err(RandoError::unexpected("camera type", "0 to 4", camera_type).into());
+            }
         })
     }
 
@@ -63,33 +70,33 @@ impl Camera {
         id: u16,
     ) -> Result<Self, Box<dyn Error>> {
         let section_id = reader.read_u8()?;
-        assert_eq!(section_id, 1);
+        expect_value("section id", 1, section_id)?;
         let position = read_3_floats(reader)?;
 
         let section_id = reader.read_u8()?;
-        assert_eq!(section_id, 2);
+        expect_value("section id", 2, section_id)?;
         let speed = read_2_floats(reader)?;
 
         let section_id = reader.read_u8()?;
-        assert_eq!(section_id, 3);
+        expect_value("section id", 3, section_id)?;
         let rotation = reader.read_f32::<BigEndian>()?;
         let acceleration = reader.read_f32::<BigEndian>()?;
 
         let section_id = reader.read_u8()?;
-        assert_eq!(section_id, 4);
+        expect_value("section id", 4, section_id)?;
         let angles = read_3_floats(reader)?;
 
         let section_id = reader.read_u8()?;
-        assert_eq!(section_id, 5);
+        expect_value("section id", 5, section_id)?;
         let unk = reader.read_u32::<BigEndian>()?;
 
         if camera_type == 3 {
             let section_id = reader.read_u8()?;
-            assert_eq!(section_id, 6);
+            expect_value("section id", 6, section_id)?;
             let distances = read_2_floats(reader)?;
 
             let end_of_camera = reader.read_u8()?;
-            assert_eq!(end_of_camera, 0);
+            expect_value("end of camera", 0, end_of_camera)?;
 
             Ok(Camera::Zoom {
                 id,
@@ -103,7 +110,7 @@ impl Camera {
             })
         } else {
             let end_of_camera = reader.read_u8()?;
-            assert_eq!(end_of_camera, 0);
+            expect_value("end of camera", 0, end_of_camera)?;
 
             Ok(Camera::Pivot {
                 id,
@@ -119,13 +126,13 @@ impl Camera {
 
     fn read_static_camera<R: Read>(reader: &mut R, id: u16) -> Result<Self, Box<dyn Error>> {
         let section_id = reader.read_u8()?;
-        assert_eq!(section_id, 1);
+        expect_value("section id", 1, section_id)?;
         let position = read_3_floats(reader)?;
         let section_id = reader.read_u8()?;
-        assert_eq!(section_id, 2);
+        expect_value("section id", 2, section_id)?;
         let angles = read_3_floats(reader)?;
         let end_of_camera = reader.read_u8()?;
-        assert_eq!(end_of_camera, 0);
+        expect_value("end of camera", 0, end_of_camera)?;
 
         Ok(Camera::Static {
             id,
@@ -136,10 +143,10 @@ impl Camera {
 
     fn read_random_camera<R: Read>(reader: &mut R, id: u16) -> Result<Self, Box<dyn Error>> {
         let section_id = reader.read_u8()?;
-        assert_eq!(section_id, 1);
+        expect_value("section id", 1, section_id)?;
         let unk = reader.read_u32::<BigEndian>()?;
         let end_of_camera = reader.read_u8()?;
-        assert_eq!(end_of_camera, 0);
+        expect_value("end of camera", 0, end_of_camera)?;
 
         Ok(Camera::Random { id, unk })
     }
@@ -1,11 +1,16 @@
 use super::Prop1;
 use super::Prop2;
+use crate::error::RandoError;
+use crate::error::expect_value;
 use byteorder::ReadBytesExt;
 use byteorder::WriteBytesExt;
+use serde::Deserialize;
+use serde::Serialize;
 use std::error::Error;
 use std::io::Read;
 use std::io::Write;
 
+#[derive(Serialize, Deserialize)]
 pub struct Cube {
     pub x: i32,
     pub y: i32,
@@ -29,7 +34,7 @@ impl Cube {
             },
             3 => {
                 let subheader = reader.read_u8()?;
-                assert_eq!(subheader, 10);
+                expect_value("cube subheader", 10, subheader)?;
 
                 let mut props_1 = vec![];
                 let mut props_2 = vec![];
@@ -42,13 +47,13 @@ impl Cube {
                     }
                     list_type = reader.read_u8()?;
                 }
-                assert_eq!(list_type, 8);
+                expect_value("props 2 count marker", 8, list_type)?;
 
                 let prop_2_count = reader.read_u8()?;
 
                 if prop_2_count > 0 {
                     let list_type = reader.read_u8()?;
-                    assert_eq!(list_type, 9);
+                    expect_value("props 2 list marker", 9, list_type)?;
 
                     for _ in 0..prop_2_count {
                         props_2.push(Prop2::new(reader)?);
@@ -56,7 +61,7 @@ impl Cube {
                 }
 
                 let end = reader.read_u8()?;
-                assert_eq!(end, 1);
+                expect_value("end of cube", 1, end)?;
 
                 Cube {
                     x: 0,
@@ -67,7 +72,7 @@ impl Cube {
                     missing: false,
                 }
             }
-            _ => unimplemented!(),
+            _ => return Err(RandoError::unexpected("cube header", "1 or 3", id).into()),
         })
     }
 
@@ -81,7 +86,7 @@ impl Cube {
         writer.write_u8(10)?;
 
         writer.write_u8(self.props_1.len() as u8)?;
-        if self.props_1.len() > 0 {
+        if !self.props_1.is_empty() {
             writer.write_u8(11)?;
             for p1 in &self.props_1 {
                 p1.write(writer)?;
@@ -90,7 +95,7 @@ impl Cube {
         writer.write_u8(8)?;
 
         writer.write_u8(self.props_2.len() as u8)?;
-        if self.props_2.len() > 0 {
+        if !self.props_2.is_empty() {
             writer.write_u8(9)?;
             for p2 in &self.props_2 {
                 p2.write(writer)?;
@@ -1,11 +1,15 @@
+use crate::error::expect_value;
 use crate::utils::*;
 use byteorder::BigEndian;
 use byteorder::ReadBytesExt;
 use byteorder::WriteBytesExt;
+use serde::Deserialize;
+use serde::Serialize;
 use std::error::Error;
 use std::io::Read;
 use std::io::Write;
 
+#[derive(Serialize, Deserialize)]
 pub struct Lighting {
     position: Vector3<f32>,
     unk: Vector2<f32>,
@@ -17,15 +21,15 @@ pub struct Lighting {
 impl Lighting {
     pub fn new<R: Read>(reader: &mut R) -> Result<Self, Box<dyn Error>> {
         let section_id = reader.read_u8()?;
-        assert_eq!(section_id, 2);
+        expect_value("section id", 2, section_id)?;
         let position = read_3_floats(reader)?;
 
         let section_id = reader.read_u8()?;
-        assert_eq!(section_id, 3);
+        expect_value("section id", 3, section_id)?;
         let unk = read_2_floats(reader)?;
 
         let section_id = reader.read_u8()?;
-        assert_eq!(section_id, 4);
+        expect_value("section id", 4, section_id)?;
         let colours = read_3_u32(reader)?;
 
         Ok(Self {
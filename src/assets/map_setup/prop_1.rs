@@ -4,12 +4,14 @@ use crate::utils::*;
 use byteorder::BigEndian;
 use byteorder::ReadBytesExt;
 use byteorder::WriteBytesExt;
+use serde::Deserialize;
+use serde::Serialize;
 use std::error::Error;
 use std::io::Read;
 use std::io::Write;
 
 #[repr(u8)]
-#[derive(Copy, Clone, PartialEq, Debug)]
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub enum Category {
     WarpOrTrigger(WarpOrTriggerId),
     CameraController(u16),
@@ -21,9 +23,9 @@ pub enum Category {
     Unknown(u8, u16),
 }
 
-impl Into<u8> for Category {
-    fn into(self) -> u8 {
-        match self {
+impl From<Category> for u8 {
+    fn from(category: Category) -> Self {
+        match category {
             Category::WarpOrTrigger(_) => 3,
             Category::CameraController(_) => 4,
             Category::Actor(_) => 6,
@@ -51,7 +53,7 @@ impl std::fmt::Display for Category {
     }
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
 pub struct Prop1 {
     pub position: Vector3<i16>,
     pub selector_or_radius: u16,
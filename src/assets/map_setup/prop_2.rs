@@ -1,13 +1,16 @@
 use crate::enums::SpritePropId;
+use crate::error::RandoError;
 use crate::utils::*;
 use byteorder::BigEndian;
 use byteorder::ReadBytesExt;
 use byteorder::WriteBytesExt;
+use serde::Deserialize;
+use serde::Serialize;
 use std::error::Error;
 use std::io::Read;
 use std::io::Write;
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Serialize, Deserialize)]
 pub enum Prop2 {
     Actor {
         flags: u32,
@@ -28,8 +31,15 @@ impl Prop2 {
         let flags = reader.read_u32::<BigEndian>()?;
         let position = read_3_i16(reader)?;
         let ending = reader.read_u16::<BigEndian>()?;
-        assert_ne!(ending & 0b11, 1); // !is_3d and is_actor
-        assert_ne!(ending & 0b11, 3); // is_3d and is_actor
+        // !is_3d and is_actor, or is_3d and is_actor
+        if ending & 0b01 != 0 {
+            return Err(RandoError::unexpected(
+                "prop 2 kind",
+                "a sprite or a 3D actor",
+                format!("0x{ending:04X}"),
+            )
+            .into());
+        }
 
         if (ending & 0b10) != 0 {
             let scale = (ending >> 8) as u8;
@@ -1,4 +1,9 @@
+use crate::error::RandoError;
+use crate::error::expect_value;
+use byteorder::BigEndian;
+use byteorder::ReadBytesExt;
 use std::error::Error;
+use std::io::Cursor;
 use std::io::Read;
 use std::io::Write;
 
@@ -9,12 +14,86 @@ pub struct Midi {
 impl Midi {
     pub fn new<R: Read>(reader: &mut R, length: usize) -> Result<Self, Box<dyn Error>> {
         let mut buffer = vec![0u8; length];
-        reader.read(&mut buffer)?;
+        reader.read_exact(&mut buffer)?;
+        Ok(Self { buffer })
+    }
+
+    /// A MIDI file made outside of the game, refused if it isn't a valid Standard MIDI File.
+    pub fn from_bytes(buffer: Vec<u8>) -> Result<Self, Box<dyn Error>> {
+        check_header(&buffer)?;
         Ok(Self { buffer })
     }
 
     pub fn write<W: Write>(&self, writer: &mut W) -> Result<(), Box<dyn Error>> {
-        writer.write(&self.buffer)?;
+        writer.write_all(&self.buffer)?;
         Ok(())
     }
 }
+
+// the "MThd" chunk, then as many "MTrk" as it says, each ending with "end of track"
+fn check_header(buffer: &[u8]) -> Result<(), Box<dyn Error>> {
+    let mut reader = Cursor::new(buffer);
+
+    let mut magic = [0u8; 4];
+    reader.read_exact(&mut magic)?;
+    if &magic != b"MThd" {
+        return Err(
+            RandoError::unexpected("MIDI header", "MThd", String::from_utf8_lossy(&magic)).into(),
+        );
+    }
+
+    expect_value("MIDI header length", 6, reader.read_u32::<BigEndian>()?)?;
+
+    let format = reader.read_u16::<BigEndian>()?;
+    let tracks = reader.read_u16::<BigEndian>()?;
+    let division = reader.read_u16::<BigEndian>()?;
+
+    if format > 1 {
+        return Err(RandoError::unexpected("MIDI format", "0 or 1", format).into());
+    }
+    if tracks == 0 || (format == 0 && tracks != 1) {
+        let expected = if format == 0 { "1" } else { "at least 1" };
+        return Err(RandoError::unexpected("MIDI track count", expected, tracks).into());
+    }
+    if division == 0 {
+        return Err(RandoError::unexpected("MIDI division", "at least 1", division).into());
+    }
+
+    // other chunks are allowed, and skipped
+    let mut found = 0;
+    while (reader.position() as usize) < buffer.len() {
+        let mut chunk = [0u8; 4];
+        reader.read_exact(&mut chunk)?;
+        let length = reader.read_u32::<BigEndian>()? as usize;
+
+        let start = reader.position() as usize;
+        let Some(data) = buffer.get(start..start + length) else {
+            return Err(RandoError::unexpected(
+                "MIDI chunk length",
+                format!("at most {}", buffer.len() - start),
+                length,
+            )
+            .into());
+        };
+
+        if &chunk == b"MTrk" {
+            if !data.ends_with(&[0xFF, 0x2F, 0x00]) {
+                return Err(RandoError::unexpected(
+                    "end of MIDI track",
+                    "FF 2F 00",
+                    format!("{:02X?}", &data[data.len().saturating_sub(3)..]),
+                )
+                .into());
+            }
+            found += 1;
+        }
+
+        reader.set_position((start + length) as u64);
+    }
+
+    if found != tracks {
+        return Err(RandoError::unexpected("MIDI track count", tracks, found).into());
+    }
+
+    Ok(())
+}
@@ -1,192 +1,305 @@
-// use crate::utils::align_reader;
-// use crate::utils::read_2_i16;
-// use crate::utils::read_3_i16;
-// use byteorder::BigEndian;
-// use byteorder::ReadBytesExt;
-// use byteorder::WriteBytesExt;
-// use geometry_layout::GeometryLayout;
+use crate::error::RandoError;
+use crate::error::expect_value;
+use byteorder::BigEndian;
+use byteorder::ReadBytesExt;
+use byteorder::WriteBytesExt;
 use std::error::Error;
+use std::io::Cursor;
 use std::io::Read;
-use std::io::Seek;
 use std::io::Write;
 
-pub mod geometry_layout;
+mod animation_setup;
+pub use animation_setup::AnimationSetup;
+pub use animation_setup::Bone;
+mod collision_setup;
+pub use collision_setup::CollisionGeo;
+pub use collision_setup::CollisionSetup;
+pub use collision_setup::CollisionTriangle;
+mod display_list;
+pub use display_list::DisplayList;
+pub use display_list::DrawnTriangle;
+mod geometry_layout;
+pub use geometry_layout::GeometryLayout;
+mod texture_setup;
+pub use texture_setup::ModelTexture;
+pub use texture_setup::TextureSetup;
+mod vertex_store;
+pub use vertex_store::Vertex;
+pub use vertex_store::VertexStore;
+
+const MODEL_MAGIC: u32 = 0xB;
+const HEADER_SIZE: usize = 0x38;
+
+/// The sections a model can have, in the order of their offset in the header.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum SectionId {
+    GeometryLayout,
+    TextureSetup,
+    DisplayList,
+    VertexStore,
+    Unknown14,
+    AnimationSetup,
+    CollisionSetup,
+    Unknown20, // effects setup end?
+    EffectsSetup,
+    Unknown28,
+    AnimatedTextures,
+}
+
+const SECTION_IDS: [SectionId; 11] = [
+    SectionId::GeometryLayout,
+    SectionId::TextureSetup,
+    SectionId::DisplayList,
+    SectionId::VertexStore,
+    SectionId::Unknown14,
+    SectionId::AnimationSetup,
+    SectionId::CollisionSetup,
+    SectionId::Unknown20,
+    SectionId::EffectsSetup,
+    SectionId::Unknown28,
+    SectionId::AnimatedTextures,
+];
 
 pub struct Model {
-    buffer: Vec<u8>,
+    pub geo_type: u16,
+    pub tri_count: u16,
+    pub vertex_count: u16,
+    unk34: f32,
+    /// In the order of the file, each one up to the next.
+    pub sections: Vec<Section>,
+}
+
+pub struct Section {
+    pub id: SectionId,
+    pub data: SectionData,
+    // alignment up to the next section, as the game has it
+    padding: Vec<u8>,
+}
+
+pub enum SectionData {
+    TextureSetup(TextureSetup),
+    DisplayList(DisplayList),
+    VertexStore(VertexStore),
+    AnimationSetup(AnimationSetup),
+    CollisionSetup(CollisionSetup),
+    GeometryLayout(Vec<GeometryLayout>),
+    /// Not understood, or not what the reader expected: kept as the game has it.
+    Raw(Vec<u8>),
 }
 
 impl Model {
-    pub fn new<R: Read + Seek>(reader: &mut R, length: usize) -> Result<Self, Box<dyn Error>> {
-        // let start = reader.seek(std::io::SeekFrom::Current(0))?;
-
-        // println!("\n{start:#X}");
-
-        // let magic = reader.read_u32::<BigEndian>()?;
-        // assert_eq!(magic, 0xB);
-        // let geometry_layout_offset = reader.read_u32::<BigEndian>()?;
-        // let texture_setup_offset = reader.read_u16::<BigEndian>()?;
-        // assert_eq!(texture_setup_offset, 0x38);
-        // let geo_type = reader.read_u16::<BigEndian>()?;
-        // let display_list_setup_offset = reader.read_u32::<BigEndian>()?;
-        // let vertex_store_setup_offset = reader.read_u32::<BigEndian>()?;
-        // let unknown = reader.read_u32::<BigEndian>()?;
-        // let animation_setup_offset = reader.read_u32::<BigEndian>()?;
-        // let collision_setup_offset = reader.read_u32::<BigEndian>()?;
-        // let effects_setup_end_address = reader.read_u32::<BigEndian>()?;
-        // let effects_setup = reader.read_u32::<BigEndian>()?;
-        // let unknown2 = reader.read_u32::<BigEndian>()?;
-        // let animated_textures_offset = reader.read_u32::<BigEndian>()?;
-        // let tri_count = reader.read_u16::<BigEndian>()?;
-        // let vert_count = reader.read_u16::<BigEndian>()?;
-        // let unknown3 = reader.read_f32::<BigEndian>()?;
-
-        // println!("geometry_layout_offset: {geometry_layout_offset:X}");
-        // println!("geo_type: {geo_type:X}");
-        // println!("display_list_setup_offset: {display_list_setup_offset:X}");
-        // println!("vertex_store_setup_offset: {vertex_store_setup_offset:X}");
-        // println!("unknown: {unknown:X}");
-        // println!("animation_setup_offset: {animation_setup_offset:X}");
-        // println!("collision_setup_offset: {collision_setup_offset:X}");
-        // println!("effects_setup_end_address: {effects_setup_end_address:X}");
-        // println!("effects_setup: {effects_setup:X}");
-        // println!("unknown2: {unknown2:X}");
-        // println!("animated_textures_offset: {animated_textures_offset:X}");
-        // println!("tri_count: {tri_count}");
-        // println!("vert_count: {vert_count}");
-        // println!("unknown3: {unknown3}");
-
-        // // texture setup
-        // let pos = reader.seek(std::io::SeekFrom::Current(0))?;
-        // assert_eq!(start + texture_setup_offset as u64, pos);
-
-        // let size = reader.read_u32::<BigEndian>()?;
-        // let texture_count = reader.read_u16::<BigEndian>()?;
-        // let padding = reader.read_u16::<BigEndian>()?;
-        // assert_eq!(padding, 0);
-        // println!("{size:X}");
-        // println!("{texture_count} textures");
-        // assert_eq!(8 + 0x10 * texture_count as u32, size);
-
-        // for _ in 0..texture_count {
-        //     let offset = reader.read_u32::<BigEndian>()?;
-        //     let kind = reader.read_u16::<BigEndian>()?;
-        //     let padding = reader.read_u16::<BigEndian>()?;
-        //     assert_eq!(padding, 0);
-        //     let width = reader.read_u8()?;
-        //     let height = reader.read_u8()?;
-        //     let unk1 = reader.read_u16::<BigEndian>()?; // texture id?
-        //     let unk2 = reader.read_u32::<BigEndian>()?;
-        //     println!("{offset} for {kind} ({unk1}, {unk2}), {width} x {height}");
-        // }
-
-        // // display list setup
-        // let pos = reader.seek(std::io::SeekFrom::Current(0))?;
-        // assert_eq!(start + display_list_setup_offset as u64, pos);
-
-        // let command_count = reader.read_u32::<BigEndian>()?;
-        // println!("{command_count} command_count");
-        // let padding = reader.read_u32::<BigEndian>()?;
-        // if padding != 0 {
-        //     println!("{padding}!!! padding");
-        // }
-        // for _ in 0..command_count {
-        //     let _command = reader.read_u64::<BigEndian>()?;
-        // }
-
-        // // vertex store setup
-        // let pos = reader.seek(std::io::SeekFrom::Current(0))?;
-        // assert_eq!(start + vertex_store_setup_offset as u64, pos);
-
-        // let _min = read_3_i16(reader)?;
-        // let _max = read_3_i16(reader)?;
-        // let _centre = read_3_i16(reader)?;
-        // let _largest_distance_to_centre = reader.read_i16::<BigEndian>()?;
-        // let vertex_count = reader.read_u16::<BigEndian>()?;
-        // let _largest_distance_to_origin = reader.read_i16::<BigEndian>()?;
-        // assert_eq!(vert_count, vertex_count);
-
-        // for _ in 0..vertex_count {
-        //     let _part1 = reader.read_u64::<BigEndian>()?;
-        //     let _part2 = reader.read_u64::<BigEndian>()?;
-        // }
-
-        // if unknown != 0 {
-        //     align_reader(reader)?;
-        //     let pos = reader.seek(std::io::SeekFrom::Current(0))?;
-        //     println!("unknown {:X}, {unknown:X}", pos - start);
-        //     assert_eq!(start + unknown as u64, pos);
-
-        //     let mut buffer = vec![0u8; 0x18];
-        //     reader.read(&mut buffer)?;
-        //     println!("{buffer:?}");
-        // }
-
-        // // collision setup
-        // if collision_setup_offset != 0 {
-        //     align_reader(reader)?;
-        //     let pos = reader.seek(std::io::SeekFrom::Current(0))?;
-        //     println!(
-        //         "collision_setup_offset {:X}, {collision_setup_offset:X}",
-        //         pos - start
-        //     );
-        //     assert_eq!(start + collision_setup_offset as u64, pos);
-
-        //     let _min = read_3_i16(reader)?;
-        //     let _max = read_3_i16(reader)?;
-        //     let _stride = read_2_i16(reader)?;
-
-        //     let geo_count = reader.read_u16::<BigEndian>()?;
-        //     let _scale = reader.read_u16::<BigEndian>()?;
-        //     let tri_count = reader.read_u16::<BigEndian>()?;
-        //     let padding = reader.read_u16::<BigEndian>()?;
-        //     assert_eq!(padding, 0);
-
-        //     for _ in 0..geo_count {
-        //         let _start_tri_index = reader.read_u16::<BigEndian>()?;
-        //         let _tri_count = reader.read_u16::<BigEndian>()?;
-        //     }
-
-        //     for _ in 0..tri_count {
-        //         let _vtx_indx_1 = reader.read_u16::<BigEndian>()?;
-        //         let _vtx_indx_2 = reader.read_u16::<BigEndian>()?;
-        //         let _vtx_indx_3 = reader.read_u16::<BigEndian>()?;
-        //         let _unk = reader.read_u16::<BigEndian>()?;
-        //         let _flags = reader.read_u32::<BigEndian>()?;
-        //     }
-        // }
-
-        // // geometry layout
-        // let before_pos = reader.seek(std::io::SeekFrom::Current(0))?;
-        // align_reader(reader)?;
-        // let pos = reader.seek(std::io::SeekFrom::Current(0))?;
-        // println!(
-        //     "geometry_layout_offset {:X}, {:X}, {geometry_layout_offset:X}",
-        //     before_pos - start,
-        //     pos - start
-        // );
-        // assert_eq!(start + geometry_layout_offset as u64, pos);
-
-        // loop {
-        //     let pos = reader.seek(std::io::SeekFrom::Current(0))?;
-        //     println!("- {pos:X}");
-
-        //     let (_geo_layout, last) = GeometryLayout::new(reader)?;
-        //     println!("layout is last? {last}");
-
-        //     if last {
-        //         break;
-        //     }
-        // }
-
-        // todo!();
+    /// Errors out if the header doesn't look like one, sections that can't be read are kept raw.
+    pub fn new<R: Read>(reader: &mut R, length: usize) -> Result<Self, Box<dyn Error>> {
         let mut buffer = vec![0u8; length];
-        reader.read(&mut buffer)?;
-        Ok(Self { buffer })
+        reader.read_exact(&mut buffer)?;
+        let mut reader = Cursor::new(&buffer[..]);
+
+        let magic = reader.read_u32::<BigEndian>()?;
+        expect_value("model magic", MODEL_MAGIC, magic)?;
+        let mut offsets = vec![];
+        offsets.push(reader.read_u32::<BigEndian>()?);
+        offsets.push(reader.read_u16::<BigEndian>()? as u32);
+        let geo_type = reader.read_u16::<BigEndian>()?;
+        for _ in 2..SECTION_IDS.len() {
+            offsets.push(reader.read_u32::<BigEndian>()?);
+        }
+        let tri_count = reader.read_u16::<BigEndian>()?;
+        let vertex_count = reader.read_u16::<BigEndian>()?;
+        let unk34 = reader.read_f32::<BigEndian>()?;
+
+        // 0 is no section
+        let mut starts = SECTION_IDS
+            .iter()
+            .zip(offsets)
+            .filter(|(_, offset)| *offset != 0)
+            .map(|(id, offset)| (*id, offset as usize))
+            .collect::<Vec<_>>();
+        starts.sort_by_key(|(_, offset)| *offset);
+
+        // the header has to be followed by a section, or nothing would keep the bytes after it
+        let first = starts.first().map(|(_, offset)| *offset).unwrap_or(length);
+        if first != HEADER_SIZE {
+            return Err(RandoError::unexpected(
+                "first section offset",
+                format!("0x{HEADER_SIZE:X}"),
+                format!("0x{first:X}"),
+            )
+            .into());
+        }
+        if let Some((id, offset)) = starts.last()
+            && *offset > length
+        {
+            return Err(RandoError::unexpected(
+                "section offset",
+                format!("at most 0x{length:X}"),
+                format!("0x{offset:X} ({id:?})"),
+            )
+            .into());
+        }
+
+        let mut sections = vec![];
+        for (i, (id, start)) in starts.iter().enumerate() {
+            let end = starts.get(i + 1).map(|(_, next)| *next).unwrap_or(length);
+            sections.push(Section::new(*id, &buffer[*start..end]));
+        }
+
+        Ok(Self {
+            geo_type,
+            tri_count,
+            vertex_count,
+            unk34,
+            sections,
+        })
     }
 
     pub fn write<W: Write>(&self, writer: &mut W) -> Result<(), Box<dyn Error>> {
-        writer.write(&self.buffer)?;
+        let mut body = vec![];
+        let mut offsets = [0u32; SECTION_IDS.len()];
+        for section in &self.sections {
+            let index = SECTION_IDS.iter().position(|id| *id == section.id).unwrap();
+            offsets[index] = (HEADER_SIZE + body.len()) as u32;
+            section.write(&mut body)?;
+        }
+
+        let texture_setup = offsets[1];
+        if texture_setup > u16::MAX as u32 {
+            return Err(RandoError::unexpected(
+                "texture setup offset",
+                "at most 0xFFFF",
+                format!("0x{texture_setup:X}"),
+            )
+            .into());
+        }
+
+        writer.write_u32::<BigEndian>(MODEL_MAGIC)?;
+        writer.write_u32::<BigEndian>(offsets[0])?;
+        writer.write_u16::<BigEndian>(texture_setup as u16)?;
+        writer.write_u16::<BigEndian>(self.geo_type)?;
+        for offset in &offsets[2..] {
+            writer.write_u32::<BigEndian>(*offset)?;
+        }
+        writer.write_u16::<BigEndian>(self.tri_count)?;
+        writer.write_u16::<BigEndian>(self.vertex_count)?;
+        writer.write_f32::<BigEndian>(self.unk34)?;
+        writer.write_all(&body)?;
+
         Ok(())
     }
+
+    pub fn section(&self, id: SectionId) -> Option<&SectionData> {
+        self.sections.iter().find(|s| s.id == id).map(|s| &s.data)
+    }
+
+    pub fn section_mut(&mut self, id: SectionId) -> Option<&mut SectionData> {
+        self.sections
+            .iter_mut()
+            .find(|s| s.id == id)
+            .map(|s| &mut s.data)
+    }
+
+    pub fn texture_setup(&self) -> Option<&TextureSetup> {
+        match self.section(SectionId::TextureSetup) {
+            Some(SectionData::TextureSetup(data)) => Some(data),
+            _ => None,
+        }
+    }
+
+    pub fn display_list(&self) -> Option<&DisplayList> {
+        match self.section(SectionId::DisplayList) {
+            Some(SectionData::DisplayList(data)) => Some(data),
+            _ => None,
+        }
+    }
+
+    pub fn vertex_store(&self) -> Option<&VertexStore> {
+        match self.section(SectionId::VertexStore) {
+            Some(SectionData::VertexStore(data)) => Some(data),
+            _ => None,
+        }
+    }
+
+    pub fn animation_setup(&self) -> Option<&AnimationSetup> {
+        match self.section(SectionId::AnimationSetup) {
+            Some(SectionData::AnimationSetup(data)) => Some(data),
+            _ => None,
+        }
+    }
+
+    pub fn collision_setup(&self) -> Option<&CollisionSetup> {
+        match self.section(SectionId::CollisionSetup) {
+            Some(SectionData::CollisionSetup(data)) => Some(data),
+            _ => None,
+        }
+    }
+
+    pub fn geometry_layout(&self) -> Option<&[GeometryLayout]> {
+        match self.section(SectionId::GeometryLayout) {
+            Some(SectionData::GeometryLayout(data)) => Some(data),
+            _ => None,
+        }
+    }
+}
+
+impl Section {
+    // a section is only kept parsed if it's written back the same (the alignment aside)
+    fn new(id: SectionId, bytes: &[u8]) -> Self {
+        let raw = || Self {
+            id,
+            data: SectionData::Raw(bytes.to_vec()),
+            padding: vec![],
+        };
+
+        let Ok(data) = SectionData::new(id, bytes) else {
+            return raw();
+        };
+        let mut written = vec![];
+        if data.write(&mut written).is_err()
+            || !bytes.starts_with(&written)
+            || bytes.len() - written.len() >= 8
+        {
+            return raw();
+        }
+
+        Self {
+            id,
+            data,
+            padding: bytes[written.len()..].to_vec(),
+        }
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<(), Box<dyn Error>> {
+        self.data.write(writer)?;
+        writer.write_all(&self.padding)?;
+        Ok(())
+    }
+}
+
+impl SectionData {
+    fn new(id: SectionId, bytes: &[u8]) -> Result<Self, Box<dyn Error>> {
+        let reader = &mut Cursor::new(bytes);
+        Ok(match id {
+            SectionId::TextureSetup => Self::TextureSetup(TextureSetup::new(reader)?),
+            SectionId::DisplayList => Self::DisplayList(DisplayList::new(reader)?),
+            SectionId::VertexStore => Self::VertexStore(VertexStore::new(reader)?),
+            SectionId::AnimationSetup => Self::AnimationSetup(AnimationSetup::new(reader)?),
+            SectionId::CollisionSetup => Self::CollisionSetup(CollisionSetup::new(reader)?),
+            SectionId::GeometryLayout => Self::GeometryLayout(GeometryLayout::read_list(bytes, 0)?),
+            _ => Self::Raw(bytes.to_vec()),
+        })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<(), Box<dyn Error>> {
+        match self {
+            Self::TextureSetup(data) => data.write(writer),
+            Self::DisplayList(data) => data.write(writer),
+            Self::VertexStore(data) => data.write(writer),
+            Self::AnimationSetup(data) => data.write(writer),
+            Self::CollisionSetup(data) => data.write(writer),
+            Self::GeometryLayout(data) => GeometryLayout::write_list(data, writer),
+            Self::Raw(bytes) => {
+                writer.write_all(bytes)?;
+                Ok(())
+            }
+        }
+    }
 }
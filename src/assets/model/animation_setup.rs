@@ -0,0 +1,60 @@
+use crate::utils::Vector3;
+use crate::utils::read_3_floats;
+use crate::utils::write_3_floats;
+use byteorder::BigEndian;
+use byteorder::ReadBytesExt;
+use byteorder::WriteBytesExt;
+use std::error::Error;
+use std::io::Read;
+use std::io::Write;
+
+/// The bones the animations move, laid out like in the N64 version.
+pub struct AnimationSetup {
+    unk00: f32,
+    unk06: u16,
+    pub bones: Vec<Bone>,
+}
+
+pub struct Bone {
+    pub position: Vector3<f32>,
+    pub id: i16,
+    /// -1 for the root.
+    pub parent: i16,
+}
+
+impl AnimationSetup {
+    pub fn new<R: Read>(reader: &mut R) -> Result<Self, Box<dyn Error>> {
+        let unk00 = reader.read_f32::<BigEndian>()?;
+        let bone_count = reader.read_u16::<BigEndian>()?;
+        let unk06 = reader.read_u16::<BigEndian>()?;
+
+        let mut bones = vec![];
+        for _ in 0..bone_count {
+            bones.push(Bone {
+                position: read_3_floats(reader)?,
+                id: reader.read_i16::<BigEndian>()?,
+                parent: reader.read_i16::<BigEndian>()?,
+            });
+        }
+
+        Ok(Self {
+            unk00,
+            unk06,
+            bones,
+        })
+    }
+
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<(), Box<dyn Error>> {
+        writer.write_f32::<BigEndian>(self.unk00)?;
+        writer.write_u16::<BigEndian>(self.bones.len() as u16)?;
+        writer.write_u16::<BigEndian>(self.unk06)?;
+
+        for bone in &self.bones {
+            write_3_floats(writer, &bone.position)?;
+            writer.write_i16::<BigEndian>(bone.id)?;
+            writer.write_i16::<BigEndian>(bone.parent)?;
+        }
+
+        Ok(())
+    }
+}
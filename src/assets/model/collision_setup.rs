@@ -0,0 +1,100 @@
+use crate::utils::Vector2;
+use crate::utils::Vector3;
+use crate::utils::read_2_i16;
+use crate::utils::read_3_i16;
+use crate::utils::read_3_u16;
+use crate::utils::write_2_i16;
+use crate::utils::write_3_i16;
+use crate::utils::write_3_u16;
+use byteorder::BigEndian;
+use byteorder::ReadBytesExt;
+use byteorder::WriteBytesExt;
+use std::error::Error;
+use std::io::Read;
+use std::io::Write;
+
+/// The triangles Banjo walks on, sorted in a grid of `stride` cells.
+pub struct CollisionSetup {
+    pub min: Vector3<i16>,
+    pub max: Vector3<i16>,
+    pub stride: Vector2<i16>,
+    pub scale: u16,
+    unk16: u16,
+    pub geos: Vec<CollisionGeo>,
+    pub triangles: Vec<CollisionTriangle>,
+}
+
+/// A cell of the grid, its triangles are `triangles[start_tri_index..][..tri_count]`.
+pub struct CollisionGeo {
+    pub start_tri_index: u16,
+    pub tri_count: u16,
+}
+
+pub struct CollisionTriangle {
+    /// Indices in the vertex store.
+    pub vertices: Vector3<u16>,
+    unk06: u16,
+    pub flags: u32,
+}
+
+impl CollisionSetup {
+    pub fn new<R: Read>(reader: &mut R) -> Result<Self, Box<dyn Error>> {
+        let min = read_3_i16(reader)?;
+        let max = read_3_i16(reader)?;
+        let stride = read_2_i16(reader)?;
+        let geo_count = reader.read_u16::<BigEndian>()?;
+        let scale = reader.read_u16::<BigEndian>()?;
+        let tri_count = reader.read_u16::<BigEndian>()?;
+        let unk16 = reader.read_u16::<BigEndian>()?;
+
+        let mut geos = vec![];
+        for _ in 0..geo_count {
+            geos.push(CollisionGeo {
+                start_tri_index: reader.read_u16::<BigEndian>()?,
+                tri_count: reader.read_u16::<BigEndian>()?,
+            });
+        }
+
+        let mut triangles = vec![];
+        for _ in 0..tri_count {
+            triangles.push(CollisionTriangle {
+                vertices: read_3_u16(reader)?,
+                unk06: reader.read_u16::<BigEndian>()?,
+                flags: reader.read_u32::<BigEndian>()?,
+            });
+        }
+
+        Ok(Self {
+            min,
+            max,
+            stride,
+            scale,
+            unk16,
+            geos,
+            triangles,
+        })
+    }
+
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<(), Box<dyn Error>> {
+        write_3_i16(writer, &self.min)?;
+        write_3_i16(writer, &self.max)?;
+        write_2_i16(writer, &self.stride)?;
+        writer.write_u16::<BigEndian>(self.geos.len() as u16)?;
+        writer.write_u16::<BigEndian>(self.scale)?;
+        writer.write_u16::<BigEndian>(self.triangles.len() as u16)?;
+        writer.write_u16::<BigEndian>(self.unk16)?;
+
+        for geo in &self.geos {
+            writer.write_u16::<BigEndian>(geo.start_tri_index)?;
+            writer.write_u16::<BigEndian>(geo.tri_count)?;
+        }
+
+        for triangle in &self.triangles {
+            write_3_u16(writer, &triangle.vertices)?;
+            writer.write_u16::<BigEndian>(triangle.unk06)?;
+            writer.write_u32::<BigEndian>(triangle.flags)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,99 @@
+use byteorder::BigEndian;
+use byteorder::ReadBytesExt;
+use byteorder::WriteBytesExt;
+use std::error::Error;
+use std::io::Read;
+use std::io::Write;
+
+/// The N64 graphics commands (F3DEX) drawing the model, 8 bytes each.
+pub struct DisplayList {
+    unk04: u32,
+    pub commands: Vec<u64>,
+}
+
+/// A triangle of the display list.
+pub struct DrawnTriangle {
+    /// Indices in the vertex store.
+    pub vertices: [usize; 3],
+    /// What the last `G_SETTIMG` pointed at, matches `ModelTexture::offset`.
+    pub texture: Option<u32>,
+}
+
+const G_VTX: u8 = 0x04;
+const G_TRI2: u8 = 0xB1;
+const G_TRI1: u8 = 0xBF;
+const G_SETTIMG: u8 = 0xFD;
+// vertices loaded at once
+const VERTEX_CACHE_SIZE: usize = 32;
+const VERTEX_SIZE: u32 = 0x10;
+
+impl DisplayList {
+    pub fn new<R: Read>(reader: &mut R) -> Result<Self, Box<dyn Error>> {
+        let command_count = reader.read_u32::<BigEndian>()?;
+        let unk04 = reader.read_u32::<BigEndian>()?;
+
+        let mut commands = vec![];
+        for _ in 0..command_count {
+            commands.push(reader.read_u64::<BigEndian>()?);
+        }
+
+        Ok(Self { unk04, commands })
+    }
+
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<(), Box<dyn Error>> {
+        writer.write_u32::<BigEndian>(self.commands.len() as u32)?;
+        writer.write_u32::<BigEndian>(self.unk04)?;
+        for command in &self.commands {
+            writer.write_u64::<BigEndian>(*command)?;
+        }
+
+        Ok(())
+    }
+
+    /// Every triangle drawn, the commands are run from the first to the last, whatever the
+    /// geometry layout picks (e.g. all the levels of detail are there).
+    pub fn triangles(&self) -> Vec<DrawnTriangle> {
+        let mut cache = [None; VERTEX_CACHE_SIZE];
+        let mut texture = None;
+        let mut triangles = vec![];
+
+        let mut draw = |cache: &[Option<usize>], indices: [u32; 3], texture| {
+            // indices are doubled in F3DEX
+            let vertices = indices.map(|i| cache.get(i as usize / 2).copied().flatten());
+            if let [Some(a), Some(b), Some(c)] = vertices {
+                triangles.push(DrawnTriangle {
+                    vertices: [a, b, c],
+                    texture,
+                });
+            }
+        };
+
+        for command in &self.commands {
+            let w0 = (command >> 32) as u32;
+            let w1 = *command as u32;
+
+            match (w0 >> 24) as u8 {
+                G_VTX => {
+                    let count = ((w0 >> 10) & 0x3F) as usize;
+                    let first = ((w0 >> 16) & 0xFF) as usize / 2;
+                    // the address is in the vertex segment
+                    let index = ((w1 & 0xFF_FFFF) / VERTEX_SIZE) as usize;
+                    for i in 0..count {
+                        if let Some(slot) = cache.get_mut(first + i) {
+                            *slot = Some(index + i);
+                        }
+                    }
+                }
+                G_TRI1 => draw(&cache, [w1 >> 16, w1 >> 8, w1].map(|i| i & 0xFF), texture),
+                G_TRI2 => {
+                    draw(&cache, [w0 >> 16, w0 >> 8, w0].map(|i| i & 0xFF), texture);
+                    draw(&cache, [w1 >> 16, w1 >> 8, w1].map(|i| i & 0xFF), texture);
+                }
+                G_SETTIMG => texture = Some(w1 & 0xFF_FFFF),
+                _ => {}
+            }
+        }
+
+        triangles
+    }
+}
@@ -1,135 +1,311 @@
+use crate::error::RandoError;
+use crate::error::expect_value;
+use crate::utils::Vector2;
+use crate::utils::Vector3;
 use crate::utils::read_2_floats;
 use crate::utils::read_3_floats;
+use crate::utils::write_2_floats;
+use crate::utils::write_3_floats;
 use byteorder::BigEndian;
 use byteorder::ReadBytesExt;
+use byteorder::WriteBytesExt;
 use std::error::Error;
-use std::io::Read;
+use std::io::Cursor;
 use std::io::Write;
 
+/// A node of the tree telling what parts of the display list are drawn, and how. Every node
+/// starts with its id and the offset of the next one of its list (0 for the last), children are
+/// lists too, at an offset from the start of their parent.
 pub enum GeometryLayout {
-    Unknown0,
-    Sort,
-    LoadDl,
-    Lod,
-    ReferencePoint,
-    Selector,
+    Unknown0 {
+        unk0a: u16, // child count? always 1
+        unk0c: Vector3<f32>,
+        children: Vec<GeometryLayout>,
+    },
+    Sort {
+        first_child: Vector3<f32>,
+        second_child: Vector3<f32>,
+        sort: u16,
+        first: Vec<GeometryLayout>,
+        second: Vec<GeometryLayout>,
+    },
+    LoadDl {
+        /// Index of the first command in the display list.
+        offset_in_dl_segment: u16,
+        tri_count: u16,
+    },
+    Lod {
+        distance: Vector2<f32>,
+        test: Vector3<f32>,
+        children: Vec<GeometryLayout>,
+    },
+    ReferencePoint {
+        ref_point: u16,
+        bone: u16,
+        offset: Vector3<f32>,
+    },
+    Selector {
+        selector_index: u16,
+        /// Empty when there's no child for that value.
+        children: Vec<Vec<GeometryLayout>>,
+    },
 }
 
+// nodes start on 8 bytes
+const ALIGNMENT: usize = 8;
+
 impl GeometryLayout {
-    pub fn new<R: Read>(reader: &mut R) -> Result<(Self, bool), Box<dyn Error>> {
-        let id = reader.read_u32::<BigEndian>()?;
-        println!("GEO {id}");
-        let length = reader.read_u32::<BigEndian>()?;
-
-        Ok(match id {
-            0 => {
-                assert_eq!(length, 0x28);
-                let len = reader.read_u16::<BigEndian>()?;
-                assert_eq!(len, 0x18);
-                let child_count = reader.read_u16::<BigEndian>()?;
-                assert_eq!(child_count, 1);
-                let _unk = read_3_floats(reader)?;
-
-                loop {
-                    let (_geo_layout, last) = GeometryLayout::new(reader)?;
-                    if last {
-                        break;
+    /// The list starting at `start` in `bytes`, children included.
+    pub fn read_list(bytes: &[u8], start: usize) -> Result<Vec<Self>, Box<dyn Error>> {
+        let mut list = vec![];
+        let mut start = start;
+
+        loop {
+            let mut reader = Cursor::new(bytes);
+            reader.set_position(start as u64);
+            let id = reader.read_u32::<BigEndian>()?;
+            let length = reader.read_u32::<BigEndian>()?;
+
+            list.push(match id {
+                0 => {
+                    let child = reader.read_u16::<BigEndian>()?;
+                    expect_value("geometry layout header length", 0x18, child)?;
+                    let unk0a = reader.read_u16::<BigEndian>()?;
+                    let unk0c = read_3_floats(&mut reader)?;
+
+                    Self::Unknown0 {
+                        unk0a,
+                        unk0c,
+                        children: read_child(bytes, start, child as u32)?,
                     }
                 }
+                1 => {
+                    let first_child = read_3_floats(&mut reader)?;
+                    let second_child = read_3_floats(&mut reader)?;
+                    let sort = reader.read_u16::<BigEndian>()?;
+                    if sort > 1 {
+                        return Err(RandoError::unexpected("sort", "0 or 1", sort).into());
+                    }
+                    let child1 = reader.read_u16::<BigEndian>()?;
+                    let child2 = reader.read_u32::<BigEndian>()?;
 
-                if length != 0 {
-                    let padding = reader.read_u32::<BigEndian>()?;
-                    assert_eq!(padding, 0);
+                    Self::Sort {
+                        first_child,
+                        second_child,
+                        sort,
+                        first: read_child(bytes, start, child1 as u32)?,
+                        second: read_child(bytes, start, child2)?,
+                    }
                 }
+                3 => Self::LoadDl {
+                    offset_in_dl_segment: reader.read_u16::<BigEndian>()?,
+                    tri_count: reader.read_u16::<BigEndian>()?,
+                },
+                8 => {
+                    let distance = read_2_floats(&mut reader)?;
+                    let test = read_3_floats(&mut reader)?;
+                    let child = reader.read_u32::<BigEndian>()?;
+                    expect_value("LOD child offset", 0x20, child)?;
 
-                (Self::Unknown0, length == 0)
-            }
-            1 => {
-                let _first_child = read_3_floats(reader)?;
-                let _second_child = read_3_floats(reader)?;
-                let sort = reader.read_u16::<BigEndian>()?;
-                assert!(sort == 1 || sort == 0);
-                let _child1 = reader.read_u16::<BigEndian>()?;
-                let child2 = reader.read_u32::<BigEndian>()?;
-                assert!(child2 < 0x10000);
-
-                let (_child_1, _last) = GeometryLayout::new(reader)?;
-                let (_child_2, _last) = GeometryLayout::new(reader)?;
-
-                (Self::Sort, length == 0)
-            }
-            3 => {
-                assert!(length == 0 || length == 0x10);
-                let _offset_in_dl_segment = reader.read_u16::<BigEndian>()?;
-                let _tri_count = reader.read_u16::<BigEndian>()?;
-
-                if length == 0x10 {
-                    let padding = reader.read_u32::<BigEndian>()?;
-                    assert_eq!(padding, 0);
+                    Self::Lod {
+                        distance,
+                        test,
+                        children: read_child(bytes, start, child)?,
+                    }
                 }
+                10 => Self::ReferencePoint {
+                    ref_point: reader.read_u16::<BigEndian>()?,
+                    bone: reader.read_u16::<BigEndian>()?,
+                    offset: read_3_floats(&mut reader)?,
+                },
+                12 => {
+                    let child_count = reader.read_u16::<BigEndian>()?;
+                    let selector_index = reader.read_u16::<BigEndian>()?;
 
-                (Self::LoadDl, length == 0)
-            }
-            8 => {
-                let _distance = read_2_floats(reader)?;
-                let _test = read_3_floats(reader)?;
-                let offset = reader.read_u32::<BigEndian>()?;
-                assert_eq!(offset, 0x20);
-
-                loop {
-                    let (_geo_layout, last) = GeometryLayout::new(reader)?;
-                    if last {
-                        break;
+                    let mut offsets = vec![];
+                    for _ in 0..child_count {
+                        offsets.push(reader.read_u32::<BigEndian>()?);
                     }
-                }
 
-                if length != 0 {
-                    let padding = reader.read_u32::<BigEndian>()?;
-                    assert_eq!(padding, 0);
+                    let mut children = vec![];
+                    for offset in offsets {
+                        children.push(read_child(bytes, start, offset)?);
+                    }
+
+                    Self::Selector {
+                        selector_index,
+                        children,
+                    }
                 }
+                _ => {
+                    return Err(RandoError::unexpected(
+                        "geometry layout id",
+                        "0, 1, 3, 8, 10 or 12",
+                        id,
+                    )
+                    .into());
+                }
+            });
 
-                (Self::Lod, length == 0)
+            if length == 0 {
+                break;
             }
-            10 => {
-                let _ref_point = reader.read_u16::<BigEndian>()?;
-                let _bone = reader.read_u16::<BigEndian>()?;
-                let _offset = read_3_floats(reader)?;
+            start += length as usize;
+        }
+
+        Ok(list)
+    }
 
-                (Self::ReferencePoint, length == 0)
+    /// Children right after their parent, every node but the last of a list padded to 8 bytes.
+    pub fn write_list<W: Write>(list: &[Self], writer: &mut W) -> Result<(), Box<dyn Error>> {
+        for (i, node) in list.iter().enumerate() {
+            let mut buffer = node.to_bytes()?;
+            if i + 1 < list.len() {
+                pad(&mut buffer);
+                let length = buffer.len() as u32;
+                buffer[4..8].copy_from_slice(&length.to_be_bytes());
             }
-            12 => {
-                let child_count = reader.read_u16::<BigEndian>()?;
-                let _selector_index = reader.read_u16::<BigEndian>()?;
+            writer.write_all(&buffer)?;
+        }
 
-                for _ in 0..child_count {
-                    let _ = reader.read_u32::<BigEndian>()?;
-                }
+        Ok(())
+    }
 
-                if (child_count % 2) == 0 {
-                    let padding = reader.read_u32::<BigEndian>()?;
-                    assert_eq!(padding, 0);
-                }
+    // the length is left at 0, the list sets it
+    fn to_bytes(&self) -> Result<Vec<u8>, Box<dyn Error>> {
+        let mut buffer = vec![];
 
-                for i in 0..child_count {
-                    let _ = GeometryLayout::new(reader)?;
-                    if i + 1 < child_count {
-                        let padding = reader.read_u32::<BigEndian>()?;
-                        assert_eq!(padding, 0);
-                    }
-                }
+        match self {
+            Self::Unknown0 {
+                unk0a,
+                unk0c,
+                children,
+            } => {
+                buffer.write_u32::<BigEndian>(0)?;
+                buffer.write_u32::<BigEndian>(0)?;
+                buffer.write_u16::<BigEndian>(0x18)?;
+                buffer.write_u16::<BigEndian>(*unk0a)?;
+                write_3_floats(&mut buffer, unk0c)?;
+                write_child(&mut buffer, children)?;
+            }
+            Self::Sort {
+                first_child,
+                second_child,
+                sort,
+                first,
+                second,
+            } => {
+                buffer.write_u32::<BigEndian>(1)?;
+                buffer.write_u32::<BigEndian>(0)?;
+                write_3_floats(&mut buffer, first_child)?;
+                write_3_floats(&mut buffer, second_child)?;
+                buffer.write_u16::<BigEndian>(*sort)?;
+                buffer.write_u16::<BigEndian>(0)?;
+                buffer.write_u32::<BigEndian>(0)?;
 
-                if length != 0 {
-                    let padding = reader.read_u32::<BigEndian>()?;
-                    assert_eq!(padding, 0);
+                let offset = write_child(&mut buffer, first)?;
+                if offset > u16::MAX as u32 {
+                    return Err(RandoError::unexpected(
+                        "first child offset",
+                        "at most 0xFFFF",
+                        format!("0x{offset:X}"),
+                    )
+                    .into());
                 }
-
-                (Self::Selector, length == 0)
+                buffer[0x22..0x24].copy_from_slice(&(offset as u16).to_be_bytes());
+                let offset = write_child(&mut buffer, second)?;
+                buffer[0x24..0x28].copy_from_slice(&offset.to_be_bytes());
+            }
+            Self::LoadDl {
+                offset_in_dl_segment,
+                tri_count,
+            } => {
+                buffer.write_u32::<BigEndian>(3)?;
+                buffer.write_u32::<BigEndian>(0)?;
+                buffer.write_u16::<BigEndian>(*offset_in_dl_segment)?;
+                buffer.write_u16::<BigEndian>(*tri_count)?;
+            }
+            Self::Lod {
+                distance,
+                test,
+                children,
+            } => {
+                buffer.write_u32::<BigEndian>(8)?;
+                buffer.write_u32::<BigEndian>(0)?;
+                write_2_floats(&mut buffer, distance)?;
+                write_3_floats(&mut buffer, test)?;
+                buffer.write_u32::<BigEndian>(0)?;
+                let offset = write_child(&mut buffer, children)?;
+                buffer[0x1C..0x20].copy_from_slice(&offset.to_be_bytes());
+            }
+            Self::ReferencePoint {
+                ref_point,
+                bone,
+                offset,
+            } => {
+                buffer.write_u32::<BigEndian>(10)?;
+                buffer.write_u32::<BigEndian>(0)?;
+                buffer.write_u16::<BigEndian>(*ref_point)?;
+                buffer.write_u16::<BigEndian>(*bone)?;
+                write_3_floats(&mut buffer, offset)?;
             }
-            _ => panic!("TODO GeometryLayout {id}"),
-        })
+            Self::Selector {
+                selector_index,
+                children,
+            } => {
+                buffer.write_u32::<BigEndian>(12)?;
+                buffer.write_u32::<BigEndian>(0)?;
+                buffer.write_u16::<BigEndian>(children.len() as u16)?;
+                buffer.write_u16::<BigEndian>(*selector_index)?;
+                for _ in children {
+                    buffer.write_u32::<BigEndian>(0)?;
+                }
+                for (i, child) in children.iter().enumerate() {
+                    let offset = write_child(&mut buffer, child)?;
+                    let position = 0xC + 4 * i;
+                    buffer[position..position + 4].copy_from_slice(&offset.to_be_bytes());
+                }
+            }
+        }
+
+        Ok(buffer)
+    }
+}
+
+// an offset of 0 is no child
+fn read_child(
+    bytes: &[u8],
+    parent: usize,
+    offset: u32,
+) -> Result<Vec<GeometryLayout>, Box<dyn Error>> {
+    if offset == 0 {
+        return Ok(vec![]);
+    }
+    // a child before the end of the id and length would be read forever
+    if offset < 8 {
+        return Err(
+            RandoError::unexpected("child offset", "at least 8", format!("0x{offset:X}")).into(),
+        );
     }
 
-    pub fn write<W: Write>(&self, _writer: &mut W) -> Result<(), Box<dyn Error>> {
-        Ok(())
+    GeometryLayout::read_list(bytes, parent + offset as usize)
+}
+
+// appends the child to its parent, returns its offset
+fn write_child(buffer: &mut Vec<u8>, child: &[GeometryLayout]) -> Result<u32, Box<dyn Error>> {
+    if child.is_empty() {
+        return Ok(0);
+    }
+
+    pad(buffer);
+    let offset = buffer.len() as u32;
+    GeometryLayout::write_list(child, buffer)?;
+
+    Ok(offset)
+}
+
+fn pad(buffer: &mut Vec<u8>) {
+    while !buffer.len().is_multiple_of(ALIGNMENT) {
+        buffer.push(0);
     }
 }
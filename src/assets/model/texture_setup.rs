@@ -0,0 +1,84 @@
+use crate::error::expect_value;
+use byteorder::BigEndian;
+use byteorder::ReadBytesExt;
+use byteorder::WriteBytesExt;
+use std::error::Error;
+use std::io::Read;
+use std::io::Write;
+
+const TEXTURE_SIZE: u32 = 0x10;
+
+/// The textures the display list draws with, the pixels aren't in the model.
+pub struct TextureSetup {
+    unk06: u16,
+    pub textures: Vec<ModelTexture>,
+}
+
+pub struct ModelTexture {
+    /// What `G_SETTIMG` points at in the display list.
+    pub offset: u32,
+    pub kind: u16,
+    unk06: u16,
+    pub width: u8,
+    pub height: u8,
+    unk0a: u16, // texture id?
+    unk0c: u32,
+}
+
+impl ModelTexture {
+    /// Index in db360.textures.cmp, a guess: the pixels aren't in the model anymore.
+    pub fn texture_id(&self) -> usize {
+        self.unk0a as usize
+    }
+}
+
+impl TextureSetup {
+    pub fn new<R: Read>(reader: &mut R) -> Result<Self, Box<dyn Error>> {
+        let size = reader.read_u32::<BigEndian>()?;
+        let texture_count = reader.read_u16::<BigEndian>()?;
+        let unk06 = reader.read_u16::<BigEndian>()?;
+        expect_value(
+            "texture setup size",
+            8 + TEXTURE_SIZE * texture_count as u32,
+            size,
+        )?;
+
+        let mut textures = vec![];
+        for _ in 0..texture_count {
+            textures.push(ModelTexture {
+                offset: reader.read_u32::<BigEndian>()?,
+                kind: reader.read_u16::<BigEndian>()?,
+                unk06: reader.read_u16::<BigEndian>()?,
+                width: reader.read_u8()?,
+                height: reader.read_u8()?,
+                unk0a: reader.read_u16::<BigEndian>()?,
+                unk0c: reader.read_u32::<BigEndian>()?,
+            });
+        }
+
+        Ok(Self { unk06, textures })
+    }
+
+    /// The texture `G_SETTIMG` points at, not the palette loaded before it.
+    pub fn find(&self, offset: u32) -> Option<&ModelTexture> {
+        self.textures.iter().find(|t| t.offset == offset)
+    }
+
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<(), Box<dyn Error>> {
+        writer.write_u32::<BigEndian>(8 + TEXTURE_SIZE * self.textures.len() as u32)?;
+        writer.write_u16::<BigEndian>(self.textures.len() as u16)?;
+        writer.write_u16::<BigEndian>(self.unk06)?;
+
+        for texture in &self.textures {
+            writer.write_u32::<BigEndian>(texture.offset)?;
+            writer.write_u16::<BigEndian>(texture.kind)?;
+            writer.write_u16::<BigEndian>(texture.unk06)?;
+            writer.write_u8(texture.width)?;
+            writer.write_u8(texture.height)?;
+            writer.write_u16::<BigEndian>(texture.unk0a)?;
+            writer.write_u32::<BigEndian>(texture.unk0c)?;
+        }
+
+        Ok(())
+    }
+}
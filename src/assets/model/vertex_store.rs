@@ -0,0 +1,86 @@
+use crate::utils::Vector2;
+use crate::utils::Vector3;
+use crate::utils::read_2_i16;
+use crate::utils::read_3_i16;
+use crate::utils::write_2_i16;
+use crate::utils::write_3_i16;
+use byteorder::BigEndian;
+use byteorder::ReadBytesExt;
+use byteorder::WriteBytesExt;
+use std::error::Error;
+use std::io::Read;
+use std::io::Write;
+
+pub struct VertexStore {
+    pub min: Vector3<i16>,
+    pub max: Vector3<i16>,
+    pub centre: Vector3<i16>,
+    pub largest_distance_to_centre: i16,
+    pub largest_distance_to_origin: i16,
+    pub vertices: Vec<Vertex>,
+}
+
+/// An N64 `Vtx`.
+#[derive(Clone)]
+pub struct Vertex {
+    pub position: Vector3<i16>,
+    flag: u16,
+    /// Texture coordinates, in 1/32 of a texel (10.5 fixed point).
+    pub uv: Vector2<i16>,
+    /// Or the normal, when the model is lit.
+    pub color: [u8; 4],
+}
+
+impl VertexStore {
+    pub fn new<R: Read>(reader: &mut R) -> Result<Self, Box<dyn Error>> {
+        let min = read_3_i16(reader)?;
+        let max = read_3_i16(reader)?;
+        let centre = read_3_i16(reader)?;
+        let largest_distance_to_centre = reader.read_i16::<BigEndian>()?;
+        let vertex_count = reader.read_u16::<BigEndian>()?;
+        let largest_distance_to_origin = reader.read_i16::<BigEndian>()?;
+
+        let mut vertices = vec![];
+        for _ in 0..vertex_count {
+            let position = read_3_i16(reader)?;
+            let flag = reader.read_u16::<BigEndian>()?;
+            let uv = read_2_i16(reader)?;
+            let mut color = [0u8; 4];
+            reader.read_exact(&mut color)?;
+
+            vertices.push(Vertex {
+                position,
+                flag,
+                uv,
+                color,
+            });
+        }
+
+        Ok(Self {
+            min,
+            max,
+            centre,
+            largest_distance_to_centre,
+            largest_distance_to_origin,
+            vertices,
+        })
+    }
+
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<(), Box<dyn Error>> {
+        write_3_i16(writer, &self.min)?;
+        write_3_i16(writer, &self.max)?;
+        write_3_i16(writer, &self.centre)?;
+        writer.write_i16::<BigEndian>(self.largest_distance_to_centre)?;
+        writer.write_u16::<BigEndian>(self.vertices.len() as u16)?;
+        writer.write_i16::<BigEndian>(self.largest_distance_to_origin)?;
+
+        for vertex in &self.vertices {
+            write_3_i16(writer, &vertex.position)?;
+            writer.write_u16::<BigEndian>(vertex.flag)?;
+            write_2_i16(writer, &vertex.uv)?;
+            writer.write_all(&vertex.color)?;
+        }
+
+        Ok(())
+    }
+}
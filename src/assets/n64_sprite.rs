@@ -0,0 +1,327 @@
+use crate::enum_builder;
+use crate::error::RandoError;
+use crate::error::expect_value;
+use crate::textures::Image;
+use byteorder::BigEndian;
+use byteorder::ReadBytesExt;
+use byteorder::WriteBytesExt;
+use std::error::Error;
+use std::io::Cursor;
+use std::io::Read;
+use std::io::Write;
+
+enum_builder! {
+    #[repr(u16)]
+    pub enum N64Format {
+        Ci4    = 0x0001,
+        Ci8    = 0x0004,
+        I4     = 0x0020,
+        I8     = 0x0040,
+        Rgba16 = 0x0400,
+        Rgba32 = 0x0800,
+    }
+}
+
+impl N64Format {
+    // bits per pixel, and the number of colors of the palette
+    fn bits(&self) -> Option<(usize, usize)> {
+        match self {
+            N64Format::Ci4 => Some((4, 16)),
+            N64Format::Ci8 => Some((8, 256)),
+            N64Format::I4 => Some((4, 0)),
+            N64Format::I8 => Some((8, 0)),
+            N64Format::Rgba16 => Some((16, 0)),
+            N64Format::Rgba32 => Some((32, 0)),
+            N64Format::Unknown(_) => None,
+        }
+    }
+}
+
+/// The sprites that kept the layout of the N64 version: the pixels are in the sprite, cut in
+/// chunks, instead of in db360.textures.cmp.
+pub struct N64Sprite {
+    pub format: N64Format,
+    unk04: u16,
+    unk06: u16,
+    unk08: u16,
+    unk0a: u16,
+    unk0c: u16,
+    unk0e: u16,
+    pub frames: Vec<N64SpriteFrame>,
+    // zeros after the last frame
+    padding: usize,
+}
+
+pub struct N64SpriteFrame {
+    pub x: i16,
+    pub y: i16,
+    pub width: u16,
+    pub height: u16,
+    unk0a: u16,
+    unk0c: u16,
+    unk0e: u16,
+    unk10: u16,
+    unk12: u16,
+    /// RGBA5551, only for the CI formats
+    pub palette: Vec<u16>,
+    pub chunks: Vec<N64SpriteChunk>,
+}
+
+/// A piece of a frame, `x` and `y` are from the top left of the frame.
+pub struct N64SpriteChunk {
+    pub x: i16,
+    pub y: i16,
+    pub width: u16,
+    pub height: u16,
+    pub pixels: Vec<u8>,
+}
+
+// palettes and chunks start on 8 bytes from the start of their frame, frames on 8 bytes from the
+// end of the offsets
+const ALIGNMENT: u64 = 8;
+
+impl N64Sprite {
+    /// Errors out if what's read isn't the whole asset (the 0xCD of db360.cmp aside), the
+    /// layout is only known from the N64 version.
+    pub fn new<R: Read>(reader: &mut R, length: usize) -> Result<Self, Box<dyn Error>> {
+        let mut buffer = vec![0u8; length];
+        reader.read_exact(&mut buffer)?;
+        let mut reader = Cursor::new(&buffer[..]);
+
+        let frame_count = reader.read_u16::<BigEndian>()?;
+        let format = N64Format::from(reader.read_u16::<BigEndian>()?);
+        let Some((bits, colors)) = format.bits() else {
+            return Err(RandoError::unexpected("sprite format", "an N64 format", format).into());
+        };
+        let unk04 = reader.read_u16::<BigEndian>()?;
+        let unk06 = reader.read_u16::<BigEndian>()?;
+        let unk08 = reader.read_u16::<BigEndian>()?;
+        let unk0a = reader.read_u16::<BigEndian>()?;
+        let unk0c = reader.read_u16::<BigEndian>()?;
+        let unk0e = reader.read_u16::<BigEndian>()?;
+
+        let mut offsets = vec![];
+        for _ in 0..frame_count {
+            offsets.push(reader.read_u32::<BigEndian>()? as u64);
+        }
+        let table_end = reader.position();
+
+        let mut frames = vec![];
+        for offset in offsets {
+            skip_padding(&mut reader, table_end)?;
+            expect_value("frame offset", offset, reader.position() - table_end)?;
+
+            let start = reader.position();
+            let x = reader.read_i16::<BigEndian>()?;
+            let y = reader.read_i16::<BigEndian>()?;
+            let width = reader.read_u16::<BigEndian>()?;
+            let height = reader.read_u16::<BigEndian>()?;
+            let chunk_count = reader.read_u16::<BigEndian>()?;
+            let unk0a = reader.read_u16::<BigEndian>()?;
+            let unk0c = reader.read_u16::<BigEndian>()?;
+            let unk0e = reader.read_u16::<BigEndian>()?;
+            let unk10 = reader.read_u16::<BigEndian>()?;
+            let unk12 = reader.read_u16::<BigEndian>()?;
+
+            let mut palette = vec![];
+            if colors > 0 {
+                skip_padding(&mut reader, start)?;
+                for _ in 0..colors {
+                    palette.push(reader.read_u16::<BigEndian>()?);
+                }
+            }
+
+            let mut chunks = vec![];
+            for _ in 0..chunk_count {
+                skip_padding(&mut reader, start)?;
+                let x = reader.read_i16::<BigEndian>()?;
+                let y = reader.read_i16::<BigEndian>()?;
+                let width = reader.read_u16::<BigEndian>()?;
+                let height = reader.read_u16::<BigEndian>()?;
+
+                let mut pixels = vec![0u8; (width as usize * height as usize * bits).div_ceil(8)];
+                reader.read_exact(&mut pixels)?;
+
+                chunks.push(N64SpriteChunk {
+                    x,
+                    y,
+                    width,
+                    height,
+                    pixels,
+                });
+            }
+
+            frames.push(N64SpriteFrame {
+                x,
+                y,
+                width,
+                height,
+                unk0a,
+                unk0c,
+                unk0e,
+                unk10,
+                unk12,
+                palette,
+                chunks,
+            });
+        }
+
+        let rest = &buffer[reader.position() as usize..];
+        let padding = rest.iter().take_while(|&&b| b == 0).count();
+        let alignment = &rest[padding..];
+        if alignment.len() >= 8 || alignment.iter().any(|&b| b != 0xCD) {
+            return Err(RandoError::unexpected(
+                "end of sprite",
+                "at most 7 bytes of 0xCD",
+                format!("{} other bytes", alignment.len()),
+            )
+            .into());
+        }
+
+        Ok(Self {
+            format,
+            unk04,
+            unk06,
+            unk08,
+            unk0a,
+            unk0c,
+            unk0e,
+            frames,
+            padding,
+        })
+    }
+
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<(), Box<dyn Error>> {
+        let mut buffer = Cursor::new(vec![]);
+        buffer.write_u16::<BigEndian>(self.frames.len() as u16)?;
+        buffer.write_u16::<BigEndian>(self.format.into())?;
+        buffer.write_u16::<BigEndian>(self.unk04)?;
+        buffer.write_u16::<BigEndian>(self.unk06)?;
+        buffer.write_u16::<BigEndian>(self.unk08)?;
+        buffer.write_u16::<BigEndian>(self.unk0a)?;
+        buffer.write_u16::<BigEndian>(self.unk0c)?;
+        buffer.write_u16::<BigEndian>(self.unk0e)?;
+
+        let offsets_position = buffer.position() as usize;
+        for _ in &self.frames {
+            buffer.write_u32::<BigEndian>(0)?;
+        }
+        let table_end = buffer.position();
+
+        let mut offsets = vec![];
+        for frame in &self.frames {
+            write_padding(&mut buffer, table_end)?;
+            offsets.push((buffer.position() - table_end) as u32);
+
+            let start = buffer.position();
+            buffer.write_i16::<BigEndian>(frame.x)?;
+            buffer.write_i16::<BigEndian>(frame.y)?;
+            buffer.write_u16::<BigEndian>(frame.width)?;
+            buffer.write_u16::<BigEndian>(frame.height)?;
+            buffer.write_u16::<BigEndian>(frame.chunks.len() as u16)?;
+            buffer.write_u16::<BigEndian>(frame.unk0a)?;
+            buffer.write_u16::<BigEndian>(frame.unk0c)?;
+            buffer.write_u16::<BigEndian>(frame.unk0e)?;
+            buffer.write_u16::<BigEndian>(frame.unk10)?;
+            buffer.write_u16::<BigEndian>(frame.unk12)?;
+
+            if !frame.palette.is_empty() {
+                write_padding(&mut buffer, start)?;
+                for color in &frame.palette {
+                    buffer.write_u16::<BigEndian>(*color)?;
+                }
+            }
+
+            for chunk in &frame.chunks {
+                write_padding(&mut buffer, start)?;
+                buffer.write_i16::<BigEndian>(chunk.x)?;
+                buffer.write_i16::<BigEndian>(chunk.y)?;
+                buffer.write_u16::<BigEndian>(chunk.width)?;
+                buffer.write_u16::<BigEndian>(chunk.height)?;
+                buffer.write_all(&chunk.pixels)?;
+            }
+        }
+        buffer.write_all(&vec![0; self.padding])?;
+
+        let mut buffer = buffer.into_inner();
+        for (i, offset) in offsets.iter().enumerate() {
+            let position = offsets_position + 4 * i;
+            buffer[position..position + 4].copy_from_slice(&offset.to_be_bytes());
+        }
+
+        writer.write_all(&buffer)?;
+        Ok(())
+    }
+
+    /// A frame with its chunks put together.
+    pub fn frame_image(&self, frame: usize) -> Image {
+        let frame = &self.frames[frame];
+        let mut image = Image::new(frame.width as u32, frame.height as u32);
+
+        for chunk in &frame.chunks {
+            for cy in 0..chunk.height as usize {
+                for cx in 0..chunk.width as usize {
+                    let rgba = self.texel(frame, chunk, cy * chunk.width as usize + cx);
+                    let x = chunk.x as i64 + cx as i64;
+                    let y = chunk.y as i64 + cy as i64;
+                    if x >= 0 && y >= 0 {
+                        image.set_pixel(x as u32, y as u32, rgba);
+                    }
+                }
+            }
+        }
+
+        image
+    }
+
+    fn texel(&self, frame: &N64SpriteFrame, chunk: &N64SpriteChunk, index: usize) -> [u8; 4] {
+        // the first pixel is in the high bits
+        let nibble = |i: usize| (chunk.pixels[i / 2] >> (4 * (1 - i % 2))) & 0xF;
+
+        match self.format {
+            N64Format::Ci4 => rgba5551(frame.palette[nibble(index) as usize]),
+            N64Format::Ci8 => rgba5551(frame.palette[chunk.pixels[index] as usize]),
+            N64Format::I4 => {
+                let i = nibble(index) * 0x11;
+                [i, i, i, i]
+            }
+            N64Format::I8 => {
+                let i = chunk.pixels[index];
+                [i, i, i, i]
+            }
+            N64Format::Rgba16 => rgba5551(u16::from_be_bytes([
+                chunk.pixels[2 * index],
+                chunk.pixels[2 * index + 1],
+            ])),
+            N64Format::Rgba32 => {
+                let p = &chunk.pixels[4 * index..4 * index + 4];
+                [p[0], p[1], p[2], p[3]]
+            }
+            N64Format::Unknown(_) => [0; 4],
+        }
+    }
+}
+
+fn rgba5551(color: u16) -> [u8; 4] {
+    let channel = |c: u16| ((c & 0x1F) as u32 * 255 / 31) as u8;
+    [
+        channel(color >> 11),
+        channel(color >> 6),
+        channel(color >> 1),
+        if color & 1 != 0 { 0xFF } else { 0 },
+    ]
+}
+
+fn skip_padding(reader: &mut Cursor<&[u8]>, from: u64) -> Result<(), Box<dyn Error>> {
+    while !(reader.position() - from).is_multiple_of(ALIGNMENT) {
+        expect_value("padding", 0, reader.read_u8()?)?;
+    }
+    Ok(())
+}
+
+fn write_padding(writer: &mut Cursor<Vec<u8>>, from: u64) -> Result<(), Box<dyn Error>> {
+    while !(writer.position() - from).is_multiple_of(ALIGNMENT) {
+        writer.write_u8(0)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,222 @@
+use super::map_setup::MapSetup;
+use super::map_setup::Prop2;
+use super::model::Model;
+use super::model::ModelTexture;
+use crate::error::RandoError;
+use crate::textures::Image;
+use crate::textures::Textures;
+use crate::utils::Vector3;
+use std::collections::BTreeMap;
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt::Write;
+use std::fs;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+// half the width of a marker, the game's units are small (Banjo is about 100 high)
+const MARKER_SIZE: f32 = 25.0;
+const MARKER_MATERIAL: &str = "marker";
+const UNTEXTURED_MATERIAL: &str = "untextured";
+
+/// A scene written as Wavefront OBJ, with its materials (MTL) and textures (PNG) next to it, for
+/// Blender and such. Positions are the game's, Y up.
+#[derive(Default)]
+pub struct Obj {
+    obj: String,
+    mtl: String,
+    materials: HashSet<String>,
+    textures: BTreeMap<usize, Image>,
+    vertex_count: usize,
+    uv_count: usize,
+}
+
+impl Obj {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the triangles of the display list of `model`, textured if `textures` has them.
+    pub fn add_model(
+        &mut self,
+        name: &str,
+        model: &Model,
+        textures: Option<&Textures>,
+    ) -> Result<(), Box<dyn Error>> {
+        let (Some(store), Some(display_list)) = (model.vertex_store(), model.display_list()) else {
+            return Err(RandoError::unexpected(
+                "model",
+                "a vertex store and a display list",
+                "sections that can't be read",
+            )
+            .into());
+        };
+
+        writeln!(self.obj, "o {name}")?;
+        let first = self.vertex_count + 1;
+        for vertex in &store.vertices {
+            let position = vertex.position;
+            writeln!(self.obj, "v {} {} {}", position.x, position.y, position.z)?;
+        }
+        self.vertex_count += store.vertices.len();
+
+        let mut current = None;
+        for triangle in display_list.triangles() {
+            if triangle.vertices.iter().any(|v| *v >= store.vertices.len()) {
+                continue;
+            }
+
+            let texture = triangle
+                .texture
+                .and_then(|offset| model.texture_setup()?.find(offset));
+            let material = self.material(texture, textures)?;
+            if current.as_ref() != Some(&material) {
+                writeln!(self.obj, "usemtl {material}")?;
+                current = Some(material);
+            }
+
+            let Some(texture) = texture else {
+                let [a, b, c] = triangle.vertices.map(|v| first + v);
+                writeln!(self.obj, "f {a} {b} {c}")?;
+                continue;
+            };
+
+            // the coordinates are in 1/32 of a texel of the N64 texture
+            let width = texture.width.max(1) as f32 * 32.0;
+            let height = texture.height.max(1) as f32 * 32.0;
+            for v in triangle.vertices {
+                let uv = store.vertices[v].uv;
+                writeln!(
+                    self.obj,
+                    "vt {} {}",
+                    uv.x as f32 / width,
+                    1.0 - uv.y as f32 / height
+                )?;
+            }
+            let uv = self.uv_count + 1;
+            self.uv_count += 3;
+
+            let [a, b, c] = triangle.vertices.map(|v| first + v);
+            writeln!(self.obj, "f {a}/{uv} {b}/{} {c}/{}", uv + 1, uv + 2)?;
+        }
+
+        Ok(())
+    }
+
+    /// Adds a diamond where each prop of `map_setup` is, named after what it is.
+    pub fn add_markers(&mut self, map_setup: &MapSetup) -> Result<(), Box<dyn Error>> {
+        self.add_material(MARKER_MATERIAL, "Kd 1 0 1")?;
+
+        for cube in &map_setup.cubes {
+            for prop in &cube.props_1 {
+                self.add_marker(&format!("Prop1 {}", prop.category), prop.position)?;
+            }
+            for prop in &cube.props_2 {
+                match prop {
+                    Prop2::Actor { position, .. } => self.add_marker("Prop2 Actor", *position)?,
+                    Prop2::Sprite { id, position, .. } => {
+                        self.add_marker(&format!("Prop2 Sprite({id})"), *position)?
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Writes `path`, the `.mtl` with the same name and the textures in the same directory.
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        let directory = path.parent().unwrap_or(Path::new("."));
+        if !directory.as_os_str().is_empty() {
+            fs::create_dir_all(directory)?;
+        }
+
+        let mtl = path.with_extension("mtl");
+        let mtl_name = mtl.file_name().unwrap_or_default().to_string_lossy();
+        fs::write(path, format!("mtllib {mtl_name}\n{}", self.obj))?;
+        fs::write(&mtl, &self.mtl)?;
+
+        for (id, image) in &self.textures {
+            let file = File::create(directory.join(texture_file(*id)))?;
+            image.write_png(BufWriter::new(file))?;
+        }
+
+        Ok(())
+    }
+
+    // textures that can't be decoded get a material anyway, without the image
+    fn material(
+        &mut self,
+        texture: Option<&ModelTexture>,
+        textures: Option<&Textures>,
+    ) -> Result<String, Box<dyn Error>> {
+        let Some(texture) = texture else {
+            // a grey so that the shapes can be seen
+            self.add_material(UNTEXTURED_MATERIAL, "Kd 0.8 0.8 0.8")?;
+            return Ok(UNTEXTURED_MATERIAL.into());
+        };
+
+        let id = texture.texture_id();
+        let name = format!("texture_{id}");
+        if !self.materials.contains(&name) {
+            let image = textures.and_then(|t| t.decode(id).ok());
+            let properties = match image {
+                Some(image) => {
+                    self.textures.insert(id, image);
+                    format!("Kd 1 1 1\nmap_Kd {}", texture_file(id))
+                }
+                None => "Kd 1 1 1".into(),
+            };
+            self.add_material(&name, &properties)?;
+        }
+
+        Ok(name)
+    }
+
+    fn add_material(&mut self, name: &str, properties: &str) -> Result<(), Box<dyn Error>> {
+        if self.materials.insert(name.into()) {
+            writeln!(self.mtl, "newmtl {name}\n{properties}\n")?;
+        }
+        Ok(())
+    }
+
+    fn add_marker(&mut self, name: &str, position: Vector3<i16>) -> Result<(), Box<dyn Error>> {
+        let (x, y, z) = (position.x as f32, position.y as f32, position.z as f32);
+        let s = MARKER_SIZE;
+
+        writeln!(self.obj, "o {name}")?;
+        writeln!(self.obj, "usemtl {MARKER_MATERIAL}")?;
+        for (dx, dy, dz) in [
+            (s, 0.0, 0.0),
+            (-s, 0.0, 0.0),
+            (0.0, s, 0.0),
+            (0.0, -s, 0.0),
+            (0.0, 0.0, s),
+            (0.0, 0.0, -s),
+        ] {
+            writeln!(self.obj, "v {} {} {}", x + dx, y + dy, z + dz)?;
+        }
+
+        let first = self.vertex_count + 1;
+        for [a, b, c] in [
+            [0, 2, 4],
+            [4, 2, 1],
+            [1, 2, 5],
+            [5, 2, 0],
+            [4, 3, 0],
+            [1, 3, 4],
+            [5, 3, 1],
+            [0, 3, 5],
+        ] {
+            writeln!(self.obj, "f {} {} {}", first + a, first + b, first + c)?;
+        }
+        self.vertex_count += 6;
+
+        Ok(())
+    }
+}
+
+fn texture_file(id: usize) -> String {
+    format!("texture_{id}.png")
+}
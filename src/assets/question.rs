@@ -1,9 +1,13 @@
 use crate::enums::Language;
+use crate::error::RandoError;
+use crate::error::expect_value;
 use crate::utils::read_string;
 use crate::utils::write_string;
 use byteorder::BigEndian;
 use byteorder::ReadBytesExt;
 use byteorder::WriteBytesExt;
+use serde::Deserialize;
+use serde::Serialize;
 use std::collections::HashMap;
 use std::error::Error;
 use std::io::Read;
@@ -11,14 +15,21 @@ use std::io::Seek;
 use std::io::SeekFrom;
 use std::io::Write;
 
-#[derive(Debug)]
+// `kind` of the Furnace Fun questions
+pub const QUESTION: u16 = 0x0201;
+pub const GRUNTY_QUIZ: u16 = 0x0003;
+
+/// The lines of a question and of its answers, the right answer is `answer_1`
+/// (there's no field for it).
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct QuestionData {
-    question: Vec<String>,
-    answer_1: Vec<String>,
-    answer_2: Vec<String>,
-    answer_3: Vec<String>,
+    pub question: Vec<String>,
+    pub answer_1: Vec<String>,
+    pub answer_2: Vec<String>,
+    pub answer_3: Vec<String>,
 }
 
+#[derive(Serialize, Deserialize)]
 pub struct Question {
     pub kind: u16,
     pub translations: HashMap<Language, QuestionData>,
@@ -27,11 +38,9 @@ pub struct Question {
 impl Question {
     pub fn new<R: Read>(reader: &mut R) -> Result<Self, Box<dyn Error>> {
         let languages = reader.read_u8()?;
-        assert_eq!(languages, 4);
+        expect_value("language count", 4, languages)?;
 
         let kind = reader.read_u16::<BigEndian>()?;
-        // 0201 = Question
-        // 0003 = Grunty Quiz
 
         // offsets
         for _ in 0..languages {
@@ -50,21 +59,21 @@ impl Question {
         writer.write_u8(self.translations.len() as u8)?;
         writer.write_u16::<BigEndian>(self.kind)?;
 
-        let offsets_position = writer.seek(SeekFrom::Current(0))?;
+        let offsets_position = writer.stream_position()?;
 
         for _ in 0..self.translations.len() {
             writer.write_u16::<BigEndian>(0)?;
         }
 
         let mut offsets = vec![];
-        let header_offset = writer.seek(SeekFrom::Current(0))?;
+        let header_offset = writer.stream_position()?;
         for lang in 0..(self.translations.len() as u8) {
-            let offset = (writer.seek(SeekFrom::Current(0))? - header_offset) as u16;
+            let offset = (writer.stream_position()? - header_offset) as u16;
             offsets.push(offset);
             self.translations[&lang.into()].write(writer)?;
         }
 
-        let end_of_file = writer.seek(SeekFrom::Current(0))?;
+        let end_of_file = writer.stream_position()?;
 
         writer.seek(SeekFrom::Start(offsets_position))?;
         for offset in offsets {
@@ -93,7 +102,14 @@ impl QuestionData {
                 0x81 => answer_1.push(read_string(reader)?),
                 0x82 => answer_2.push(read_string(reader)?),
                 0x83 => answer_3.push(read_string(reader)?),
-                _ => unreachable!(),
+                _ => {
+                    return Err(RandoError::unexpected(
+                        "question command",
+                        "0x80 to 0x83",
+                        format!("0x{command_id:X}"),
+                    )
+                    .into());
+                }
             }
         }
 
@@ -112,22 +128,22 @@ impl QuestionData {
 
         for q in &self.question {
             writer.write_u8(0x80)?;
-            write_string(writer, &q)?;
+            write_string(writer, q)?;
         }
 
         for a in &self.answer_1 {
             writer.write_u8(0x81)?;
-            write_string(writer, &a)?;
+            write_string(writer, a)?;
         }
 
         for a in &self.answer_2 {
             writer.write_u8(0x82)?;
-            write_string(writer, &a)?;
+            write_string(writer, a)?;
         }
 
         for a in &self.answer_3 {
             writer.write_u8(0x83)?;
-            write_string(writer, &a)?;
+            write_string(writer, a)?;
         }
 
         Ok(())
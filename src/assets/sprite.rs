@@ -1,3 +1,7 @@
+use crate::error::RandoError;
+use crate::error::expect_value;
+use crate::textures::Image;
+use crate::textures::Textures;
 use byteorder::BigEndian;
 use byteorder::ReadBytesExt;
 use byteorder::WriteBytesExt;
@@ -19,7 +23,7 @@ impl Sprite {
     pub fn new<R: Read>(reader: &mut R) -> Result<Self, Box<dyn Error>> {
         let frame_count = reader.read_u16::<BigEndian>()?;
         let format = reader.read_u16::<BigEndian>()?;
-        assert_eq!(format, 0x1000);
+        expect_value("sprite format", 0x1000, format)?;
         let unk04 = reader.read_u16::<BigEndian>()?;
         let unk06 = reader.read_u16::<BigEndian>()?;
         let unk08 = reader.read_u16::<BigEndian>()?;
@@ -31,7 +35,7 @@ impl Sprite {
         // offsets
         for _ in 0..frame_count {
             let offset = reader.read_u32::<BigEndian>()?;
-            assert_eq!(offset % SPRITE_FRAME_SIZE, 0);
+            expect_value("frame offset alignment", 0, offset % SPRITE_FRAME_SIZE)?;
         }
 
         for _ in 0..frame_count {
@@ -50,10 +54,10 @@ impl Sprite {
             let unk18 = reader.read_u16::<BigEndian>()?;
             let unk1a = reader.read_u16::<BigEndian>()?;
 
-            assert_eq!(unk14, 0);
-            assert_eq!(unk16, 0);
-            assert_eq!(unk18, unk04);
-            assert_eq!(unk1a, unk06);
+            expect_value("unk14", 0, unk14)?;
+            expect_value("unk16", 0, unk16)?;
+            expect_value("unk18 (same as unk04)", unk04, unk18)?;
+            expect_value("unk1a (same as unk06)", unk06, unk1a)?;
 
             frames.push(SpriteFrame {
                 unk00,
@@ -117,8 +121,75 @@ impl Sprite {
 
         Ok(())
     }
+
+    pub fn frames(&self) -> &[SpriteFrame] {
+        &self.frames
+    }
+
+    /// Each frame, cut from the top left of its texture.
+    pub fn frame_images(&self, textures: &Textures) -> Result<Vec<Image>, Box<dyn Error>> {
+        self.frames
+            .iter()
+            .map(|frame| {
+                let texture = textures.decode(frame.texture_id())?;
+                Ok(texture.crop(frame.width() as u32, frame.height() as u32))
+            })
+            .collect()
+    }
+
+    /// The frames side by side.
+    pub fn write_sheet<W: Write>(
+        &self,
+        textures: &Textures,
+        writer: W,
+    ) -> Result<(), Box<dyn Error>> {
+        Image::sheet(&self.frame_images(textures)?).write_png(writer)
+    }
+
+    pub fn write_gif<W: Write>(
+        &self,
+        textures: &Textures,
+        writer: W,
+    ) -> Result<(), Box<dyn Error>> {
+        Image::write_gif(&self.frame_images(textures)?, writer, GIF_DELAY)
+    }
+
+    /// Draws `image` over the texture of `frame`, which keeps its size. The frames and sprites
+    /// sharing that texture change too.
+    pub fn replace_frame(
+        &self,
+        frame: usize,
+        textures: &mut Textures,
+        image: &Image,
+    ) -> Result<(), Box<dyn Error>> {
+        let Some(frame) = self.frames.get(frame) else {
+            return Err(RandoError::unexpected(
+                "sprite frame",
+                format!("less than {}", self.frames.len()),
+                frame,
+            )
+            .into());
+        };
+
+        let size = (frame.width() as u32, frame.height() as u32);
+        if (image.width, image.height) != size {
+            return Err(RandoError::unexpected(
+                "frame size",
+                format!("{}x{}", size.0, size.1),
+                format!("{}x{}", image.width, image.height),
+            )
+            .into());
+        }
+
+        let mut texture = textures.decode(frame.texture_id())?;
+        texture.paste(image, 0, 0);
+        textures.replace(frame.texture_id(), &texture)
+    }
 }
 
+// the speed the game plays them at isn't known, in hundredths of a second
+const GIF_DELAY: u16 = 5;
+
 const SPRITE_FRAME_SIZE: u32 = 0x1C;
 pub struct SpriteFrame {
     unk00: u16,
@@ -136,3 +207,18 @@ pub struct SpriteFrame {
     unk18: u16, // width?
     unk1a: u16, // height?
 }
+
+impl SpriteFrame {
+    /// Index in db360.textures.cmp.
+    pub fn texture_id(&self) -> usize {
+        self.texture_id as usize
+    }
+
+    pub fn width(&self) -> u16 {
+        self.unk04
+    }
+
+    pub fn height(&self) -> u16 {
+        self.unk06
+    }
+}
@@ -9,12 +9,12 @@ pub struct Unknown {
 impl Unknown {
     pub fn new<R: Read>(reader: &mut R, length: usize) -> Result<Self, Box<dyn Error>> {
         let mut buffer = vec![0u8; length];
-        reader.read(&mut buffer)?;
+        reader.read_exact(&mut buffer)?;
         Ok(Self { buffer })
     }
 
     pub fn write<W: Write>(&self, writer: &mut W) -> Result<(), Box<dyn Error>> {
-        writer.write(&self.buffer)?;
+        writer.write_all(&self.buffer)?;
         Ok(())
     }
 }
@@ -5,3 +5,6 @@ pub mod powerpc;
 pub mod xex;
 
 pub const NOTE_DOORS_COSTS: [u32; 12] = [50, 180, 260, 350, 450, 640, 765, 810, 828, 846, 864, 882];
+
+// indexed by LevelOrder, the lair's is the one opening the tower
+pub const PUZZLES_COSTS: [u32; 10] = [1, 2, 5, 7, 8, 25, 9, 15, 12, 10];
@@ -1,7 +1,7 @@
 use crate::enums::*;
 
 #[rustfmt::skip]
-pub const ASSETS: [AssetId; 3701] = [
+pub static ASSETS: [AssetId; 3701] = [
 AssetId::Animation(AnimationId::Animation00000000),
 AssetId::Animation(AnimationId::Animation00000158),
 AssetId::Animation(AnimationId::Animation000009D8),
@@ -1,9 +1,10 @@
+use crate::data::levels::LevelOrder;
 use crate::enums::WarpOrTriggerId::*;
 use crate::enums::file_progress::FileProgress;
 use crate::enums::{Ability, MapSetupId};
 use crate::enums::{MapSetupId::*, WarpOrTriggerId};
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub enum Transformation {
     Termite,
     Pumpkin,
@@ -12,11 +13,15 @@ pub enum Transformation {
     Bee,
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub enum Requirement {
     Ability(Ability),
     Transformation(Transformation),
     Flag(FileProgress),
+    // index in the note doors costs table
+    NoteDoor(usize),
+    // puzzle of the world entrance, not of the world behind it. the lair's opens the tower
+    Puzzle(LevelOrder),
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -45,7 +50,7 @@ pub struct Map {
     pub entrances: &'static [Entrance],
 }
 
-pub const MAPS: &'static [Map] = &[
+pub const MAPS: &[Map] = &[
     Map {
         id: SpiralMountain,
         entrances: &[
@@ -76,10 +81,16 @@ pub const MAPS: &'static [Map] = &[
                 from: SpiralMountain,
                 warp: WarpSmExitLair,
                 paths: &[
-                    Destination::new(MumbosMountain),
+                    Destination {
+                        target: MumbosMountain,
+                        required: &[Requirement::Puzzle(LevelOrder::MumbosMountain)],
+                    },
                     Destination {
                         target: GlTtcAndCcPuzzle,
-                        required: &[Requirement::Ability(Ability::TalonTrot)],
+                        required: &[
+                            Requirement::Ability(Ability::TalonTrot),
+                            Requirement::NoteDoor(0),
+                        ],
                     },
                 ],
             },
@@ -90,7 +101,10 @@ pub const MAPS: &'static [Map] = &[
                     Destination::new(SpiralMountain),
                     Destination {
                         target: GlTtcAndCcPuzzle,
-                        required: &[Requirement::Ability(Ability::TalonTrot)],
+                        required: &[
+                            Requirement::Ability(Ability::TalonTrot),
+                            Requirement::NoteDoor(0),
+                        ],
                     },
                 ],
             },
@@ -98,7 +112,10 @@ pub const MAPS: &'static [Map] = &[
                 from: GlTtcAndCcPuzzle,
                 warp: WarpLairEnterPuzzlesRoomFromMmLobby,
                 paths: &[
-                    Destination::new(MumbosMountain),
+                    Destination {
+                        target: MumbosMountain,
+                        required: &[Requirement::Puzzle(LevelOrder::MumbosMountain)],
+                    },
                     Destination::new(SpiralMountain),
                 ],
             },
@@ -115,7 +132,10 @@ pub const MAPS: &'static [Map] = &[
             Entrance {
                 from: Gl180NoteDoor,
                 warp: WarpLairEnterCcwPuzzleRoomFromPuzzlesRoom,
-                paths: &[Destination::new(GlMmLobby)],
+                paths: &[Destination {
+                    target: GlMmLobby,
+                    required: &[Requirement::NoteDoor(0)],
+                }],
             },
         ],
     },
@@ -130,7 +150,10 @@ pub const MAPS: &'static [Map] = &[
                     Destination::new(GlTtcLobby),
                     Destination {
                         target: GlStatueRoom,
-                        required: &[Requirement::Ability(Ability::TalonTrot)],
+                        required: &[
+                            Requirement::Ability(Ability::TalonTrot),
+                            Requirement::NoteDoor(1),
+                        ],
                     },
                 ],
             },
@@ -142,7 +165,10 @@ pub const MAPS: &'static [Map] = &[
                     Destination::new(GlTtcLobby),
                     Destination {
                         target: GlStatueRoom,
-                        required: &[Requirement::Ability(Ability::TalonTrot)],
+                        required: &[
+                            Requirement::Ability(Ability::TalonTrot),
+                            Requirement::NoteDoor(1),
+                        ],
                     },
                 ],
             },
@@ -154,7 +180,10 @@ pub const MAPS: &'static [Map] = &[
                     Destination::new(GlCcLobby),
                     Destination {
                         target: GlStatueRoom,
-                        required: &[Requirement::Ability(Ability::TalonTrot)],
+                        required: &[
+                            Requirement::Ability(Ability::TalonTrot),
+                            Requirement::NoteDoor(1),
+                        ],
                     },
                 ],
             },
@@ -175,7 +204,10 @@ pub const MAPS: &'static [Map] = &[
             Entrance {
                 from: Gl180NoteDoor,
                 warp: WarpLairEnterTtcLobbyFromCcwPuzzleRoom,
-                paths: &[Destination::new(TreasureTroveCove)],
+                paths: &[Destination {
+                    target: TreasureTroveCove,
+                    required: &[Requirement::Puzzle(LevelOrder::TreasureTroveCove)],
+                }],
             },
             Entrance {
                 from: TreasureTroveCove,
@@ -192,7 +224,12 @@ pub const MAPS: &'static [Map] = &[
                 warp: WarpLairEnterCcwPuzzleRoomFromCCLobby,
                 paths: &[Destination {
                     target: ClankersCavern,
-                    required: &[Requirement::Ability(Ability::BeakBuster)],
+                    required: &[
+                        Requirement::Flag(FileProgress::CcLobbyPipe1Raised),
+                        Requirement::Flag(FileProgress::CcLobbyPipe2Raised),
+                        Requirement::Flag(FileProgress::CcLobbyPipe3Raised),
+                        Requirement::Puzzle(LevelOrder::ClankersCavern),
+                    ],
                 }],
             },
             Entrance {
@@ -215,7 +252,10 @@ pub const MAPS: &'static [Map] = &[
                     },
                     Destination {
                         target: GlGvLobby,
-                        required: &[Requirement::Ability(Ability::ShockJump)],
+                        required: &[
+                            Requirement::Ability(Ability::ShockJump),
+                            Requirement::NoteDoor(2),
+                        ],
                     },
                 ],
             },
@@ -223,10 +263,16 @@ pub const MAPS: &'static [Map] = &[
                 from: GlBgsLobby,
                 warp: WarpLairEnterBgsLobbyFromPointingGruntyStatueRoom,
                 paths: &[
-                    Destination::new(Gl180NoteDoor),
+                    Destination {
+                        target: Gl180NoteDoor,
+                        required: &[Requirement::NoteDoor(1)],
+                    },
                     Destination {
                         target: GlGvLobby,
-                        required: &[Requirement::Ability(Ability::ShockJump)],
+                        required: &[
+                            Requirement::Ability(Ability::ShockJump),
+                            Requirement::NoteDoor(2),
+                        ],
                     },
                 ],
             },
@@ -234,7 +280,10 @@ pub const MAPS: &'static [Map] = &[
                 from: GlGvLobby,
                 warp: WarpLairEnterGvLobbyFromPointingStatueRoom,
                 paths: &[
-                    Destination::new(Gl180NoteDoor),
+                    Destination {
+                        target: Gl180NoteDoor,
+                        required: &[Requirement::NoteDoor(1)],
+                    },
                     Destination {
                         target: GlBgsLobby,
                         required: &[Requirement::Ability(Ability::TalonTrot)],
@@ -249,7 +298,10 @@ pub const MAPS: &'static [Map] = &[
             Entrance {
                 from: GlStatueRoom,
                 warp: WarpLairEnterPointingGruntyStatueFromBgsLobby,
-                paths: &[Destination::new(BubbleGloopSwamp)],
+                paths: &[Destination {
+                    target: BubbleGloopSwamp,
+                    required: &[Requirement::Puzzle(LevelOrder::BubbleGloopSwamp)],
+                }],
             },
             Entrance {
                 from: BubbleGloopSwamp,
@@ -264,19 +316,43 @@ pub const MAPS: &'static [Map] = &[
             Entrance {
                 from: GlStatueRoom,
                 warp: WarpLairEnterPointingStatueRoomFromGvLobbyNoteDoor,
-                paths: &[Destination::new(GobisValley), Destination::new(GlFpLobby)],
+                paths: &[
+                    Destination {
+                        target: GobisValley,
+                        required: &[Requirement::Puzzle(LevelOrder::GobisValley)],
+                    },
+                    Destination {
+                        target: GlFpLobby,
+                        required: &[Requirement::NoteDoor(3)],
+                    },
+                ],
             },
             Entrance {
                 from: GobisValley,
                 warp: WarpGvEnterLevel,
-                paths: &[Destination::new(GlStatueRoom), Destination::new(GlFpLobby)],
+                paths: &[
+                    Destination {
+                        target: GlStatueRoom,
+                        required: &[Requirement::NoteDoor(2)],
+                    },
+                    Destination {
+                        target: GlFpLobby,
+                        required: &[Requirement::NoteDoor(3)],
+                    },
+                ],
             },
             Entrance {
                 from: GlFpLobby,
                 warp: WarpLairEnterFpLobbyFromGvLobby,
                 paths: &[
-                    Destination::new(GlStatueRoom),
-                    Destination::new(GobisValley),
+                    Destination {
+                        target: GlStatueRoom,
+                        required: &[Requirement::NoteDoor(2)],
+                    },
+                    Destination {
+                        target: GobisValley,
+                        required: &[Requirement::Puzzle(LevelOrder::GobisValley)],
+                    },
                 ],
             },
         ],
@@ -288,36 +364,72 @@ pub const MAPS: &'static [Map] = &[
                 from: GlGvLobby,
                 warp: WarpLairEnterGvLobbyFromFpLobby,
                 paths: &[
-                    Destination::new(GlGvPuzzle),
-                    Destination::new(Gl640NoteDoor),
-                    Destination::new(FreezeezyPeak),
+                    Destination {
+                        target: GlGvPuzzle,
+                        required: &[Requirement::NoteDoor(4)],
+                    },
+                    Destination {
+                        target: Gl640NoteDoor,
+                        required: &[Requirement::NoteDoor(5)],
+                    },
+                    Destination {
+                        target: FreezeezyPeak,
+                        required: &[Requirement::Puzzle(LevelOrder::FreezeezyPeak)],
+                    },
                 ],
             },
             Entrance {
                 from: GlGvPuzzle,
                 warp: WarpLairEnterGvPuzzleRoomFromFpLobby,
                 paths: &[
-                    Destination::new(GlGvLobby),
-                    Destination::new(Gl640NoteDoor),
-                    Destination::new(FreezeezyPeak),
+                    Destination {
+                        target: GlGvLobby,
+                        required: &[Requirement::NoteDoor(3)],
+                    },
+                    Destination {
+                        target: Gl640NoteDoor,
+                        required: &[Requirement::NoteDoor(5)],
+                    },
+                    Destination {
+                        target: FreezeezyPeak,
+                        required: &[Requirement::Puzzle(LevelOrder::FreezeezyPeak)],
+                    },
                 ],
             },
             Entrance {
                 from: Gl640NoteDoor,
                 warp: WarpLairEnter640NoteDoorRoomFromFpLobby,
                 paths: &[
-                    Destination::new(GlGvLobby),
-                    Destination::new(GlGvPuzzle),
-                    Destination::new(FreezeezyPeak),
+                    Destination {
+                        target: GlGvLobby,
+                        required: &[Requirement::NoteDoor(3)],
+                    },
+                    Destination {
+                        target: GlGvPuzzle,
+                        required: &[Requirement::NoteDoor(4)],
+                    },
+                    Destination {
+                        target: FreezeezyPeak,
+                        required: &[Requirement::Puzzle(LevelOrder::FreezeezyPeak)],
+                    },
                 ],
             },
             Entrance {
                 from: FreezeezyPeak,
                 warp: WarpFpEnterLevel,
                 paths: &[
-                    Destination::new(GlGvLobby),
-                    Destination::new(GlGvPuzzle),
-                    Destination::new(Gl640NoteDoor),
+                    Destination {
+                        target: GlGvLobby,
+                        required: &[Requirement::NoteDoor(3)],
+                    },
+                    Destination {
+                        target: GlGvPuzzle,
+                        required: &[Requirement::NoteDoor(4)],
+                    },
+                    Destination {
+                        target: Gl640NoteDoor,
+                        required: &[Requirement::NoteDoor(5)],
+                    },
                 ],
             },
         ],
@@ -333,7 +445,10 @@ pub const MAPS: &'static [Map] = &[
             Entrance {
                 from: GlMmmLobby,
                 warp: WarpLairEnterMmmLobbyFromGvPuzzleRoom,
-                paths: &[Destination::new(GlFpLobby)],
+                paths: &[Destination {
+                    target: GlFpLobby,
+                    required: &[Requirement::NoteDoor(4)],
+                }],
             },
         ],
     },
@@ -348,7 +463,10 @@ pub const MAPS: &'static [Map] = &[
                         target: GlCrypt,
                         required: &[Requirement::Transformation(Transformation::Pumpkin)],
                     },
-                    Destination::new(MadMonsterMansion),
+                    Destination {
+                        target: MadMonsterMansion,
+                        required: &[Requirement::Puzzle(LevelOrder::MadMonsterMansion)],
+                    },
                 ],
             },
             Entrance {
@@ -356,7 +474,10 @@ pub const MAPS: &'static [Map] = &[
                 warp: WarpLairEnterCryptFromMmmLobby,
                 paths: &[
                     Destination::new(GlGvPuzzle),
-                    Destination::new(MadMonsterMansion),
+                    Destination {
+                        target: MadMonsterMansion,
+                        required: &[Requirement::Puzzle(LevelOrder::MadMonsterMansion)],
+                    },
                 ],
             },
             Entrance {
@@ -389,7 +510,10 @@ pub const MAPS: &'static [Map] = &[
                 paths: &[
                     Destination {
                         target: GlCcwLobby,
-                        required: &[Requirement::Flag(FileProgress::WaterSwitch2Pressed)],
+                        required: &[
+                            Requirement::Flag(FileProgress::WaterSwitch2Pressed),
+                            Requirement::NoteDoor(6),
+                        ],
                     },
                     Destination::new(GlRbbLobby),
                 ],
@@ -397,7 +521,13 @@ pub const MAPS: &'static [Map] = &[
             Entrance {
                 from: GlCcwLobby,
                 warp: WarpLairCcwLobbyFrom640NoteDoorRoomDoorEntrance,
-                paths: &[Destination::new(GlFpLobby), Destination::new(GlRbbLobby)],
+                paths: &[
+                    Destination {
+                        target: GlFpLobby,
+                        required: &[Requirement::NoteDoor(5)],
+                    },
+                    Destination::new(GlRbbLobby),
+                ],
             },
             Entrance {
                 from: GlRbbLobby,
@@ -405,9 +535,15 @@ pub const MAPS: &'static [Map] = &[
                 paths: &[
                     Destination {
                         target: GlCcwLobby,
-                        required: &[Requirement::Flag(FileProgress::WaterSwitch2Pressed)],
+                        required: &[
+                            Requirement::Flag(FileProgress::WaterSwitch2Pressed),
+                            Requirement::NoteDoor(6),
+                        ],
+                    },
+                    Destination {
+                        target: GlFpLobby,
+                        required: &[Requirement::NoteDoor(5)],
                     },
-                    Destination::new(GlFpLobby),
                 ],
             },
         ],
@@ -421,7 +557,10 @@ pub const MAPS: &'static [Map] = &[
                 paths: &[
                     Destination {
                         target: RustyBucketBay,
-                        required: &[Requirement::Flag(FileProgress::WaterSwitch1Pressed)],
+                        required: &[
+                            Requirement::Flag(FileProgress::WaterSwitch1Pressed),
+                            Requirement::Puzzle(LevelOrder::RustyBucketBay),
+                        ],
                     },
                     Destination {
                         target: GlRbbAndMmmPuzzle,
@@ -436,7 +575,10 @@ pub const MAPS: &'static [Map] = &[
                 paths: &[
                     Destination {
                         target: RustyBucketBay,
-                        required: &[Requirement::Flag(FileProgress::WaterSwitch1Pressed)],
+                        required: &[
+                            Requirement::Flag(FileProgress::WaterSwitch1Pressed),
+                            Requirement::Puzzle(LevelOrder::RustyBucketBay),
+                        ],
                     },
                     Destination::new(GlRbbAndMmmPuzzle),
                     Destination::new(Gl640NoteDoor),
@@ -448,7 +590,10 @@ pub const MAPS: &'static [Map] = &[
                 paths: &[
                     Destination {
                         target: RustyBucketBay,
-                        required: &[Requirement::Flag(FileProgress::WaterSwitch1Pressed)],
+                        required: &[
+                            Requirement::Flag(FileProgress::WaterSwitch1Pressed),
+                            Requirement::Puzzle(LevelOrder::RustyBucketBay),
+                        ],
                     },
                     Destination {
                         target: GlRbbAndMmmPuzzle,
@@ -495,27 +640,45 @@ pub const MAPS: &'static [Map] = &[
                 paths: &[
                     Destination {
                         target: CcwHub,
-                        required: &[Requirement::Ability(Ability::TalonTrot)],
+                        required: &[
+                            Requirement::Ability(Ability::TalonTrot),
+                            Requirement::Puzzle(LevelOrder::ClickClockWood),
+                        ],
+                    },
+                    Destination {
+                        target: GlFfEntrance,
+                        required: &[Requirement::NoteDoor(7)],
                     },
-                    Destination::new(GlFfEntrance),
                 ],
             },
             Entrance {
                 from: CcwHub,
                 warp: WarpCcwEnterLevel,
                 paths: &[
-                    Destination::new(Gl640NoteDoor),
-                    Destination::new(GlFfEntrance),
+                    Destination {
+                        target: Gl640NoteDoor,
+                        required: &[Requirement::NoteDoor(6)],
+                    },
+                    Destination {
+                        target: GlFfEntrance,
+                        required: &[Requirement::NoteDoor(7)],
+                    },
                 ],
             },
             Entrance {
                 from: GlFfEntrance,
                 warp: WarpLairEnterFurnaceFunPathFromCcwLobby,
                 paths: &[
-                    Destination::new(Gl640NoteDoor),
+                    Destination {
+                        target: Gl640NoteDoor,
+                        required: &[Requirement::NoteDoor(6)],
+                    },
                     Destination {
                         target: CcwHub,
-                        required: &[Requirement::Ability(Ability::TalonTrot)],
+                        required: &[
+                            Requirement::Ability(Ability::TalonTrot),
+                            Requirement::Puzzle(LevelOrder::ClickClockWood),
+                        ],
                     },
                 ],
             },
@@ -603,17 +766,140 @@ pub const MAPS: &'static [Map] = &[
     },
 ];
 
-pub fn get_warp_data(from: MapSetupId, to: MapSetupId) -> WarpOrTriggerId {
-    for map in MAPS {
-        if map.id == from {
-            for entrance in map.entrances {
-                if entrance.from == to {
-                    return entrance.warp;
-                }
-            }
-            break;
-        }
-    }
+#[derive(Copy, Clone)]
+pub struct MumboSkull {
+    pub map: MapSetupId,
+    pub transformation: Transformation,
+    pub cost: u32,
+}
+
+pub const MUMBO_SKULLS: [MumboSkull; 5] = [
+    MumboSkull {
+        map: MmMumbosSkull,
+        transformation: Transformation::Termite,
+        cost: 5,
+    },
+    MumboSkull {
+        map: BgsMumbosSkull,
+        transformation: Transformation::Croc,
+        cost: 10,
+    },
+    MumboSkull {
+        map: FpMumbosSkull,
+        transformation: Transformation::Walrus,
+        cost: 15,
+    },
+    MumboSkull {
+        map: MmmMumbosSkull,
+        transformation: Transformation::Pumpkin,
+        cost: 20,
+    },
+    MumboSkull {
+        map: CcwSpringMumbosSkull,
+        transformation: Transformation::Bee,
+        cost: 25,
+    },
+];
+
+// maps of a world that aren't reachable from its hub as soon as you get there
+pub const LEVEL_MAPS: &[Destination] = &[
+    Destination {
+        target: TickersTower,
+        required: &[Requirement::Transformation(Transformation::Termite)],
+    },
+    Destination {
+        target: MrVile,
+        required: &[Requirement::Transformation(Transformation::Croc)],
+    },
+    Destination {
+        target: WozzasCave,
+        required: &[Requirement::Transformation(Transformation::Walrus)],
+    },
+    Destination {
+        target: Well,
+        required: &[Requirement::Transformation(Transformation::Pumpkin)],
+    },
+    Destination {
+        target: InsideLoggo,
+        required: &[Requirement::Transformation(Transformation::Pumpkin)],
+    },
+    Destination {
+        target: CcwSpringZubbaHive,
+        required: &[Requirement::Transformation(Transformation::Bee)],
+    },
+    Destination {
+        target: CcwSummerZubbaHive,
+        required: &[Requirement::Transformation(Transformation::Bee)],
+    },
+    Destination {
+        target: CcwAutumnZubbaHive,
+        required: &[Requirement::Transformation(Transformation::Bee)],
+    },
+];
+
+// room of the podium where each puzzle is completed. the lair's opens the tower
+pub const PUZZLE_PODIUMS: [(LevelOrder, MapSetupId); 10] = [
+    (LevelOrder::MumbosMountain, GlMmLobby),
+    (LevelOrder::TreasureTroveCove, GlTtcAndCcPuzzle),
+    (LevelOrder::ClankersCavern, GlTtcAndCcPuzzle),
+    (LevelOrder::BubbleGloopSwamp, GlCcLobby),
+    (LevelOrder::FreezeezyPeak, GlStatueRoom),
+    (LevelOrder::Lair, GlDingpot),
+    (LevelOrder::GobisValley, GlGvPuzzle),
+    (LevelOrder::ClickClockWood, Gl180NoteDoor),
+    (LevelOrder::RustyBucketBay, GlRbbAndMmmPuzzle),
+    (LevelOrder::MadMonsterMansion, GlRbbAndMmmPuzzle),
+];
+
+// past the Furnace Fun path, each one only reachable from the one before
+pub const TOWER_MAPS: &[Destination] = &[
+    Destination::new(GlFurnaceFun),
+    Destination::new(GlDingpot),
+    Destination {
+        target: GlBattlements,
+        required: &[Requirement::Puzzle(LevelOrder::Lair)],
+    },
+];
+
+// where Gruntilda is fought
+pub const FINAL_MAP: MapSetupId = GlBattlements;
+
+pub struct FlagSource {
+    pub flag: FileProgress,
+    pub map: MapSetupId,
+    pub required: &'static [Requirement],
+}
+
+// where the flags used by the requirements above get set
+// flags not listed here are considered always set
+pub const FLAG_SOURCES: &[FlagSource] = &[
+    FlagSource {
+        flag: FileProgress::CcLobbyPipe1Raised,
+        map: GlCcLobby,
+        required: &[Requirement::Ability(Ability::BeakBuster)],
+    },
+    FlagSource {
+        flag: FileProgress::CcLobbyPipe2Raised,
+        map: GlCcLobby,
+        required: &[Requirement::Ability(Ability::BeakBuster)],
+    },
+    FlagSource {
+        flag: FileProgress::CcLobbyPipe3Raised,
+        map: GlCcLobby,
+        required: &[Requirement::Ability(Ability::BeakBuster)],
+    },
+    FlagSource {
+        flag: FileProgress::WaterSwitch1Pressed,
+        map: GlGvPuzzle,
+        required: &[],
+    },
+    FlagSource {
+        flag: FileProgress::WaterSwitch2Pressed,
+        map: GlRbbLobby,
+        required: &[Requirement::Flag(FileProgress::WaterSwitch1Pressed)],
+    },
+];
 
-    panic!("There are no warps between {from} and {to}");
+pub fn get_map(id: MapSetupId) -> Option<&'static Map> {
+    MAPS.iter().find(|m| m.id == id)
 }
@@ -7,6 +7,7 @@ use crate::enums::MapSetupId;
 use crate::enums::TextureId;
 use crate::enums::WarpOrTriggerId;
 use crate::enums::map::MapId;
+use serde::Deserialize;
 use std::ops::Index;
 
 pub struct WarpLair {
@@ -36,6 +37,7 @@ pub struct LevelInfo {
 
 enum_builder! {
     #[repr(usize)]
+    #[derive(Deserialize)]
     pub enum LevelOrder {
         MumbosMountain = 0,
         TreasureTroveCove = 1,
@@ -59,6 +61,13 @@ impl LevelOrder {
         LEVELS_INFO[*self].molehills.len()
     }
 
+    pub fn maps(&self) -> &'static [MapSetupId] {
+        match self {
+            LevelOrder::Lair => &LAIR_MAPS,
+            _ => LEVELS_INFO[*self].maps,
+        }
+    }
+
     pub fn get_name(&self, lang: Language) -> &'static str {
         match self {
             LevelOrder::MumbosMountain => match lang {
@@ -1,14 +1,9 @@
-use super::xex::CODE_START_CUSTOM_ADDRESS;
 use crate::enum_builder;
 use crate::enums::file_progress::FileProgress;
-use byteorder::BigEndian;
-use byteorder::WriteBytesExt;
-use ppc::Block;
-use ppc::Instruction;
-use ppc::Register;
+use crate::ppc::Block;
+use crate::ppc::Instruction;
+use crate::ppc::Register;
 use std::error::Error;
-use std::io::Seek;
-use std::io::SeekFrom;
 use std::io::Write;
 
 enum_builder! {
@@ -26,7 +21,7 @@ enum_builder! {
 pub fn prologue(block: &mut Block) {
     block.add(Instruction::Mflr(Register::R12));
     block.add(Instruction::Stw(Register::R12, Register::R1, -0x08));
-    block.add(Instruction::Stw(Register::R1, Register::R1, -0x60));
+    block.add(Instruction::Stwu(Register::R1, Register::R1, -0x60));
 }
 
 pub fn epilogue(block: &mut Block) {
@@ -36,19 +31,6 @@ pub fn epilogue(block: &mut Block) {
     block.add(Instruction::Blr);
 }
 
-pub fn call(address: u32, target: Functions) -> u32 {
-    let target: u32 = target.into();
-
-    jump(address, target) + 1
-}
-
-pub fn jump(address: u32, target: u32) -> u32 {
-    let diff = target as i64 - address as i64;
-    let inst = ((diff as u32) & 0x03FFFFFC) + 0x48000000;
-
-    inst
-}
-
 pub fn nop<W: Write>(writer: &mut W) -> Result<(), Box<dyn Error>> {
     // ori r0, r0, 0
     // i.e. "noop"
@@ -73,7 +55,7 @@ pub fn set_flags(block: &mut Block, start_flag: FileProgress, length: u32) {
     let func: u32 = Functions::FileProgressFlagSetN.into();
 
     block.add(Instruction::Li(Register::R5, length as u16));
-    let bits = (u16::MAX >> (16 - length));
+    let bits = u16::MAX >> (16 - length);
     block.add(Instruction::Li(Register::R4, bits));
     block.add(Instruction::Li(Register::R3, start_flag as u16));
     block.add(Instruction::Bl(func));
@@ -1,4 +1,5 @@
 use serde::Deserialize;
+use serde::Serialize;
 
 pub use self::actors::ActorId;
 pub use self::animations::AnimationId;
@@ -96,6 +97,7 @@ enum_builder! {
 
 enum_builder! {
     #[repr(u8)]
+    #[derive(Deserialize)]
     pub enum Ability {
         Barge = 0x0,
         BeakBomb = 0x1,
@@ -122,7 +124,7 @@ enum_builder! {
 
 enum_builder! {
     #[repr(u16)]
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub enum SpritePropId {
         RedFeather = 14,
         BlueTulip = 70,
@@ -137,6 +139,14 @@ enum_builder! {
     }
 }
 
+impl SpritePropId {
+    /// The sprite drawn for the prop, they're numbered from the first sprite of db360.cmp.
+    pub fn sprite(&self) -> SpriteId {
+        let id: u16 = (*self).into();
+        SpriteId::from(id.wrapping_add(0x572))
+    }
+}
+
 pub mod actors;
 pub mod animations;
 pub mod credits;
@@ -186,7 +196,7 @@ impl std::fmt::Display for AssetId {
 
 enum_builder! {
     #[repr(u8)]
-    #[derive(Eq, Hash)]
+    #[derive(Eq, Hash, Serialize, Deserialize)]
     pub enum Language {
         English = 0,
         Japanese = 1,
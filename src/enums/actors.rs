@@ -1,11 +1,12 @@
 use crate::enum_builder;
+use rand::Rng;
 use rand::prelude::IndexedRandom;
-use rand::rng;
 use serde::Deserialize;
+use serde::Serialize;
 
 enum_builder! {
     #[repr(u16)]
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub enum ActorId {
         EntryPoint1 = 1,
         EntryPoint2 = 2,
@@ -166,26 +167,37 @@ const ENEMIES: [ActorId; 22] = [
 
 impl ActorId {
     pub fn needs_flag(&self) -> bool {
-        match self {
-            Self::Jiggy => true,
-            Self::MumboToken => true,
-            Self::EmptyHoneycomb => true,
-            _ => false,
-        }
+        matches!(self, Self::Jiggy | Self::MumboToken | Self::EmptyHoneycomb)
+    }
+
+    // jinjos are counted per world, the others are only useful in their world
+    pub fn stays_in_world(&self) -> bool {
+        matches!(
+            self,
+            Self::BlueJinjo
+                | Self::GreenJinjo
+                | Self::OrangeJinjo
+                | Self::PinkJinjo
+                | Self::YellowJinjo
+                | Self::BlubbersGold
+                | Self::CollectableBluePresent
+                | Self::CollectableGreenPresent
+                | Self::CollectableRedPresent
+                | Self::NabnutsAcorn
+                | Self::Caterpillar
+                | Self::MmmFlowerPot
+        )
     }
 
     pub fn can_globalize(&self) -> bool {
-        match self {
-            ActorId::ChimpysOrange => true,
-            _ => false,
-        }
+        matches!(self, ActorId::ChimpysOrange)
     }
 
     pub fn is_enemy(&self) -> bool {
         ENEMIES.contains(self)
     }
 
-    pub fn random_enemy() -> Self {
-        *ENEMIES.choose(&mut rng()).unwrap()
+    pub fn random_enemy<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        *ENEMIES.choose(rng).unwrap()
     }
 }
@@ -1,7 +1,9 @@
 use crate::enum_builder;
+use serde::Deserialize;
 
 enum_builder! {
     #[repr(u16)]
+    #[derive(Deserialize)]
     pub enum MapSetupId {
         SpiralMountain = 1821,
         MumbosMountain = 1822,
@@ -1,7 +1,9 @@
 use crate::enum_builder;
+use serde::Deserialize;
 
 enum_builder! {
     #[repr(u16)]
+    #[derive(Deserialize)]
     pub enum MidiId {
         Nothing = 3452,
         FinalBattleWithGrunty = 3453,
@@ -178,3 +180,89 @@ enum_builder! {
         TheEndFanfare = 3624,
     }
 }
+
+// the music of the worlds, their rooms and the lair (not the minigames, races and battles)
+pub const BACKGROUND_TRACKS: [MidiId; 51] = [
+    MidiId::MumbosMountain,
+    MidiId::MumbosMountainTickerstower,
+    MidiId::MumbosHut,
+    MidiId::TreasureTroveCove,
+    MidiId::TreasureTroveCoveInsideNipper,
+    MidiId::TreasureTroveCoveInsidethesandcastle,
+    MidiId::ClankersCavern,
+    MidiId::ClankersCavernInsideClanker,
+    MidiId::BubblegloopSwamp,
+    MidiId::BubblegloopSwampInteriors,
+    MidiId::FreezeezyPeak,
+    MidiId::FreezeezyPeakBoggysIglooSad,
+    MidiId::FreezeezyPeakBoggysIglooHappy,
+    MidiId::FreezeezyPeakInsideTheChristmasTree,
+    MidiId::FreezeezyPeakWozzasCave,
+    MidiId::GobisValley,
+    MidiId::GobisValleyInsidethepyramid,
+    MidiId::MadMonsterMansion,
+    MidiId::MadMonsterMansionInthemansion,
+    MidiId::MadMonsterMansionCemetery,
+    MidiId::MadMonsterMansionInsidethechurch,
+    MidiId::MadMonsterDownLoggo,
+    MidiId::RustyBucketBay,
+    MidiId::RustyBucketBayEngineRoom,
+    MidiId::RustyBucketBayInsideTheWarehouse,
+    MidiId::RustyBucketBayInsideCrewquarters,
+    MidiId::ClickClockWoodLobby,
+    MidiId::ClickClockWoodSpring,
+    MidiId::ClickClockWoodSummer,
+    MidiId::ClickClockWoodAutumn,
+    MidiId::ClickClockWoodWinter,
+    MidiId::ClickClockWoodNabnutsTheme,
+    MidiId::ClickClockWoodNabnutsStorageRoom,
+    MidiId::ClickClockWoodZubbasNest,
+    MidiId::ClickClockWoodTopOfTheTree,
+    MidiId::ClickClockWoodGnawtysHouse,
+    MidiId::SpiralMountain,
+    MidiId::SpiralMountainBridgetoGruntysLair,
+    MidiId::GruntysLairMumbosMountain,
+    MidiId::GruntysLairTreasureTroveCoveAndClankersCavern,
+    MidiId::GruntysLairTreasureTroveCoveAndClickClockWood,
+    MidiId::GruntysLairClankersCavernAndBubblegloopSwamp,
+    MidiId::GruntysLairBubblegloopSwampAndFreezeezyPeak,
+    MidiId::GruntysLairFreezeezyPeak,
+    MidiId::GruntysLairGobisValley,
+    MidiId::GruntysLairGobisValleyAndMadMonsterMansion,
+    MidiId::GruntysLairMadMonsterMansionAndRustyBucketBay,
+    MidiId::GruntysLairClickClockWoodAndFurnaceFun,
+    MidiId::TopOfTheGruntysLair,
+    MidiId::BrentildasTheme,
+    MidiId::CheatosTheme,
+];
+
+// the short tunes played when collecting something or opening a door
+pub const JINGLES: [MidiId; 27] = [
+    MidiId::NoteCollectSound,
+    MidiId::JinjoCollectSound,
+    MidiId::RedFeatherCollectSound,
+    MidiId::BlueEggCollectSound,
+    MidiId::JiggyCollectSound,
+    MidiId::GoldFeatherCollectSound,
+    MidiId::ExtraLifeCollectSound,
+    MidiId::HoneycombCollectSound,
+    MidiId::EmptyhoneycombCollectSound,
+    MidiId::Completeextrahoneycombjingle,
+    MidiId::MumbosMagicSound,
+    MidiId::CorrectFanfare,
+    MidiId::AllJinjosCollectedFanfare,
+    MidiId::HundreadNotesFanfare,
+    MidiId::JiggyAppearance,
+    MidiId::OpenNoteDoorFanfare,
+    MidiId::OpenNoteDoorFanfare2,
+    MidiId::WorldDoorOpeningJingle,
+    MidiId::WorldPuzzleCompletedFanfare,
+    MidiId::RedFeatherPillowFanfare,
+    MidiId::GoldFeatherPillowFanfare,
+    MidiId::BlueEggPillowFanfare,
+    MidiId::GobisValleySNSSecretsFanfare,
+    MidiId::TreasureTroveCoveSNSSecretsFanfare,
+    MidiId::FreezeezyPeakSNSSecretsFanfare,
+    MidiId::MadMonsterMansionSNSSecretsFanfare,
+    MidiId::ClickClockWoodSNSSecretsFanfare,
+];
@@ -1,7 +1,9 @@
 use crate::enum_builder;
+use serde::Deserialize;
 
 enum_builder! {
     #[repr(u16)]
+    #[derive(Deserialize)]
     pub enum ModelId {
         Model001D0758 = 721,
         Model001D0CE0 = 722,
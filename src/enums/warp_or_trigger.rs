@@ -1,7 +1,10 @@
 use crate::enum_builder;
+use serde::Deserialize;
+use serde::Serialize;
 
 enum_builder! {
     #[repr(u16)]
+    #[derive(Serialize, Deserialize)]
     pub enum WarpOrTriggerId {
         TriggerFeedingJuju = 0,
         TriggerOrangePad1 = 2,
@@ -0,0 +1,119 @@
+use std::error::Error;
+use std::fmt;
+
+#[derive(Debug)]
+pub enum RandoError {
+    /// one of the game files can't be opened
+    MissingFile {
+        name: &'static str,
+        source: std::io::Error,
+    },
+    /// the file hasn't been through xbdecompress
+    Compressed { name: &'static str },
+    EntryCount {
+        name: &'static str,
+        expected: u32,
+        found: u32,
+    },
+    /// a value that isn't what the parser knows how to read
+    Unexpected {
+        what: &'static str,
+        expected: String,
+        found: String,
+    },
+    /// an asset of db360.cmp that can't be read, `offset` is where it failed
+    Asset {
+        id: usize,
+        asset: String,
+        start: u64,
+        offset: u64,
+        source: Box<dyn Error>,
+    },
+    /// a file written by `dump` that can't be packed back
+    Dumped {
+        file: String,
+        source: Box<dyn Error>,
+    },
+}
+
+impl RandoError {
+    pub fn unexpected(
+        what: &'static str,
+        expected: impl fmt::Display,
+        found: impl fmt::Display,
+    ) -> Self {
+        RandoError::Unexpected {
+            what,
+            expected: expected.to_string(),
+            found: found.to_string(),
+        }
+    }
+}
+
+/// Errors out if a value read from a file isn't the only one it can be.
+pub fn expect_value<T: PartialEq + fmt::UpperHex>(
+    what: &'static str,
+    expected: T,
+    found: T,
+) -> Result<(), RandoError> {
+    if found == expected {
+        Ok(())
+    } else {
+        Err(RandoError::unexpected(
+            what,
+            format!("0x{expected:X}"),
+            format!("0x{found:X}"),
+        ))
+    }
+}
+
+impl fmt::Display for RandoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RandoError::MissingFile { name, source } => {
+                write!(f, "Can't open {name}, missing? ({source})")
+            }
+            RandoError::Compressed { name } => {
+                write!(
+                    f,
+                    "{name} looks compressed, decompress it with xbdecompress first"
+                )
+            }
+            RandoError::EntryCount {
+                name,
+                expected,
+                found,
+            } => write!(
+                f,
+                "unexpected entry count {found} in {name} (expected {expected}), wrong version of the game?"
+            ),
+            RandoError::Unexpected {
+                what,
+                expected,
+                found,
+            } => write!(f, "expected {what} {expected}, found {found}"),
+            RandoError::Asset {
+                id,
+                asset,
+                start,
+                offset,
+                source,
+            } => write!(
+                f,
+                "can't read asset {id} ({asset}) of db360.cmp at offset 0x{offset:X} (it starts at 0x{start:X}): {source}"
+            ),
+            RandoError::Dumped { file, source } => write!(f, "can't pack {file}: {source}"),
+        }
+    }
+}
+
+impl Error for RandoError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            RandoError::MissingFile { source, .. } => Some(source),
+            RandoError::Asset { source, .. } => Some(source.as_ref()),
+            RandoError::Dumped { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,488 @@
+use crate::data::NOTE_DOORS_COSTS;
+use crate::data::levels::LEVELS_INFO;
+use crate::enums::Ability;
+use crate::enums::ActorId;
+use crate::enums::Language;
+use crate::enums::MapSetupId;
+use crate::enums::SpritePropId;
+use crate::enums::midis::MidiId;
+use crate::utils::convert_to_banjo_string;
+use serde::Deserialize;
+use std::error::Error;
+use std::fmt;
+use std::path::PathBuf;
+
+pub mod assets;
+mod data;
+pub mod enums;
+pub mod error;
+mod logic;
+mod ppc;
+pub mod strings;
+pub mod textures;
+mod utils;
+
+pub use data::levels::LevelOrder;
+pub use error::RandoError;
+pub use logic::check::Report;
+pub use logic::randomizer::Randomizer;
+pub use logic::spoiler::Spoiler;
+pub use utils::Vector2;
+pub use utils::Vector3;
+
+#[derive(Debug, Default, Clone, Deserialize)]
+pub struct Config {
+    #[serde(default)]
+    pub seed: Option<u64>,
+
+    #[serde(default)]
+    pub actors: Vec<ActorId>,
+
+    #[serde(default)]
+    pub sprites: Vec<SpritePropId>,
+
+    #[serde(default)]
+    pub mix: bool,
+
+    #[serde(default)]
+    pub pool: Pool,
+
+    #[serde(default)]
+    pub worlds: bool,
+
+    #[serde(default)]
+    pub entrances: bool,
+
+    #[serde(default)]
+    pub moves: bool,
+
+    #[serde(default)]
+    pub notedoors: Vec<u32>,
+
+    #[serde(default)]
+    pub notedoor_costs: Option<Costs>,
+
+    #[serde(default)]
+    pub pipes: bool,
+
+    #[serde(default)]
+    pub cauldrons: bool,
+
+    #[serde(default)]
+    pub skip_furnace_fun: bool,
+
+    #[serde(default)]
+    pub mix_questions: bool,
+
+    #[serde(default)]
+    pub seed_questions: usize,
+
+    #[serde(default)]
+    pub questions: Vec<CustomQuestion>,
+
+    #[serde(default)]
+    pub enemies: bool,
+
+    #[serde(default)]
+    pub music: bool,
+
+    #[serde(default)]
+    pub jingles: bool,
+
+    #[serde(default)]
+    pub custom_music: Vec<CustomMusic>,
+
+    #[serde(default)]
+    pub custom_textures: Vec<CustomTexture>,
+
+    #[serde(default)]
+    pub easy_talon_trot: bool,
+
+    #[serde(default)]
+    pub hints: Option<Hints>,
+
+    #[serde(default)]
+    pub plando: Plando,
+}
+
+// either every value, or a range to pick them from
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum Costs {
+    Fixed(Vec<u32>),
+    Random { min: u32, max: u32 },
+}
+
+// which worlds share their items
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Pool {
+    // items stay in their world
+    #[default]
+    Level,
+    // items move between the worlds of the same group, the others stay in their world
+    Groups(Vec<Vec<LevelOrder>>),
+    // items move anywhere, the lair included
+    Global,
+}
+
+// a MIDI file replacing one of the tracks
+#[derive(Debug, Clone, Deserialize)]
+pub struct CustomMusic {
+    pub track: MidiId,
+    pub file: PathBuf,
+}
+
+// a PNG replacing one of the textures of db360.textures.cmp
+#[derive(Debug, Clone, Deserialize)]
+pub struct CustomTexture {
+    pub texture: usize,
+    pub file: PathBuf,
+}
+
+// a Furnace Fun question, the right answer first
+#[derive(Debug, Clone, Deserialize)]
+pub struct CustomQuestion {
+    pub question: String,
+    pub answers: [String; 3],
+
+    // the languages not listed use the English text
+    #[serde(default)]
+    pub translations: Vec<QuestionTranslation>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct QuestionTranslation {
+    pub language: Language,
+    pub question: String,
+    pub answers: [String; 3],
+}
+
+// how often each kind of Brentilda's hints is picked
+#[derive(Debug, Clone, Deserialize)]
+pub struct Hints {
+    #[serde(default)]
+    pub items: u32,
+
+    #[serde(default)]
+    pub moves: u32,
+
+    #[serde(default)]
+    pub worlds: u32,
+}
+
+// placements forced before the random ones, the entries are the same as the spoiler's
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Plando {
+    #[serde(default)]
+    pub items: Vec<PlandoItem>,
+
+    #[serde(default)]
+    pub molehills: Vec<PlandoMolehill>,
+
+    #[serde(default)]
+    pub worlds: Vec<PlandoWorld>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PlandoItem {
+    pub map: MapSetupId,
+    pub cube: usize,
+    pub position: Vector3<i16>,
+    pub item: PlandoProp,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum PlandoProp {
+    Actor(ActorId),
+    Sprite(SpritePropId),
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PlandoMolehill {
+    // the ability taught by this molehill in the original game
+    pub original: Ability,
+    pub ability: Ability,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PlandoWorld {
+    pub entrance: LevelOrder,
+    pub world: LevelOrder,
+}
+
+impl fmt::Display for Pool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Pool::Level => write!(f, "level"),
+            Pool::Groups(groups) => {
+                let groups = groups.iter().map(|g| list(g)).collect::<Vec<_>>();
+                write!(f, "groups {}", groups.join(";"))
+            }
+            Pool::Global => write!(f, "global"),
+        }
+    }
+}
+
+impl fmt::Display for PlandoProp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlandoProp::Actor(id) => write!(f, "{id}"),
+            PlandoProp::Sprite(id) => write!(f, "{id}"),
+        }
+    }
+}
+
+impl Config {
+    /// Checks the options that can't be used together.
+    pub fn validate(&self) -> Result<(), Box<dyn Error>> {
+        if !self.notedoors.iter().all(|c| NOTE_DOORS_COSTS.contains(c)) {
+            return Err("Invalid note door!".into());
+        }
+
+        if let Some(Costs::Random { min, max }) = &self.notedoor_costs
+            && min > max
+        {
+            return Err(format!("Invalid costs range: {min} is more than {max}!").into());
+        }
+
+        if self.actors.iter().any(|c| c.is_enemy()) {
+            return Err("Can't shuffle enemies with other actors!".into());
+        }
+
+        if let Pool::Groups(groups) = &self.pool {
+            let levels = groups.iter().flatten().collect::<Vec<_>>();
+            for (i, level) in levels.iter().enumerate() {
+                if let LevelOrder::Unknown(_) = level {
+                    return Err(format!("Unknown world in pool: {level}").into());
+                }
+                if levels[..i].contains(level) {
+                    return Err(format!("{level} is in several pools!").into());
+                }
+            }
+        }
+
+        if let Some(hints) = &self.hints
+            && hints.items + hints.moves + hints.worlds == 0
+        {
+            return Err("Every kind of hint is disabled!".into());
+        }
+
+        for question in &self.questions {
+            let translations = question
+                .translations
+                .iter()
+                .map(|t| (&t.question, &t.answers));
+            for (text, answers) in [(&question.question, &question.answers)]
+                .into_iter()
+                .chain(translations)
+            {
+                for text in [text].into_iter().chain(answers) {
+                    if convert_to_banjo_string(&text.to_uppercase()).is_err() {
+                        return Err(
+                            format!("The game can't display the question \"{text}\"!").into()
+                        );
+                    }
+                }
+            }
+        }
+
+        self.validate_plando()?;
+
+        Ok(())
+    }
+
+    fn validate_plando(&self) -> Result<(), Box<dyn Error>> {
+        let plando = &self.plando;
+
+        for (i, pin) in plando.items.iter().enumerate() {
+            let shuffled = match pin.item {
+                PlandoProp::Actor(id) => self.actors.contains(&id),
+                PlandoProp::Sprite(id) => self.sprites.contains(&id),
+            };
+            if !shuffled {
+                return Err(format!("Plando: {} isn't shuffled!", pin.item).into());
+            }
+
+            let same_location = |p: &PlandoItem| {
+                p.map == pin.map && p.cube == pin.cube && p.position == pin.position
+            };
+            if plando.items[..i].iter().any(same_location) {
+                return Err(format!(
+                    "Plando: several items at {:?} in {} (cube {})!",
+                    pin.position, pin.map, pin.cube
+                )
+                .into());
+            }
+        }
+
+        if !plando.molehills.is_empty() && (!self.worlds || self.moves) {
+            return Err(
+                "Plando: molehills are only shuffled with `worlds` and without `moves`!".into(),
+            );
+        }
+
+        let taught = LEVELS_INFO
+            .iter()
+            .flat_map(|l| l.molehills)
+            .map(|m| m.ability)
+            .collect::<Vec<_>>();
+        for (i, pin) in plando.molehills.iter().enumerate() {
+            for ability in [pin.original, pin.ability] {
+                if !taught.contains(&ability) {
+                    return Err(format!("Plando: no molehill teaches {ability}!").into());
+                }
+            }
+
+            let previous = &plando.molehills[..i];
+            if previous.iter().any(|p| p.original == pin.original) {
+                return Err(format!(
+                    "Plando: the {} molehill is set several times!",
+                    pin.original
+                )
+                .into());
+            }
+            if previous.iter().any(|p| p.ability == pin.ability) {
+                return Err(
+                    format!("Plando: {} is taught by several molehills!", pin.ability).into(),
+                );
+            }
+        }
+
+        if !plando.worlds.is_empty() && !self.worlds {
+            return Err("Plando: worlds need `worlds`!".into());
+        }
+
+        for (i, pin) in plando.worlds.iter().enumerate() {
+            for level in [pin.entrance, pin.world] {
+                if matches!(level, LevelOrder::Lair | LevelOrder::Unknown(_)) {
+                    return Err(format!("Plando: {level} isn't a world!").into());
+                }
+            }
+
+            let previous = &plando.worlds[..i];
+            if previous.iter().any(|p| p.entrance == pin.entrance) {
+                return Err(format!(
+                    "Plando: the {} entrance is set several times!",
+                    pin.entrance
+                )
+                .into());
+            }
+            if previous.iter().any(|p| p.world == pin.world) {
+                return Err(format!("Plando: {} is behind several entrances!", pin.world).into());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Short hash of the options (without the seed), shown next to the seed in game.
+    /// The custom files are read: the same files elsewhere give the same hash.
+    pub fn settings_hash(&self) -> Result<String, Box<dyn Error>> {
+        // every option but the seed, written the same way whatever the Rust version
+        let mut settings = vec![
+            format!("actors {}", list(&self.actors)),
+            format!("sprites {}", list(&self.sprites)),
+            format!("mix {}", self.mix),
+            format!("pool {}", self.pool),
+            format!("worlds {}", self.worlds),
+            format!("entrances {}", self.entrances),
+            format!("moves {}", self.moves),
+            format!("notedoors {}", list(&self.notedoors)),
+        ];
+
+        settings.push(match &self.notedoor_costs {
+            None => "notedoor_costs".into(),
+            Some(Costs::Fixed(costs)) => format!("notedoor_costs {}", list(costs)),
+            Some(Costs::Random { min, max }) => format!("notedoor_costs {min}..{max}"),
+        });
+
+        settings.extend([
+            format!("pipes {}", self.pipes),
+            format!("cauldrons {}", self.cauldrons),
+            format!("skip_furnace_fun {}", self.skip_furnace_fun),
+            format!("mix_questions {}", self.mix_questions),
+            format!("seed_questions {}", self.seed_questions),
+        ]);
+
+        for question in &self.questions {
+            let translations = question
+                .translations
+                .iter()
+                .map(|t| (t.language.to_string(), &t.question, &t.answers));
+            for (language, text, answers) in [("".into(), &question.question, &question.answers)]
+                .into_iter()
+                .chain(translations)
+            {
+                settings.push(format!("question {language} {text}"));
+                settings.extend(answers.iter().map(|a| format!("answer {a}")));
+            }
+        }
+
+        settings.extend([
+            format!("enemies {}", self.enemies),
+            format!("music {}", self.music),
+            format!("jingles {}", self.jingles),
+        ]);
+
+        // the files count by what's in them, not by where they are
+        for music in &self.custom_music {
+            let contents = std::fs::read(&music.file)
+                .map_err(|e| format!("Can't read {}: {e}", music.file.display()))?;
+            settings.push(format!(
+                "custom_music {} {:08X}",
+                music.track,
+                fnv1a(&contents)
+            ));
+        }
+        for texture in &self.custom_textures {
+            let contents = std::fs::read(&texture.file)
+                .map_err(|e| format!("Can't read {}: {e}", texture.file.display()))?;
+            settings.push(format!(
+                "custom_texture {} {:08X}",
+                texture.texture,
+                fnv1a(&contents)
+            ));
+        }
+
+        settings.push(format!("easy_talon_trot {}", self.easy_talon_trot));
+        if let Some(hints) = &self.hints {
+            settings.push(format!(
+                "hints {} {} {}",
+                hints.items, hints.moves, hints.worlds
+            ));
+        }
+
+        for pin in &self.plando.items {
+            let position = pin.position;
+            settings.push(format!(
+                "plando {} {} {} {} {} {}",
+                pin.map, pin.cube, position.x, position.y, position.z, pin.item
+            ));
+        }
+        for pin in &self.plando.molehills {
+            settings.push(format!("plando {} {}", pin.original, pin.ability));
+        }
+        for pin in &self.plando.worlds {
+            settings.push(format!("plando {} {}", pin.entrance, pin.world));
+        }
+
+        Ok(format!("{:08X}", fnv1a(settings.join("\n").as_bytes())))
+    }
+}
+
+fn list<T: fmt::Display>(values: &[T]) -> String {
+    values
+        .iter()
+        .map(|v| v.to_string())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+// FNV-1a, unlike `DefaultHasher` it won't change with the Rust version
+fn fnv1a(bytes: &[u8]) -> u32 {
+    bytes.iter().fold(0x811C9DC5u32, |hash, b| {
+        (hash ^ *b as u32).wrapping_mul(0x01000193)
+    })
+}
@@ -1,3 +1,9 @@
+pub mod check;
 mod enums;
+mod fill;
+mod hints;
 mod location;
+pub mod progression;
+mod quiz;
 pub mod randomizer;
+pub mod spoiler;
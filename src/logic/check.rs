@@ -0,0 +1,209 @@
+use super::location::Location;
+use super::progression::Inventory;
+use super::progression::Progression;
+use crate::Config;
+use crate::data::entrances::FINAL_MAP;
+use crate::data::entrances::Requirement;
+use crate::data::levels::LEVELS_INFO;
+use crate::data::levels::LevelOrder;
+use crate::enums::Ability;
+use crate::enums::MapSetupId;
+use crate::utils::Vector3;
+use std::fmt;
+
+/// What a playthrough of the randomized game can't get to.
+pub struct Report {
+    pub beatable: bool,
+    pub unreachable: Vec<Location>,
+    // jiggies that aren't props, per map
+    pub unreachable_events: Vec<(MapSetupId, u32)>,
+    // where the player is stuck, if anything can't be reached
+    pub stall: Option<Stall>,
+}
+
+/// The state of the playthrough once nothing else can be collected.
+pub struct Stall {
+    pub inventory: Inventory,
+    pub maps: Vec<MapSetupId>,
+    // from, to and what's missing to go there
+    pub blocked: Vec<(MapSetupId, MapSetupId, Vec<Requirement>)>,
+    // to describe the requirements
+    progression: Progression,
+}
+
+impl Report {
+    pub fn is_ok(&self) -> bool {
+        self.beatable && self.unreachable.is_empty() && self.unreachable_events.is_empty()
+    }
+}
+
+/// Collects everything it can from the start of the game, and reports what's left.
+pub fn check(progression: &Progression, config: &Config, collectibles: &[Location]) -> Report {
+    let items = collectibles
+        .iter()
+        .map(|l| (l.map_id, l.prop.item()))
+        .collect::<Vec<_>>();
+
+    let (inventory, maps) = progression.sweep(progression.start_inventory(config), &items);
+
+    let unreachable = collectibles
+        .iter()
+        .filter(|l| l.prop.item().is_progression() && !maps.contains(&l.map_id))
+        .cloned()
+        .collect::<Vec<_>>();
+
+    let unreachable_events = progression
+        .event_jiggies
+        .iter()
+        .filter(|(map, count)| *count > 0 && !maps.contains(map))
+        .copied()
+        .collect::<Vec<_>>();
+
+    let beatable = maps.contains(&FINAL_MAP);
+
+    let stall = if beatable && unreachable.is_empty() && unreachable_events.is_empty() {
+        None
+    } else {
+        let (_, paths) = progression.explore(&inventory);
+
+        let mut blocked = vec![];
+        for path in paths {
+            let entry = (
+                path.from,
+                path.to,
+                progression.missing(&inventory, path.required, path.from),
+            );
+            if !blocked.contains(&entry) {
+                blocked.push(entry);
+            }
+        }
+
+        Some(Stall {
+            inventory,
+            maps,
+            blocked,
+            progression: progression.clone(),
+        })
+    };
+
+    Report {
+        beatable,
+        unreachable,
+        unreachable_events,
+        stall,
+    }
+}
+
+impl Stall {
+    fn describe(&self, requirement: &Requirement) -> String {
+        match requirement {
+            Requirement::Ability(ability) => ability.to_string(),
+            Requirement::Transformation(transformation) => {
+                match self
+                    .progression
+                    .mumbo_skulls
+                    .iter()
+                    .find(|s| s.transformation == *transformation)
+                {
+                    Some(skull) => format!(
+                        "{transformation:?} ({} tokens in {})",
+                        skull.cost, skull.map
+                    ),
+                    None => format!("{transformation:?}"),
+                }
+            }
+            Requirement::Flag(flag) => flag.to_string(),
+            Requirement::NoteDoor(door) => {
+                format!("{} notes door", self.progression.note_doors_costs[*door])
+            }
+            Requirement::Puzzle(LevelOrder::Lair) => format!(
+                "tower puzzle ({} jiggies)",
+                self.progression.puzzle_cost(LevelOrder::Lair)
+            ),
+            Requirement::Puzzle(level) => format!(
+                "{level} puzzle ({} jiggies)",
+                self.progression.puzzle_cost(*level)
+            ),
+        }
+    }
+}
+
+impl fmt::Display for Stall {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let inventory = &self.inventory;
+        writeln!(
+            f,
+            "Progress stalls with {} jiggies, {} notes and {} Mumbo tokens",
+            inventory.jiggies, inventory.notes, inventory.tokens
+        )?;
+
+        let missing = (0..=0x13u8)
+            .map(Ability::from)
+            .filter(|a| !inventory.abilities.contains(a))
+            .map(|a| a.to_string())
+            .collect::<Vec<_>>();
+        if !missing.is_empty() {
+            writeln!(f, "    missing abilities: {}", missing.join(", "))?;
+        }
+
+        let puzzles = inventory
+            .puzzles
+            .iter()
+            .map(|l| l.to_string())
+            .collect::<Vec<_>>();
+        writeln!(f, "    puzzles done: {}", puzzles.join(", "))?;
+
+        let worlds = LEVELS_INFO
+            .iter()
+            .filter(|l| l.maps.first().is_some_and(|m| self.maps.contains(m)))
+            .map(|l| l.maps[0].to_string())
+            .collect::<Vec<_>>();
+        writeln!(f, "    worlds open: {}", worlds.join(", "))?;
+
+        writeln!(f, "    blocked paths:")?;
+        for (from, to, missing) in &self.blocked {
+            let missing = missing.iter().map(|r| self.describe(r)).collect::<Vec<_>>();
+            writeln!(f, "        {from} -> {to}: {}", missing.join(", "))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_ok() {
+            return writeln!(
+                f,
+                "Everything can be collected and the top of Grunty's tower can be reached"
+            );
+        }
+
+        if !self.beatable {
+            writeln!(f, "The top of Grunty's tower can't be reached!")?;
+        }
+
+        if let Some(stall) = &self.stall {
+            write!(f, "{stall}")?;
+        }
+
+        if !self.unreachable.is_empty() || !self.unreachable_events.is_empty() {
+            writeln!(f, "Unreachable:")?;
+        }
+
+        for location in &self.unreachable {
+            let Vector3 { x, y, z } = location.position;
+            writeln!(
+                f,
+                "    {} [cube {}] ({x}, {y}, {z}): {}",
+                location.map_id, location.cube_id, location.prop
+            )?;
+        }
+
+        for (map, count) in &self.unreachable_events {
+            writeln!(f, "    {map}: {count} Jiggies given by events")?;
+        }
+
+        Ok(())
+    }
+}
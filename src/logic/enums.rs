@@ -1,7 +1,81 @@
-use crate::assets::map_setup::{Prop1, Prop2};
+use crate::assets::map_setup::{Category, Prop1, Prop2};
+use crate::enums::ActorId;
+use crate::enums::SpritePropId;
+use crate::utils::Vector3;
 
 #[derive(Copy, Clone)]
 pub enum Props {
     Prop1(Prop1),
     Prop2(Prop2),
 }
+
+impl std::fmt::Display for Props {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Props::Prop1(Prop1 {
+                category: Category::Actor(id),
+                ..
+            }) => write!(f, "{id}"),
+            Props::Prop1(prop) => write!(f, "{}", prop.category),
+            Props::Prop2(Prop2::Sprite { id, .. }) => write!(f, "{id}"),
+            Props::Prop2(Prop2::Actor { .. }) => write!(f, "Prop2Actor"),
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum Item {
+    Jiggy,
+    MumboToken,
+    MusicalNote,
+    Other,
+}
+
+impl Item {
+    pub fn is_progression(&self) -> bool {
+        *self != Item::Other
+    }
+}
+
+impl Props {
+    pub fn item(&self) -> Item {
+        match self {
+            Props::Prop1(Prop1 {
+                category: Category::Actor(ActorId::Jiggy),
+                ..
+            }) => Item::Jiggy,
+            Props::Prop1(Prop1 {
+                category: Category::Actor(ActorId::MumboToken),
+                ..
+            }) => Item::MumboToken,
+            Props::Prop2(Prop2::Sprite {
+                id: SpritePropId::MusicalNote,
+                ..
+            }) => Item::MusicalNote,
+            _ => Item::Other,
+        }
+    }
+
+    // notes too, the game keeps a note score per world
+    pub fn stays_in_world(&self) -> bool {
+        match self {
+            Props::Prop1(Prop1 {
+                category: Category::Actor(id),
+                ..
+            }) => id.stays_in_world(),
+            Props::Prop2(Prop2::Sprite {
+                id: SpritePropId::MusicalNote,
+                ..
+            }) => true,
+            _ => false,
+        }
+    }
+
+    pub fn position(&self) -> Vector3<i16> {
+        match self {
+            Props::Prop1(prop) => prop.position,
+            Props::Prop2(Prop2::Actor { position, .. }) => *position,
+            Props::Prop2(Prop2::Sprite { position, .. }) => *position,
+        }
+    }
+}
@@ -0,0 +1,174 @@
+use super::enums::Item;
+use super::enums::Props;
+use super::location::Location;
+use super::progression::Progression;
+use crate::Config;
+use crate::assets::map_setup::Prop1;
+use crate::enums::MapSetupId;
+use rand::Rng;
+use rand::prelude::IndexedRandom;
+use rand::prelude::SliceRandom;
+use std::error::Error;
+
+const MAX_ATTEMPTS: usize = 50;
+
+type Placement = Vec<Vec<(Props, Option<Prop1>)>>;
+
+/// Assumed fill: progression items are placed one at a time, only where they can be
+/// reached with every item that hasn't been placed yet, then the rest fills the gaps.
+/// Items only move between the locations of their own pool, `fixed` are the
+/// progression items that aren't shuffled.
+pub fn fill<R: Rng + ?Sized>(
+    rng: &mut R,
+    progression: &Progression,
+    config: &Config,
+    pools: &mut [Vec<Location>],
+    fixed: &[(MapSetupId, Item)],
+) -> Result<(), Box<dyn Error>> {
+    for _ in 0..MAX_ATTEMPTS {
+        let Some(placement) = try_fill(rng, progression, config, pools, fixed) else {
+            continue;
+        };
+
+        for (pool, items) in pools.iter_mut().zip(placement) {
+            for (location, (prop, linked)) in pool.iter_mut().zip(items) {
+                location.prop = prop;
+                location.linked = linked;
+            }
+        }
+
+        return Ok(());
+    }
+
+    Err(format!(
+        "Couldn't place the items so that the game can be finished after {MAX_ATTEMPTS} attempts"
+    )
+    .into())
+}
+
+fn try_fill<R: Rng + ?Sized>(
+    rng: &mut R,
+    progression: &Progression,
+    config: &Config,
+    pools: &[Vec<Location>],
+    fixed: &[(MapSetupId, Item)],
+) -> Option<Placement> {
+    let mut slots = pools
+        .iter()
+        .map(|p| vec![None; p.len()])
+        .collect::<Vec<_>>();
+
+    let mut items = vec![];
+    let mut filler = vec![];
+    for (pool_id, pool) in pools.iter().enumerate() {
+        for location in pool {
+            let item = (pool_id, location.prop, location.linked);
+            if location.prop.item().is_progression() {
+                items.push(item);
+            } else {
+                filler.push(item);
+            }
+        }
+    }
+
+    items.shuffle(rng);
+
+    let mut placed = fixed.to_vec();
+    while let Some((pool_id, prop, linked)) = items.pop() {
+        // assume we already have everything that's left to place
+        let mut inventory = progression.start_inventory(config);
+        for (_, item, _) in &items {
+            inventory.add(item.item());
+        }
+
+        let (_, maps) = progression.sweep(inventory, &placed);
+
+        let candidates = (0..slots[pool_id].len())
+            .filter(|&i| slots[pool_id][i].is_none() && maps.contains(&pools[pool_id][i].map_id))
+            .collect::<Vec<_>>();
+
+        let &slot = candidates.choose(rng)?;
+        slots[pool_id][slot] = Some((prop, linked));
+        placed.push((pools[pool_id][slot].map_id, prop.item()));
+    }
+
+    filler.shuffle(rng);
+    for (pool_id, prop, linked) in filler {
+        let slot = slots[pool_id].iter().position(Option::is_none)?;
+        slots[pool_id][slot] = Some((prop, linked));
+    }
+
+    if !progression.is_beatable(config, &placed) {
+        return None;
+    }
+
+    slots
+        .into_iter()
+        .map(|pool| pool.into_iter().collect::<Option<Vec<_>>>())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assets::map_setup::Category;
+    use crate::data::NOTE_DOORS_COSTS;
+    use crate::enums::ActorId;
+    use crate::utils::Vector3;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn location(map_id: MapSetupId, category: Category) -> Location {
+        let prop = Prop1 {
+            position: Vector3 { x: 0, y: 0, z: 0 },
+            selector_or_radius: 0,
+            category,
+            unk_bit_0: 0,
+            marker_id: 0,
+            byte_0b: 0,
+            bitfield_0c: 0,
+            bitfield_10: 0,
+        };
+
+        Location {
+            map_id,
+            cube_id: 0,
+            position: prop.position,
+            prop: Props::Prop1(prop),
+            linked: None,
+        }
+    }
+
+    #[test]
+    fn tokens_not_behind_their_transformation() {
+        // every door open, the only thing left to find is Termite's 5 tokens
+        let config = Config {
+            moves: true,
+            notedoors: NOTE_DOORS_COSTS.to_vec(),
+            ..Default::default()
+        };
+        let progression = Progression {
+            event_jiggies: vec![(MapSetupId::SpiralMountain, 100)],
+            ..Default::default()
+        };
+
+        for seed in 0..20 {
+            let mut pool = vec![];
+            for _ in 0..5 {
+                let token = Category::Actor(ActorId::MumboToken);
+                pool.push(location(MapSetupId::MumbosMountain, token));
+                pool.push(location(MapSetupId::TickersTower, Category::Flags(0)));
+            }
+            let mut pools = [pool];
+
+            let mut rng = ChaCha8Rng::seed_from_u64(seed);
+            fill(&mut rng, &progression, &config, &mut pools, &[]).unwrap();
+
+            for location in &pools[0] {
+                if location.prop.item() == Item::MumboToken {
+                    assert_ne!(location.map_id, MapSetupId::TickersTower, "seed {seed}");
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,202 @@
+use super::enums::Item;
+use crate::Hints;
+use crate::data::levels::LAIR_MAPS;
+use crate::data::levels::LEVELS_INFO;
+use crate::data::levels::LevelOrder;
+use crate::enums::Ability;
+use crate::enums::Language;
+use crate::enums::MapSetupId;
+use rand::Rng;
+use rand::prelude::IndexedMutRandom;
+
+#[derive(Clone, Copy)]
+pub enum Hint {
+    // a shuffled item and the map it's in
+    Item(Item, MapSetupId),
+    // a molehill move and the world it's taught in
+    Move(Ability, LevelOrder),
+    // an entrance and the world behind it
+    World(LevelOrder, LevelOrder),
+}
+
+impl Hint {
+    pub fn text(&self, lang: Language) -> String {
+        match self {
+            Hint::Item(item, map) => {
+                let map = map_name(*map, lang);
+                match lang {
+                    Language::French => format!("{} SE TROUVE DANS {map}.", item_name(*item, lang)),
+                    Language::German => format!("{} IST IN {map}.", item_name(*item, lang)),
+                    Language::Japanese => format!("{}は {map}に あるよ。", item_name(*item, lang)),
+                    _ => format!("{} IS IN {map}.", item_name(*item, lang)),
+                }
+            }
+            Hint::Move(ability, level) => {
+                let move_name = ability_name(*ability);
+                let level = world_name(*level, lang);
+                match lang {
+                    Language::French => {
+                        format!("BOTTLES ENSEIGNE {move_name} DANS {level}.")
+                    }
+                    Language::German => format!("BOTTLES LEHRT {move_name} IN {level}."),
+                    Language::Japanese => {
+                        format!("BOTTLESは {level}で {move_name}を おしえてくれるよ。")
+                    }
+                    _ => format!("BOTTLES TEACHES {move_name} IN {level}."),
+                }
+            }
+            Hint::World(entrance, level) => {
+                let entrance = world_name(*entrance, lang);
+                let level = world_name(*level, lang);
+                match lang {
+                    Language::French => format!("L'ENTRÉE DE {entrance} MÈNE À {level}.")
+                        .replace("DE LE ", "DU ")
+                        .replace("À LE ", "AU "),
+                    Language::German => {
+                        format!("DER EINGANG VON {entrance} FÜHRT NACH {level}.")
+                    }
+                    Language::Japanese => {
+                        format!("{entrance}の いりぐちは {level}に つながってるよ。")
+                    }
+                    _ => format!("THE ENTRANCE OF {entrance} LEADS TO {level}."),
+                }
+            }
+        }
+    }
+}
+
+/// Picks `count` hints (or less if there isn't enough), each kind as often as its weight in `mix`.
+pub fn pick_hints<R: Rng + ?Sized>(
+    rng: &mut R,
+    mix: &Hints,
+    items: Vec<Hint>,
+    moves: Vec<Hint>,
+    worlds: Vec<Hint>,
+    count: usize,
+) -> Vec<Hint> {
+    let mut kinds = vec![(mix.items, items), (mix.moves, moves), (mix.worlds, worlds)];
+
+    let mut hints = vec![];
+    while hints.len() < count {
+        kinds.retain(|(weight, candidates)| *weight > 0 && !candidates.is_empty());
+        let Ok((_, candidates)) = kinds.choose_weighted_mut(rng, |(weight, _)| *weight) else {
+            break;
+        };
+
+        let index = rng.random_range(0..candidates.len());
+        hints.push(candidates.swap_remove(index));
+    }
+
+    hints
+}
+
+// `get_name` doesn't have the Japanese names, these stay in English (the Japanese
+// characters have the capital letters)
+pub fn world_name(level: LevelOrder, lang: Language) -> &'static str {
+    match lang {
+        Language::French | Language::German => level.get_name(lang),
+        _ => level.get_name(Language::English),
+    }
+}
+
+fn map_name(map: MapSetupId, lang: Language) -> String {
+    if LAIR_MAPS.contains(&map) {
+        return world_name(LevelOrder::Lair, lang).into();
+    }
+
+    let Some(level) = (0..LEVELS_INFO.len())
+        .map(LevelOrder::from)
+        .find(|l| *l != LevelOrder::Lair && LEVELS_INFO[*l].maps.contains(&map))
+    else {
+        return room_name(map);
+    };
+
+    let world = world_name(level, lang);
+    if LEVELS_INFO[level].maps[0] == map {
+        world.into()
+    } else {
+        format!("{} ({world})", room_name(map))
+    }
+}
+
+// "CcwSummerZubbaHive" -> "SUMMER ZUBBA HIVE"
+fn room_name(map: MapSetupId) -> String {
+    let mut words = vec![];
+    for c in map.to_string().chars() {
+        if c.is_uppercase() || words.is_empty() {
+            words.push(String::new());
+        }
+        words.last_mut().unwrap().push(c.to_ascii_uppercase());
+    }
+
+    // the world is already named
+    const PREFIXES: [&str; 9] = ["MM", "MMM", "TTC", "CC", "BGS", "FP", "GV", "RBB", "CCW"];
+    if words.len() > 1 && PREFIXES.contains(&words[0].as_str()) {
+        words.remove(0);
+    }
+
+    words.join(" ")
+}
+
+fn item_name(item: Item, lang: Language) -> &'static str {
+    match (item, lang) {
+        (Item::Jiggy, Language::French) => "UN JIGGY",
+        (Item::Jiggy, Language::German) => "EIN JIGGY",
+        (Item::Jiggy, Language::Japanese) => "ジグソー",
+        (Item::Jiggy, _) => "A JIGGY",
+        (Item::MumboToken, Language::French) => "UN JETON MUMBO",
+        (Item::MumboToken, Language::German) => "EIN MUMBO-TOKEN",
+        (Item::MumboToken, Language::Japanese) => "マンボトークン",
+        (Item::MumboToken, _) => "A MUMBO TOKEN",
+        (Item::MusicalNote, Language::French) => "UNE NOTE",
+        (Item::MusicalNote, Language::German) => "EINE NOTE",
+        (Item::MusicalNote, Language::Japanese) => "おんぷ",
+        (Item::MusicalNote, _) => "A NOTE",
+        (Item::Other, Language::Japanese) => "なにか",
+        (Item::Other, _) => "SOMETHING",
+    }
+}
+
+// same names as bottles' dialogues
+pub fn ability_name(ability: Ability) -> &'static str {
+    match ability {
+        Ability::Eggs => "EGGS",
+        Ability::BeakBuster => "BEAK BUSTER",
+        Ability::TalonTrot => "TALON TROT",
+        Ability::ShockJump => "SHOCK JUMP",
+        Ability::Flight => "FLIGHT",
+        Ability::Wonderwing => "WONDERWING",
+        Ability::WadingBoots => "WADING BOOTS",
+        Ability::BeakBomb => "BEAK BOMB",
+        Ability::TurboTalon => "TURBO TALON TROT",
+        _ => "A MOVE",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::convert_to_banjo_string;
+
+    #[test]
+    fn hints_can_be_displayed() {
+        let levels = (0..LEVELS_INFO.len()).map(LevelOrder::from);
+        let maps = LEVELS_INFO.iter().flat_map(|l| l.maps).chain(&LAIR_MAPS);
+
+        let mut hints = vec![];
+        for level in levels.clone() {
+            hints.extend(levels.clone().map(|l| Hint::World(level, l)));
+            hints.push(Hint::Move(Ability::BeakBuster, level));
+        }
+        for item in [Item::Jiggy, Item::MumboToken, Item::MusicalNote] {
+            hints.extend(maps.clone().map(|m| Hint::Item(item, *m)));
+        }
+
+        for hint in hints {
+            for lang in 0..4 {
+                let text = hint.text(Language::from(lang));
+                assert!(convert_to_banjo_string(&text).is_ok(), "{text}");
+            }
+        }
+    }
+}
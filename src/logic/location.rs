@@ -3,6 +3,7 @@ use crate::assets::map_setup::Prop1;
 use crate::enums::MapSetupId;
 use crate::utils::Vector3;
 
+#[derive(Clone)]
 pub struct Location {
     pub map_id: MapSetupId,
     pub cube_id: usize,
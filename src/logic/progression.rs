@@ -0,0 +1,561 @@
+use super::enums::Item;
+use crate::Config;
+use crate::data::NOTE_DOORS_COSTS;
+use crate::data::PUZZLES_COSTS;
+use crate::data::entrances::FINAL_MAP;
+use crate::data::entrances::FLAG_SOURCES;
+use crate::data::entrances::LEVEL_MAPS;
+use crate::data::entrances::MUMBO_SKULLS;
+use crate::data::entrances::Map;
+use crate::data::entrances::MumboSkull;
+use crate::data::entrances::PUZZLE_PODIUMS;
+use crate::data::entrances::Requirement;
+use crate::data::entrances::TOWER_MAPS;
+use crate::data::entrances::Transformation;
+use crate::data::entrances::get_map;
+use crate::data::levels::LEVELS_INFO;
+use crate::data::levels::LevelOrder;
+use crate::enums::Ability;
+use crate::enums::MapSetupId;
+use crate::enums::MapSetupId::SpiralMountain;
+use crate::enums::file_progress::FileProgress;
+
+// a map and the index of one of its entrances in `MAPS`
+pub type Door = (MapSetupId, usize);
+
+#[derive(Clone, Default)]
+pub struct Inventory {
+    pub abilities: Vec<Ability>,
+    pub transformations: Vec<Transformation>,
+    pub flags: Vec<FileProgress>,
+    pub puzzles: Vec<LevelOrder>,
+    // indexes of the note doors opened from the start
+    pub note_doors: Vec<usize>,
+    pub jiggies: u32,
+    pub tokens: u32,
+    pub notes: u32,
+}
+
+impl Inventory {
+    pub fn add(&mut self, item: Item) {
+        self.add_many(item, 1);
+    }
+
+    fn add_many(&mut self, item: Item, count: u32) {
+        match item {
+            Item::Jiggy => self.jiggies += count,
+            Item::MumboToken => self.tokens += count,
+            Item::MusicalNote => self.notes += count,
+            Item::Other => {}
+        }
+    }
+}
+
+// a path that can't be taken with the current inventory
+pub struct Blocked {
+    pub from: MapSetupId,
+    pub to: MapSetupId,
+    pub required: &'static [Requirement],
+}
+
+/// What the randomizer changed that matters to know where the player can go.
+#[derive(Clone)]
+pub struct Progression {
+    // world behind each entrance, indexed by LevelOrder
+    pub level_order: Vec<LevelOrder>,
+    // world the molehill is in and the ability it teaches
+    pub molehills: Vec<(LevelOrder, Ability)>,
+    pub note_doors_costs: [u32; 12],
+    pub puzzles_costs: [u32; 10],
+    // shuffled doors, linked both ways. doors not listed keep their vanilla destination
+    pub doors: Vec<(Door, Door)>,
+    // jiggies that aren't props: given by characters, jinjos, witch switches...
+    pub event_jiggies: Vec<(MapSetupId, u32)>,
+    // transformation and cost of each skull
+    pub mumbo_skulls: [MumboSkull; 5],
+}
+
+impl Default for Progression {
+    fn default() -> Self {
+        let level_order = (0..LEVELS_INFO.len())
+            .map(LevelOrder::from)
+            .collect::<Vec<_>>();
+
+        let mut molehills = vec![];
+        for level in &level_order {
+            for mole in LEVELS_INFO[*level].molehills {
+                molehills.push((*level, mole.ability));
+            }
+        }
+
+        Self {
+            level_order,
+            molehills,
+            note_doors_costs: NOTE_DOORS_COSTS,
+            puzzles_costs: PUZZLES_COSTS,
+            doors: vec![],
+            event_jiggies: vec![],
+            mumbo_skulls: MUMBO_SKULLS,
+        }
+    }
+}
+
+impl Progression {
+    pub fn start_inventory(&self, config: &Config) -> Inventory {
+        let mut inventory = Inventory::default();
+
+        for ability in 0..=0x13u8 {
+            let ability = Ability::from(ability);
+            if config.moves || !self.molehills.iter().any(|(_, a)| *a == ability) {
+                inventory.abilities.push(ability);
+            }
+        }
+
+        if config.pipes {
+            inventory.flags.push(FileProgress::CcLobbyPipe1Raised);
+            inventory.flags.push(FileProgress::CcLobbyPipe2Raised);
+            inventory.flags.push(FileProgress::CcLobbyPipe3Raised);
+        }
+
+        for (door, cost) in NOTE_DOORS_COSTS.iter().enumerate() {
+            if config.notedoors.contains(cost) {
+                inventory.note_doors.push(door);
+            }
+        }
+
+        inventory
+    }
+
+    pub fn puzzle_cost(&self, level: LevelOrder) -> u32 {
+        let id: usize = level.into();
+        self.puzzles_costs[id]
+    }
+
+    pub fn puzzle_podium(&self, level: LevelOrder) -> MapSetupId {
+        PUZZLE_PODIUMS.iter().find(|(l, _)| *l == level).unwrap().1
+    }
+
+    fn spent_jiggies(&self, inventory: &Inventory) -> u32 {
+        inventory.puzzles.iter().map(|l| self.puzzle_cost(*l)).sum()
+    }
+
+    // `map` is where the requirement has to be met
+    fn satisfies(&self, inventory: &Inventory, requirement: &Requirement, map: MapSetupId) -> bool {
+        match requirement {
+            Requirement::Ability(ability) => inventory.abilities.contains(ability),
+            // a transformation only works in the world of the skull that gives it
+            Requirement::Transformation(transformation) => {
+                inventory.transformations.contains(transformation)
+                    && self.world_of(map).is_some()
+                    && self.world_of(map) == self.transformation_world(*transformation)
+            }
+            Requirement::Flag(flag) => {
+                inventory.flags.contains(flag) || !FLAG_SOURCES.iter().any(|s| s.flag == *flag)
+            }
+            Requirement::NoteDoor(door) => {
+                inventory.note_doors.contains(door)
+                    || inventory.notes >= self.note_doors_costs[*door]
+            }
+            // the tower's puzzle is the last one, what's left after the others is enough or not
+            Requirement::Puzzle(LevelOrder::Lair) => {
+                inventory.jiggies - self.spent_jiggies(inventory)
+                    >= self.puzzle_cost(LevelOrder::Lair)
+            }
+            Requirement::Puzzle(level) => inventory.puzzles.contains(level),
+        }
+    }
+
+    fn satisfies_all(
+        &self,
+        inventory: &Inventory,
+        requirements: &[Requirement],
+        map: MapSetupId,
+    ) -> bool {
+        requirements
+            .iter()
+            .all(|r| self.satisfies(inventory, r, map))
+    }
+
+    // the world `map` is part of. you can go back to a lobby transformed, so a lobby is
+    // part of the world behind its entrance
+    fn world_of(&self, map: MapSetupId) -> Option<LevelOrder> {
+        let mut worlds = (0..LEVELS_INFO.len())
+            .map(LevelOrder::from)
+            .filter(|l| *l != LevelOrder::Lair);
+
+        if let Some(level) = worlds.clone().find(|l| LEVELS_INFO[*l].maps.contains(&map)) {
+            return Some(level);
+        }
+
+        let entrance = worlds.find(|l| LEVELS_INFO[*l].warp_lair.map_setup == map)?;
+        let id: usize = entrance.into();
+        Some(self.level_order[id])
+    }
+
+    fn transformation_world(&self, transformation: Transformation) -> Option<LevelOrder> {
+        self.mumbo_skulls
+            .iter()
+            .find(|s| s.transformation == transformation)
+            .and_then(|s| self.world_of(s.map))
+    }
+
+    // lobbies lead to the world placed behind their entrance
+    fn connect(&self, target: MapSetupId) -> MapSetupId {
+        for (id, level) in LEVELS_INFO.iter().enumerate() {
+            if level.maps.first() == Some(&target) {
+                return LEVELS_INFO[self.level_order[id]].maps[0];
+            }
+        }
+
+        target
+    }
+
+    pub fn linked_door(&self, door: Door) -> Option<Door> {
+        self.doors.iter().find_map(|(a, b)| {
+            if *a == door {
+                Some(*b)
+            } else if *b == door {
+                Some(*a)
+            } else {
+                None
+            }
+        })
+    }
+
+    // where you end up when leaving `map` through `path`
+    fn follow(&self, map: &Map, arrival: usize, path: usize) -> Door {
+        let entrance = &map.entrances[arrival];
+        let target = entrance.paths[path].target;
+
+        // some maps have 2 doors to the same map, the nth path goes through the nth door
+        // that isn't the one we came from
+        let nth = entrance.paths[..path]
+            .iter()
+            .filter(|p| p.target == target)
+            .count();
+        let mut doors = map
+            .entrances
+            .iter()
+            .enumerate()
+            .filter(|(i, e)| e.from == target && *i != arrival)
+            .map(|(i, _)| i);
+        let door = doors.clone().nth(nth).or(doors.next());
+
+        if let Some(door) = door
+            && let Some(linked) = self.linked_door((map.id, door))
+        {
+            return linked;
+        }
+
+        // vanilla, the nth door of this side leads to the nth door of the other side
+        let nth = door.map_or(0, |door| {
+            map.entrances[..door]
+                .iter()
+                .filter(|e| e.from == target)
+                .count()
+        });
+        let target = self.connect(target);
+        let door = get_map(target)
+            .and_then(|m| {
+                let mut doors = m
+                    .entrances
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, e)| e.from == map.id)
+                    .map(|(i, _)| i);
+                doors.clone().nth(nth).or(doors.next())
+            })
+            .unwrap_or(0);
+
+        (target, door)
+    }
+
+    pub fn reachable_maps(&self, inventory: &Inventory) -> Vec<MapSetupId> {
+        self.explore(inventory).0
+    }
+
+    /// Maps that can be reached, and the paths that can't be taken yet out of them.
+    pub fn explore(&self, inventory: &Inventory) -> (Vec<MapSetupId>, Vec<Blocked>) {
+        let mut maps = vec![];
+        let mut blocked = vec![];
+        let mut visited = vec![];
+
+        // the game starts outside, both the house and the lair are reachable
+        let mut queue = (0..get_map(SpiralMountain).unwrap().entrances.len())
+            .map(|i| (SpiralMountain, i))
+            .collect::<Vec<_>>();
+
+        while let Some((map_id, door)) = queue.pop() {
+            if visited.contains(&(map_id, door)) {
+                continue;
+            }
+            visited.push((map_id, door));
+
+            if !maps.contains(&map_id) {
+                maps.push(map_id);
+            }
+
+            let Some(map) = get_map(map_id) else {
+                continue;
+            };
+            let Some(entrance) = map.entrances.get(door) else {
+                continue;
+            };
+
+            for (path, destination) in entrance.paths.iter().enumerate() {
+                if self.satisfies_all(inventory, destination.required, map_id) {
+                    queue.push(self.follow(map, door, path));
+                } else {
+                    blocked.push(Blocked {
+                        from: map_id,
+                        to: self.connect(destination.target),
+                        required: destination.required,
+                    });
+                }
+            }
+        }
+
+        for level in &LEVELS_INFO {
+            let Some(hub) = level.maps.first() else {
+                continue;
+            };
+
+            if !maps.contains(hub) {
+                continue;
+            }
+
+            for map in &level.maps[1..] {
+                let required = LEVEL_MAPS
+                    .iter()
+                    .find(|d| d.target == *map)
+                    .map_or(&[][..], |d| d.required);
+
+                if self.satisfies_all(inventory, required, *map) {
+                    maps.push(*map);
+                } else {
+                    blocked.push(Blocked {
+                        from: *hub,
+                        to: *map,
+                        required,
+                    });
+                }
+            }
+        }
+
+        let mut previous = MapSetupId::GlFfEntrance;
+        for destination in TOWER_MAPS {
+            if !maps.contains(&previous) {
+                break;
+            }
+
+            if self.satisfies_all(inventory, destination.required, destination.target) {
+                maps.push(destination.target);
+            } else {
+                blocked.push(Blocked {
+                    from: previous,
+                    to: destination.target,
+                    required: destination.required,
+                });
+            }
+            previous = destination.target;
+        }
+
+        // another way in might have been found later
+        blocked.retain(|b| !maps.contains(&b.to));
+
+        (maps, blocked)
+    }
+
+    /// The requirements of `requirements` that aren't met yet in `map`.
+    pub fn missing(
+        &self,
+        inventory: &Inventory,
+        requirements: &[Requirement],
+        map: MapSetupId,
+    ) -> Vec<Requirement> {
+        requirements
+            .iter()
+            .filter(|r| !self.satisfies(inventory, r, map))
+            .copied()
+            .collect()
+    }
+
+    /// Collects everything that can be reached, learning abilities, buying
+    /// transformations and completing puzzles along the way.
+    pub fn sweep(
+        &self,
+        mut inventory: Inventory,
+        items: &[(MapSetupId, Item)],
+    ) -> (Inventory, Vec<MapSetupId>) {
+        // group the items by map, it's way faster than checking them one by one
+        let mut remaining: Vec<(MapSetupId, Item, u32)> = vec![];
+        let events = self
+            .event_jiggies
+            .iter()
+            .map(|(map, count)| (*map, Item::Jiggy, *count));
+        let props = items.iter().map(|(map, item)| (*map, *item, 1));
+        for (map, item, count) in events.chain(props) {
+            if !item.is_progression() {
+                continue;
+            }
+
+            match remaining.iter_mut().find(|r| r.0 == map && r.1 == item) {
+                Some(entry) => entry.2 += count,
+                None => remaining.push((map, item, count)),
+            }
+        }
+
+        loop {
+            let maps = self.reachable_maps(&inventory);
+            let mut changed = false;
+
+            remaining.retain(|(map, item, count)| {
+                if maps.contains(map) {
+                    inventory.add_many(*item, *count);
+                    changed = true;
+                    false
+                } else {
+                    true
+                }
+            });
+
+            for (level, ability) in &self.molehills {
+                if maps.contains(&LEVELS_INFO[*level].maps[0])
+                    && !inventory.abilities.contains(ability)
+                {
+                    inventory.abilities.push(*ability);
+                    changed = true;
+                }
+            }
+
+            for source in FLAG_SOURCES {
+                if maps.contains(&source.map)
+                    && !inventory.flags.contains(&source.flag)
+                    && self.satisfies_all(&inventory, source.required, source.map)
+                {
+                    inventory.flags.push(source.flag);
+                    changed = true;
+                }
+            }
+
+            for skull in &self.mumbo_skulls {
+                let spent = self
+                    .mumbo_skulls
+                    .iter()
+                    .filter(|s| inventory.transformations.contains(&s.transformation))
+                    .map(|s| s.cost)
+                    .sum::<u32>();
+
+                if maps.contains(&skull.map)
+                    && !inventory.transformations.contains(&skull.transformation)
+                    && inventory.tokens - spent >= skull.cost
+                {
+                    inventory.transformations.push(skull.transformation);
+                    changed = true;
+                }
+            }
+
+            // cheapest puzzles first, like the vanilla order
+            let mut puzzles = (0..LEVELS_INFO.len())
+                .map(LevelOrder::from)
+                .filter(|l| *l != LevelOrder::Lair)
+                .collect::<Vec<_>>();
+            puzzles.sort_by_key(|l| self.puzzle_cost(*l));
+
+            for level in puzzles {
+                let spent = self.spent_jiggies(&inventory);
+                let cost = self.puzzle_cost(level);
+
+                if !inventory.puzzles.contains(&level)
+                    && maps.contains(&self.puzzle_podium(level))
+                    && inventory.jiggies - spent >= cost
+                {
+                    inventory.puzzles.push(level);
+                    changed = true;
+                }
+            }
+
+            if !changed {
+                return (inventory, maps);
+            }
+        }
+    }
+
+    /// Can Gruntilda be fought with those items? Past the 810 note door and Furnace Fun,
+    /// the jiggies left after the world puzzles have to complete the tower's.
+    pub fn is_beatable(&self, config: &Config, items: &[(MapSetupId, Item)]) -> bool {
+        let (_, maps) = self.sweep(self.start_inventory(config), items);
+        maps.contains(&FINAL_MAP)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transformation_only_in_its_world() {
+        let config = Config {
+            moves: true,
+            notedoors: NOTE_DOORS_COSTS.to_vec(),
+            ..Default::default()
+        };
+        let mut progression = Progression::default();
+        let mut inventory = progression.start_inventory(&config);
+        inventory.transformations.push(Transformation::Termite);
+        inventory.puzzles.push(LevelOrder::MumbosMountain);
+
+        assert!(
+            progression
+                .reachable_maps(&inventory)
+                .contains(&MapSetupId::TickersTower)
+        );
+
+        // BGS' skull turns you into a termite, MM's into a crocodile
+        progression.mumbo_skulls[0].transformation = Transformation::Croc;
+        progression.mumbo_skulls[1].transformation = Transformation::Termite;
+        assert!(
+            !progression
+                .reachable_maps(&inventory)
+                .contains(&MapSetupId::TickersTower)
+        );
+    }
+
+    #[test]
+    fn puzzles_on_reachable_podiums() {
+        let config = Config {
+            moves: true,
+            ..Default::default()
+        };
+        let progression = Progression::default();
+        let mut inventory = progression.start_inventory(&config);
+        inventory.jiggies = 100;
+
+        // without notes, the 50 note door keeps everything past MM's lobby closed
+        let (inventory, maps) = progression.sweep(inventory, &[]);
+        assert_eq!(inventory.puzzles, [LevelOrder::MumbosMountain]);
+        assert!(!maps.contains(&FINAL_MAP));
+    }
+
+    #[test]
+    fn tower_puzzle_takes_the_jiggies_left() {
+        let config = Config {
+            moves: true,
+            notedoors: NOTE_DOORS_COSTS.to_vec(),
+            ..Default::default()
+        };
+        let progression = Progression::default();
+        let world_puzzles = PUZZLES_COSTS.iter().sum::<u32>() - PUZZLES_COSTS[5];
+
+        let mut inventory = progression.start_inventory(&config);
+        inventory.jiggies = world_puzzles + PUZZLES_COSTS[5] - 1;
+        let (_, maps) = progression.sweep(inventory, &[]);
+        assert!(maps.contains(&MapSetupId::GlDingpot));
+        assert!(!maps.contains(&FINAL_MAP));
+
+        let mut inventory = progression.start_inventory(&config);
+        inventory.jiggies = world_puzzles + PUZZLES_COSTS[5];
+        let (_, maps) = progression.sweep(inventory, &[]);
+        assert!(maps.contains(&FINAL_MAP));
+    }
+}
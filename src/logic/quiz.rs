@@ -0,0 +1,292 @@
+use super::hints::ability_name;
+use super::hints::world_name;
+use super::progression::Progression;
+use crate::Config;
+use crate::CustomQuestion;
+use crate::assets::question::QuestionData;
+use crate::data::levels::LevelOrder;
+use crate::enums::Ability;
+use crate::enums::Language;
+use rand::Rng;
+use rand::prelude::IndexedRandom;
+use rand::prelude::SliceRandom;
+use std::collections::HashMap;
+use std::error::Error;
+
+// short enough for the quiz box (a guess, the original lines are a bit shorter)
+const LINE_WIDTH: usize = 28;
+
+const LANGUAGES: [Language; 4] = [
+    Language::English,
+    Language::Japanese,
+    Language::French,
+    Language::German,
+];
+
+// the worlds' paintings in the order the game numbers them
+const PAINTINGS: [LevelOrder; 9] = [
+    LevelOrder::MumbosMountain,
+    LevelOrder::TreasureTroveCove,
+    LevelOrder::ClankersCavern,
+    LevelOrder::BubbleGloopSwamp,
+    LevelOrder::FreezeezyPeak,
+    LevelOrder::GobisValley,
+    LevelOrder::MadMonsterMansion,
+    LevelOrder::RustyBucketBay,
+    LevelOrder::ClickClockWood,
+];
+
+// a question about the seed and its answers in a random order
+struct SeedQuestion {
+    kind: SeedQuestionKind,
+    // index of the right answer
+    right: usize,
+}
+
+enum SeedQuestionKind {
+    // the painting's number and the world behind it
+    Painting(usize, [LevelOrder; 3]),
+    // the note door's number and its cost
+    NoteDoor(usize, [u32; 3]),
+    // the world the move is taught in
+    Molehill(Ability, [LevelOrder; 3]),
+}
+
+impl SeedQuestion {
+    // `answers` has the right one first
+    fn new<R: Rng + ?Sized, T: Copy>(
+        rng: &mut R,
+        kind: impl Fn([T; 3]) -> SeedQuestionKind,
+        answers: [T; 3],
+    ) -> Self {
+        let mut order = [0, 1, 2];
+        order.shuffle(rng);
+
+        Self {
+            kind: kind(order.map(|i| answers[i])),
+            right: order.iter().position(|i| *i == 0).unwrap(),
+        }
+    }
+
+    fn text(&self, lang: Language) -> (String, [String; 3]) {
+        match &self.kind {
+            SeedQuestionKind::Painting(number, worlds) => {
+                let question = match lang {
+                    Language::French => {
+                        format!(
+                            "QUEL MONDE EST DERRIÈRE LE {} TABLEAU?",
+                            ordinal(*number, lang)
+                        )
+                    }
+                    Language::German => {
+                        format!("WELCHE WELT IST HINTER DEM {number}. GEMÄLDE?")
+                    }
+                    _ => format!(
+                        "WHICH WORLD IS BEHIND THE {} PAINTING?",
+                        ordinal(*number, lang)
+                    ),
+                };
+                (question, worlds.map(|w| world_name(w, lang).to_string()))
+            }
+            SeedQuestionKind::NoteDoor(number, costs) => {
+                let question = match lang {
+                    Language::French => format!(
+                        "COMBIEN DE NOTES OUVRENT LA {} PORTE?",
+                        ordinal(*number, lang).replace("1ER", "1ÈRE")
+                    ),
+                    Language::German => {
+                        format!("WIE VIELE NOTEN ÖFFNEN DIE {number}. NOTENTÜR?")
+                    }
+                    _ => format!(
+                        "HOW MANY NOTES OPEN THE {} NOTE DOOR?",
+                        ordinal(*number, lang)
+                    ),
+                };
+                (question, costs.map(|c| c.to_string()))
+            }
+            SeedQuestionKind::Molehill(ability, worlds) => {
+                let move_name = ability_name(*ability);
+                let question = match lang {
+                    Language::French => format!("OÙ BOTTLES ENSEIGNE-T-IL {move_name}?"),
+                    Language::German => format!("WO LEHRT BOTTLES {move_name}?"),
+                    _ => format!("WHERE DOES BOTTLES TEACH {move_name}?"),
+                };
+                (question, worlds.map(|w| world_name(w, lang).to_string()))
+            }
+        }
+    }
+}
+
+/// `count` questions about what has been randomized, in every language. Errors out if there
+/// aren't that many things randomized to ask about.
+pub fn seed_questions<R: Rng + ?Sized>(
+    rng: &mut R,
+    config: &Config,
+    progression: &Progression,
+    count: usize,
+) -> Result<Vec<HashMap<Language, QuestionData>>, Box<dyn Error>> {
+    let worlds = progression
+        .level_order
+        .iter()
+        .copied()
+        .filter(|l| *l != LevelOrder::Lair)
+        .collect::<Vec<_>>();
+
+    let mut questions = vec![];
+
+    if config.worlds {
+        for (painting, entrance) in PAINTINGS.iter().enumerate() {
+            let id: usize = (*entrance).into();
+            let world = progression.level_order[id];
+            let others = worlds.iter().filter(|w| **w != world).collect::<Vec<_>>();
+            if let Some([a, b]) = others.choose_multiple_array(rng) {
+                let kind = |answers| SeedQuestionKind::Painting(painting + 1, answers);
+                questions.push(SeedQuestion::new(rng, kind, [world, *a, *b]));
+            }
+        }
+    }
+
+    if config.worlds && !config.moves {
+        for (level, ability) in &progression.molehills {
+            let others = worlds.iter().filter(|w| *w != level).collect::<Vec<_>>();
+            if let Some([a, b]) = others.choose_multiple_array(rng) {
+                let kind = |answers| SeedQuestionKind::Molehill(*ability, answers);
+                questions.push(SeedQuestion::new(rng, kind, [*level, *a, *b]));
+            }
+        }
+    }
+
+    if config.notedoor_costs.is_some() {
+        let costs = progression.note_doors_costs;
+        for (door, cost) in costs.iter().enumerate() {
+            let mut others = costs.iter().filter(|c| *c != cost).collect::<Vec<_>>();
+            others.sort();
+            others.dedup();
+            if let Some([a, b]) = others.choose_multiple_array(rng) {
+                let kind = |answers| SeedQuestionKind::NoteDoor(door + 1, answers);
+                questions.push(SeedQuestion::new(rng, kind, [*cost, *a, *b]));
+            }
+        }
+    }
+
+    if questions.len() < count {
+        return Err(format!(
+            "Only {} questions about this seed can be asked ({count} wanted)",
+            questions.len()
+        )
+        .into());
+    }
+
+    questions.shuffle(rng);
+    questions.truncate(count);
+
+    Ok(questions
+        .iter()
+        .map(|q| {
+            LANGUAGES
+                .into_iter()
+                .map(|lang| {
+                    let (question, answers) = q.text(lang);
+                    (lang, question_data(&question, &answers, q.right))
+                })
+                .collect()
+        })
+        .collect())
+}
+
+/// A question of the custom pack, in every language (English when it isn't translated).
+pub fn custom_question(question: &CustomQuestion) -> HashMap<Language, QuestionData> {
+    LANGUAGES
+        .into_iter()
+        .map(|lang| {
+            let translation = question.translations.iter().find(|t| t.language == lang);
+            let data = match translation {
+                Some(t) => question_data(&t.question, &t.answers, 0),
+                None => question_data(&question.question, &question.answers, 0),
+            };
+            (lang, data)
+        })
+        .collect()
+}
+
+// the game knows the right answer by its place: it has to be the first one of the file
+fn question_data(question: &str, answers: &[String; 3], right: usize) -> QuestionData {
+    let mut answers = answers.clone();
+    answers[..=right].rotate_right(1);
+    let [answer_1, answer_2, answer_3] = answers.map(|a| wrap(&a));
+
+    QuestionData {
+        question: wrap(question),
+        answer_1,
+        answer_2,
+        answer_3,
+    }
+}
+
+fn wrap(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = vec![];
+    for word in text.to_uppercase().split_whitespace() {
+        match lines.last_mut() {
+            Some(line) if line.chars().count() + 1 + word.chars().count() <= LINE_WIDTH => {
+                line.push(' ');
+                line.push_str(word);
+            }
+            _ => lines.push(word.into()),
+        }
+    }
+
+    lines
+}
+
+fn ordinal(number: usize, lang: Language) -> String {
+    match lang {
+        Language::French if number == 1 => "1ER".into(),
+        Language::French => format!("{number}E"),
+        _ => match number {
+            1 => "1ST".into(),
+            2 => "2ND".into(),
+            3 => "3RD".into(),
+            _ => format!("{number}TH"),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn paintings_in_game_order() {
+        let config = Config {
+            worlds: true,
+            moves: true,
+            ..Default::default()
+        };
+        let progression = Progression::default();
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+
+        let questions = seed_questions(&mut rng, &config, &progression, PAINTINGS.len()).unwrap();
+        let answers = questions
+            .iter()
+            .map(|q| {
+                let english = &q[&Language::English];
+                (english.question.join(" "), english.answer_1.join(" "))
+            })
+            .collect::<Vec<_>>();
+
+        for (question, world) in [
+            (
+                "WHICH WORLD IS BEHIND THE 7TH PAINTING?",
+                "MAD MONSTER MANSION",
+            ),
+            (
+                "WHICH WORLD IS BEHIND THE 9TH PAINTING?",
+                "CLICK CLOCK WOOD",
+            ),
+        ] {
+            assert!(answers.contains(&(question.into(), world.into())));
+        }
+    }
+}
@@ -1,8 +1,34 @@
+use super::check::Report;
+use super::check::check;
+use super::enums::Item;
 use super::enums::Props;
+use super::fill::fill;
+use super::hints::Hint;
+use super::hints::pick_hints;
 use super::location::Location;
+use super::progression::Door;
+use super::progression::Progression;
+use super::quiz::custom_question;
+use super::quiz::seed_questions;
+use super::spoiler::EnemyEntry;
+use super::spoiler::EntranceEntry;
+use super::spoiler::LocationEntry;
+use super::spoiler::MolehillEntry;
+use super::spoiler::MusicEntry;
+use super::spoiler::Spoiler;
+use super::spoiler::WorldEntry;
 use crate::Config;
+use crate::Costs;
+use crate::PlandoMolehill;
+use crate::PlandoProp;
+use crate::PlandoWorld;
+use crate::Pool;
 use crate::assets::Asset;
-use crate::assets::animation::Animation;
+use crate::assets::db360::ASSETS;
+use crate::assets::db360::AssetData;
+use crate::assets::db360::DB360_FILENAME;
+use crate::assets::db360::read_db360;
+use crate::assets::db360::write_db360;
 use crate::assets::dialogue::Dialogue;
 use crate::assets::dialogue::DialogueCommand;
 use crate::assets::dialogue::Speaker;
@@ -12,19 +38,17 @@ use crate::assets::map_setup::Prop1;
 use crate::assets::map_setup::Prop2;
 use crate::assets::midi::Midi;
 use crate::assets::model::Model;
-use crate::assets::question::Question;
-use crate::assets::sprite::Sprite;
-use crate::assets::unknown::Unknown;
-use crate::data::db360::ASSETS;
+use crate::assets::question::GRUNTY_QUIZ;
+use crate::assets::question::QUESTION;
+use crate::data::NOTE_DOORS_COSTS;
 use crate::data::entrances::MAPS;
+use crate::data::entrances::get_map;
 use crate::data::levels::LAIR_MAPS;
 use crate::data::levels::LEVELS_INFO;
 use crate::data::levels::LevelInfo;
 use crate::data::levels::LevelOrder;
 use crate::data::powerpc::Functions;
-use crate::data::powerpc::call;
 use crate::data::powerpc::epilogue;
-use crate::data::powerpc::jump;
 use crate::data::powerpc::nop;
 use crate::data::powerpc::prologue;
 use crate::data::powerpc::set_flag;
@@ -32,62 +56,68 @@ use crate::data::powerpc::set_flags;
 use crate::data::xex::CODE_START_CUSTOM_ADDRESS;
 use crate::data::xex::LAIR_WARPS_TARGET;
 use crate::data::xex::MOLEHILLS_MOVES_DATA;
+use crate::data::xex::NOTE_DOORS_COSTS_ADDRESS;
+use crate::enums::midis::BACKGROUND_TRACKS;
+use crate::enums::midis::JINGLES;
 use crate::enums::*;
+use crate::error::RandoError;
 use crate::logic::randomizer::file_progress::FileProgress;
+use crate::ppc::Block;
+use crate::ppc::Instruction;
+use crate::ppc::Register;
+use crate::textures::Image;
+use crate::textures::TEXTURES_FILENAME;
+use crate::textures::TextureRecord;
+use crate::textures::Textures;
 use crate::utils::Vector3;
-use crate::utils::align_writer;
 use byteorder::BigEndian;
 use byteorder::LittleEndian;
 use byteorder::ReadBytesExt;
 use byteorder::WriteBytesExt;
-use ppc::Block;
-use ppc::Instruction;
-use ppc::Register;
 use rand::Rng;
+use rand::SeedableRng;
+use rand::prelude::IndexedRandom;
 use rand::prelude::SliceRandom;
-use rand::rng;
+use rand_chacha::ChaCha8Rng;
+use std::collections::HashMap;
 use std::error::Error;
 use std::fs::File;
-use std::fs::OpenOptions;
+use std::io::BufReader;
+use std::io::BufWriter;
+use std::io::Cursor;
+use std::io::Read;
 use std::io::Seek;
 use std::io::SeekFrom;
+use std::io::Write;
 use std::ops::Index;
 use std::ops::IndexMut;
+use std::path::Path;
 
-struct AssetData {
-    asset: Asset,
-    flag: u32,
-}
-
-struct TextureData {
-    address: u32,
-    edited: u32,
-}
-
-impl Index<textures::TextureId> for Vec<TextureData> {
-    type Output = TextureData;
+const XEX_FILENAME: &str = "default.xex";
+impl Index<textures::TextureId> for Vec<TextureRecord> {
+    type Output = TextureRecord;
     fn index(&self, index: textures::TextureId) -> &Self::Output {
         let i: usize = index.into();
         &self[i]
     }
 }
 
-impl IndexMut<textures::TextureId> for Vec<TextureData> {
+impl IndexMut<textures::TextureId> for Vec<TextureRecord> {
     fn index_mut(&mut self, index: textures::TextureId) -> &mut Self::Output {
         let i: usize = index.into();
         &mut self[i]
     }
 }
 
-impl Index<&textures::TextureId> for Vec<TextureData> {
-    type Output = TextureData;
+impl Index<&textures::TextureId> for Vec<TextureRecord> {
+    type Output = TextureRecord;
     fn index(&self, index: &textures::TextureId) -> &Self::Output {
         let i: usize = (*index).into();
         &self[i]
     }
 }
 
-impl IndexMut<&textures::TextureId> for Vec<TextureData> {
+impl IndexMut<&textures::TextureId> for Vec<TextureRecord> {
     fn index_mut(&mut self, index: &textures::TextureId) -> &mut Self::Output {
         let i: usize = (*index).into();
         &mut self[i]
@@ -96,24 +126,131 @@ impl IndexMut<&textures::TextureId> for Vec<TextureData> {
 
 pub struct Randomizer {
     assets: Vec<AssetData>,
-    textures: Vec<TextureData>,
+    textures: Textures,
+    // as read, what `set_level_art` copies from
+    original_textures: Vec<TextureRecord>,
+    xex: Cursor<Vec<u8>>,
+    // unlike `StdRng`, its output is fixed: a seed gives the same game with any rand release
+    rng: ChaCha8Rng,
+    spoiler: Spoiler,
+    progression: Progression,
+    // progression items moved by the shuffle, for the hints
+    moved_items: Vec<(MapSetupId, Item)>,
 }
 
 impl Randomizer {
-    pub fn new() -> Result<Self, Box<dyn Error>> {
-        let assets = read_db360()?;
-        let textures = read_textures()?;
+    /// Loads default.xex, db360.cmp and db360.textures.cmp from `input`.
+    pub fn new(input: &Path, seed: u64) -> Result<Self, Box<dyn Error>> {
+        let mut xex = open(input, XEX_FILENAME)?;
+        let mut db360 = BufReader::new(open(input, DB360_FILENAME)?);
+        let mut textures = open(input, TEXTURES_FILENAME)?;
+
+        Self::from_readers(&mut xex, &mut db360, &mut textures, seed)
+    }
+
+    pub fn from_bytes(
+        xex: &[u8],
+        db360: &[u8],
+        textures: &[u8],
+        seed: u64,
+    ) -> Result<Self, Box<dyn Error>> {
+        Self::from_readers(
+            &mut Cursor::new(xex),
+            &mut Cursor::new(db360),
+            &mut Cursor::new(textures),
+            seed,
+        )
+    }
+
+    pub fn from_readers<X: Read, D: Read + Seek, T: Read>(
+        xex: &mut X,
+        db360: &mut D,
+        textures: &mut T,
+        seed: u64,
+    ) -> Result<Self, Box<dyn Error>> {
+        let assets = read_db360(db360)?;
+
+        let mut textures_file = vec![];
+        textures.read_to_end(&mut textures_file)?;
+        let textures = Textures::new(&textures_file)?;
+        let original_textures = textures.records.clone();
+
+        let mut xex_file = vec![];
+        xex.read_to_end(&mut xex_file)?;
+
+        let rng = ChaCha8Rng::seed_from_u64(seed);
+
+        let mut rando = Self {
+            assets,
+            textures,
+            original_textures,
+            xex: Cursor::new(xex_file),
+            rng,
+            spoiler: Spoiler::new(seed),
+            progression: Progression::default(),
+            moved_items: vec![],
+        };
+        rando.progression.event_jiggies = rando.count_event_jiggies();
+
+        Ok(rando)
+    }
 
-        Ok(Self { assets, textures })
+    /// Checks if the custom function has already been written in the xex.
+    pub fn is_patched(&self) -> Result<bool, Box<dyn Error>> {
+        let mut marker = Block::new(Functions::CustomFunction.into());
+        prologue(&mut marker);
+
+        let mut expected = Cursor::new(vec![]);
+        marker.write(&mut expected)?;
+        let expected = expected.into_inner();
+
+        let start = CODE_START_CUSTOM_ADDRESS as usize;
+        let xex = self.xex.get_ref();
+
+        Ok(xex.get(start..start + expected.len()) == Some(&expected[..]))
     }
 
-    pub fn save(&self) -> Result<(), Box<dyn Error>> {
-        self.write_db360()?;
-        self.write_textures()?;
+    /// Writes the randomized files and the spoiler log in `output`.
+    pub fn save(&self, output: &Path) -> Result<(), Box<dyn Error>> {
+        std::fs::create_dir_all(output)?;
+
+        let mut db360 = BufWriter::new(File::create(output.join(DB360_FILENAME))?);
+        self.write_db360(&mut db360)?;
+        db360.flush()?;
+
+        let mut textures = BufWriter::new(File::create(output.join(TEXTURES_FILENAME))?);
+        self.write_textures(&mut textures)?;
+        textures.flush()?;
+
+        let mut xex = File::create(output.join(XEX_FILENAME))?;
+        self.write_xex(&mut xex)?;
 
+        self.spoiler.write(output)?;
+
+        Ok(())
+    }
+
+    pub fn write_xex<W: Write>(&self, writer: &mut W) -> Result<(), Box<dyn Error>> {
+        writer.write_all(self.xex.get_ref())?;
         Ok(())
     }
 
+    pub fn asset(&self, id: usize) -> Option<&Asset> {
+        self.assets.get(id).map(|a| &a.asset)
+    }
+
+    pub fn asset_mut(&mut self, id: usize) -> Option<&mut Asset> {
+        self.assets.get_mut(id).map(|a| &mut a.asset)
+    }
+
+    pub fn spoiler(&self) -> &Spoiler {
+        &self.spoiler
+    }
+
+    pub fn progression(&self) -> &Progression {
+        &self.progression
+    }
+
     pub fn shuffle_world_order(&mut self, config: &Config) -> Result<(), Box<dyn Error>> {
         println!("shuffle worlds");
 
@@ -129,15 +266,20 @@ impl Randomizer {
             LevelOrder::MadMonsterMansion,
         ];
 
+        let pins = &config.plando.worlds;
+
         if config.moves {
             // if all moves are unlocked, the order doesn't matter
-            level_order.shuffle(&mut rng());
+            level_order.shuffle(&mut self.rng);
+            pin_worlds(&mut level_order, pins);
         } else {
             // the first world need talon trot (but can't be GV)
-            level_order[..5].shuffle(&mut rng());
+            level_order[..5].shuffle(&mut self.rng);
 
+            let mut attempts = 0;
             loop {
-                level_order[1..].shuffle(&mut rng());
+                level_order[1..].shuffle(&mut self.rng);
+                pin_worlds(&mut level_order, pins);
 
                 let l0 = level_order[0].molehill_count(); // MM
                 let l1 = level_order[1].molehill_count(); // TTC
@@ -145,13 +287,20 @@ impl Randomizer {
                 let l3 = level_order[3].molehill_count(); // BGS
 
                 // need beak buster before CC and shock jump before FP
-                if (l0 + l1 >= 2 || config.pipes) && l0 + l1 + l2 + l3 >= 3 {
+                // (and a plando world in MM could have no molehill)
+                if l0 > 0 && (l0 + l1 >= 2 || config.pipes) && l0 + l1 + l2 + l3 >= 3 {
                     break;
                 }
+
+                // only the plando can make it impossible
+                attempts += 1;
+                if attempts == 1000 {
+                    return Err("Plando: no world order can be finished with those worlds".into());
+                }
             }
 
             println!("shuffle molehills");
-            self.shuffle_molehills(level_order.clone())?;
+            self.shuffle_molehills(level_order.clone(), &config.plando.molehills)?;
         }
 
         self.set_world_order(level_order.clone())?;
@@ -163,10 +312,10 @@ impl Randomizer {
 
     fn get_map_setup(&mut self, map_setup_id: MapSetupId) -> &mut MapSetup {
         let setup_id: u16 = map_setup_id.into();
-        if let Some(asset_data) = self.assets.get_mut(setup_id as usize) {
-            if let Asset::MapSetup(map_setup) = &mut asset_data.asset {
-                return map_setup;
-            }
+        if let Some(asset_data) = self.assets.get_mut(setup_id as usize)
+            && let Asset::MapSetup(map_setup) = &mut asset_data.asset
+        {
+            return map_setup;
         }
 
         unreachable!();
@@ -230,12 +379,8 @@ impl Randomizer {
         }
     }
 
-    pub fn patch_code(&self, config: &Config) -> Result<(), Box<dyn Error>> {
-        let mut xex = OpenOptions::new()
-            .read(true)
-            .write(true)
-            .open("default.xex")
-            .expect("Can't open default.xex, missing?");
+    pub fn patch_code(&mut self, config: &Config) -> Result<(), Box<dyn Error>> {
+        let xex = &mut self.xex;
 
         let mut body = Block::new(Functions::CustomFunction.into());
 
@@ -277,7 +422,7 @@ impl Randomizer {
             set_flag(&mut body, FileProgress::FurnaceFunComplete);
         }
 
-        body.add(ppc::Instruction::Bl(
+        body.add(Instruction::Bl(
             Functions::ChSmBottlesSkipIntroTutorial.into(),
         ));
 
@@ -285,7 +430,7 @@ impl Randomizer {
             println!("unlock moves");
 
             body.add(Instruction::Li(Register::R3, 0xffff));
-            body.add(ppc::Instruction::Bl(Functions::AbilitySetAllLearned.into()));
+            body.add(Instruction::Bl(Functions::AbilitySetAllLearned.into()));
         }
 
         println!("open requested note doors");
@@ -297,7 +442,7 @@ impl Randomizer {
 
         epilogue(&mut body);
 
-        body.write(&mut xex)?;
+        body.write(xex)?;
 
         // increase size of .text section
         xex.seek(SeekFrom::Start(0x2248))?;
@@ -306,19 +451,19 @@ impl Randomizer {
         // patch chSmBottles_update
         xex.seek(SeekFrom::Start(0x18da14))?;
         let call_custom_func = Instruction::Bl(Functions::CustomFunction.into());
-        call_custom_func.write(&mut xex, 0x8218ba14)?;
+        call_custom_func.write(xex, 0x8218ba14)?;
 
         // patch stoodOnPodiumCallback to skip bottles' instructions
         xex.seek(SeekFrom::Start(0x1826fc))?;
         let patch_flag = Instruction::Li(Register::R4, 4);
-        patch_flag.write(&mut xex, 0)?;
+        patch_flag.write(xex, 0)?;
 
         // patch __baMarker_8028B848 to remove
         // - DIALOG_FIRST_JIGGY
         // - DIALOG_JIGGY_COLLECT_10
         xex.seek(SeekFrom::Start(0x94068))?;
         let call_custom_func = Instruction::B(0x820920e8);
-        call_custom_func.write(&mut xex, 0x82092068)?;
+        call_custom_func.write(xex, 0x82092068)?;
 
         if config.easy_talon_trot {
             println!("easy talon trot");
@@ -335,16 +480,16 @@ impl Randomizer {
 
         xex.seek(SeekFrom::Start(0x155844))?;
         for _ in 0..13 {
-            nop(&mut xex)?;
+            nop(xex)?;
         }
 
         for _ in 0..11 {
             xex.read_u32::<BigEndian>()?; // skip
-            nop(&mut xex)?;
+            nop(xex)?;
         }
 
         xex.seek(SeekFrom::Start(0x15591c))?;
-        nop(&mut xex)?;
+        nop(xex)?;
 
         Ok(())
     }
@@ -352,6 +497,7 @@ impl Randomizer {
     fn replace_dialogues(&mut self, order: Vec<LevelOrder>) -> Result<(), Box<dyn Error>> {
         let mut order = order;
         order.insert(LevelOrder::Lair.into(), LevelOrder::Lair);
+        self.progression.level_order = order.clone();
 
         for lang_id in 0..4 {
             let lang: Language = lang_id.into();
@@ -362,61 +508,58 @@ impl Randomizer {
             }
 
             for asset in &mut self.assets {
-                match &mut asset.asset {
-                    Asset::Dialogue(dialogue) => {
-                        let mut level_replaced = false;
-                        for (id, level) in order.iter().enumerate() {
-                            if *level == LevelOrder::Lair {
-                                continue;
-                            }
+                if let Asset::Dialogue(dialogue) = &mut asset.asset {
+                    let mut level_replaced = false;
+                    for (id, level) in order.iter().enumerate() {
+                        if *level == LevelOrder::Lair {
+                            continue;
+                        }
 
-                            let orig_level: LevelOrder = id.into();
+                        let orig_level: LevelOrder = id.into();
 
-                            if orig_level == *level {
-                                // level didn't change
-                                continue;
-                            }
+                        if orig_level == *level {
+                            // level didn't change
+                            continue;
+                        }
 
-                            let orig_level_name = orig_level.get_name(lang);
-                            let level_name = level.get_name(lang);
-
-                            if let Some(dial) = dialogue.translations.get_mut(&lang) {
-                                for t in dial.top.iter_mut() {
-                                    if let DialogueCommand::Speak(_, text) = t {
-                                        if text.contains(orig_level_name) {
-                                            *text = text.replace(orig_level_name, level_name);
-                                            *text = text.replace("DE LE", "DU");
-                                            level_replaced = true;
-                                        }
-                                    }
+                        let orig_level_name = orig_level.get_name(lang);
+                        let level_name = level.get_name(lang);
+
+                        if let Some(dial) = dialogue.translations.get_mut(&lang) {
+                            for t in dial.top.iter_mut() {
+                                if let DialogueCommand::Speak(_, text) = t
+                                    && text.contains(orig_level_name)
+                                {
+                                    *text = text.replace(orig_level_name, level_name);
+                                    *text = text.replace("DE LE", "DU");
+                                    level_replaced = true;
                                 }
+                            }
 
-                                for b in dial.bottom.iter_mut() {
-                                    if let DialogueCommand::Speak(_, text) = b {
-                                        if text.contains(orig_level_name) {
-                                            *text = text.replace(orig_level_name, level_name);
-                                            *text = text.replace("DE LE", "DU");
-                                            level_replaced = true;
-                                        }
-                                    }
+                            for b in dial.bottom.iter_mut() {
+                                if let DialogueCommand::Speak(_, text) = b
+                                    && text.contains(orig_level_name)
+                                {
+                                    *text = text.replace(orig_level_name, level_name);
+                                    *text = text.replace("DE LE", "DU");
+                                    level_replaced = true;
                                 }
                             }
+                        }
 
-                            // when A becomes B
-                            // and B becomes C
-                            // don't change A into C
-                            if level_replaced {
-                                break;
-                            }
+                        // when A becomes B
+                        // and B becomes C
+                        // don't change A into C
+                        if level_replaced {
+                            break;
                         }
                     }
-                    _ => {}
                 }
             }
         }
 
         // shorter bottles' dialogues
-        const MOVES_NAMES_DIALOGUES: [(DialogueId, &'static str); 9] = [
+        const MOVES_NAMES_DIALOGUES: [(DialogueId, &str); 9] = [
             (DialogueId::BottlesLearningEggs, "EGGS"),
             (DialogueId::BottlesLearningBeakBuster, "BEAK BUSTER"),
             (DialogueId::BottlesLearningTalonTrot, "TALON TROT"),
@@ -492,6 +635,246 @@ impl Randomizer {
         );
     }
 
+    /// Replaces what Brentilda says with hints about the shuffled items, moves and worlds.
+    pub fn set_hints(&mut self, config: &Config) -> Result<(), Box<dyn Error>> {
+        let Some(mix) = &config.hints else {
+            return Ok(());
+        };
+
+        let dialogues = self.brentilda_dialogues();
+        let slots = dialogues
+            .iter()
+            .filter_map(|id| match &self.assets[*id].asset {
+                Asset::Dialogue(dialogue) => Some(brentilda_lines(dialogue, Language::English)),
+                _ => None,
+            })
+            .flatten()
+            .count();
+
+        // notes are everywhere, not worth a hint
+        let items = self
+            .moved_items
+            .iter()
+            .filter(|(_, item)| *item != Item::MusicalNote)
+            .map(|(map, item)| Hint::Item(*item, *map))
+            .collect();
+
+        let moves = if config.worlds && !config.moves {
+            self.progression
+                .molehills
+                .iter()
+                .map(|(level, ability)| Hint::Move(*ability, *level))
+                .collect()
+        } else {
+            vec![]
+        };
+
+        let worlds = self
+            .progression
+            .level_order
+            .iter()
+            .enumerate()
+            .map(|(id, level)| (LevelOrder::from(id), *level))
+            .filter(|(entrance, level)| entrance != level && *level != LevelOrder::Lair)
+            .map(|(entrance, level)| Hint::World(entrance, level))
+            .collect();
+
+        let hints = pick_hints(&mut self.rng, mix, items, moves, worlds, slots);
+        if hints.is_empty() {
+            println!("nothing to hint, Brentilda keeps her dialogues");
+            return Ok(());
+        }
+
+        write_hints(&mut self.assets, &dialogues, &hints);
+        self.spoiler.hints = hints.iter().map(|h| h.text(Language::English)).collect();
+
+        Ok(())
+    }
+
+    // her dialogues aren't named, but they're the only ones where she speaks. the credits and
+    // the Xbox texts are read as dialogues too, they're left out
+    fn brentilda_dialogues(&self) -> Vec<usize> {
+        ASSETS
+            .iter()
+            .enumerate()
+            .filter(|(id, asset_id)| {
+                matches!(asset_id, AssetId::Dialogue(_))
+                    && matches!(
+                        self.assets.get(*id).map(|a| &a.asset),
+                        Some(Asset::Dialogue(d)) if brentilda_lines(d, Language::English).next().is_some()
+                    )
+            })
+            .map(|(id, _)| id)
+            .collect()
+    }
+
+    /// Mixes Furnace Fun's questions between the squares and replaces some with the custom
+    /// ones and the ones about the seed.
+    pub fn set_questions(&mut self, config: &Config) -> Result<(), Box<dyn Error>> {
+        if config.mix_questions {
+            for kind in [QUESTION, GRUNTY_QUIZ] {
+                let ids = self.question_ids(kind);
+
+                let mut translations = ids
+                    .iter()
+                    .map(|id| match &mut self.assets[*id].asset {
+                        Asset::Question(question) => std::mem::take(&mut question.translations),
+                        _ => unreachable!(),
+                    })
+                    .collect::<Vec<_>>();
+                translations.shuffle(&mut self.rng);
+
+                for (id, translations) in ids.iter().zip(translations) {
+                    if let Asset::Question(question) = &mut self.assets[*id].asset {
+                        question.translations = translations;
+                    }
+                }
+            }
+        }
+
+        let mut questions = config
+            .questions
+            .iter()
+            .map(custom_question)
+            .collect::<Vec<_>>();
+        questions.extend(seed_questions(
+            &mut self.rng,
+            config,
+            &self.progression,
+            config.seed_questions,
+        )?);
+
+        if questions.is_empty() {
+            return Ok(());
+        }
+
+        let mut ids = self.question_ids(QUESTION);
+        if questions.len() > ids.len() {
+            return Err(format!(
+                "Too many questions, only {} can be replaced ({} given)",
+                ids.len(),
+                questions.len()
+            )
+            .into());
+        }
+
+        ids.shuffle(&mut self.rng);
+        for (id, translations) in ids.iter().zip(questions) {
+            if let Asset::Question(question) = &mut self.assets[*id].asset {
+                question.translations = translations;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn question_ids(&self, kind: u16) -> Vec<usize> {
+        self.assets
+            .iter()
+            .enumerate()
+            .filter(|(_, data)| matches!(&data.asset, Asset::Question(q) if q.kind == kind))
+            .map(|(id, _)| id)
+            .collect()
+    }
+
+    /// Shuffles the background music between the worlds and their rooms, and the jingles
+    /// between themselves.
+    pub fn shuffle_music(&mut self, config: &Config) {
+        let mut groups: Vec<&[MidiId]> = vec![];
+        if config.music {
+            groups.push(&BACKGROUND_TRACKS);
+        }
+        if config.jingles {
+            groups.push(&JINGLES);
+        }
+
+        for tracks in groups {
+            // the flag goes with the data
+            let mut songs = tracks
+                .iter()
+                .map(|track| {
+                    let id: u16 = (*track).into();
+                    let empty = AssetData {
+                        asset: Asset::Empty,
+                        flag: 0,
+                    };
+                    (
+                        *track,
+                        std::mem::replace(&mut self.assets[id as usize], empty),
+                    )
+                })
+                .collect::<Vec<_>>();
+            songs.shuffle(&mut self.rng);
+
+            for (track, (song, data)) in tracks.iter().zip(songs) {
+                let id: u16 = (*track).into();
+                self.assets[id as usize] = data;
+                self.spoiler.music.push(MusicEntry {
+                    track: track.to_string(),
+                    song: song.to_string(),
+                });
+            }
+        }
+    }
+
+    /// Replaces a track by a MIDI file (see `Midi::from_bytes`), `name` is for the spoiler log.
+    pub fn set_music(
+        &mut self,
+        track: MidiId,
+        midi: Midi,
+        name: &str,
+    ) -> Result<(), Box<dyn Error>> {
+        let id: u16 = track.into();
+        let Some(AssetData {
+            asset: asset @ Asset::Midi(_),
+            ..
+        }) = self.assets.get_mut(id as usize)
+        else {
+            return Err(format!("{track} isn't a music track").into());
+        };
+        *asset = Asset::Midi(midi);
+
+        self.spoiler.music.retain(|e| e.track != track.to_string());
+        self.spoiler.music.push(MusicEntry {
+            track: track.to_string(),
+            song: name.into(),
+        });
+
+        Ok(())
+    }
+
+    /// Points a texture at new pixels, whatever was shown there before (a painting moved by the
+    /// world order included).
+    pub fn set_texture(&mut self, id: usize, image: &Image) -> Result<(), Box<dyn Error>> {
+        if id >= self.textures.records.len() {
+            return Err(format!("there's no texture {id}").into());
+        }
+        self.textures.replace(id, image)
+    }
+
+    pub fn model_mut(&mut self, model: ModelId) -> Option<&mut Model> {
+        let id: u16 = model.into();
+        match self.assets.get_mut(id as usize).map(|a| &mut a.asset) {
+            Some(Asset::Model(data)) => Some(data),
+            _ => None,
+        }
+    }
+
+    /// Redraws a frame of a sprite (e.g. `SpritePropId::MusicalNote.sprite()`), see
+    /// `Sprite::replace_frame`.
+    pub fn set_sprite_frame(
+        &mut self,
+        sprite: SpriteId,
+        frame: usize,
+        image: &Image,
+    ) -> Result<(), Box<dyn Error>> {
+        let id: u16 = sprite.into();
+        let Some(Asset::Sprite(data)) = self.assets.get(id as usize).map(|a| &a.asset) else {
+            return Err(format!("{sprite} isn't a sprite with its pixels in the textures").into());
+        };
+        data.replace_frame(frame, &mut self.textures, image)
+    }
+
     fn set_dialogue(
         &mut self,
         id: DialogueId,
@@ -500,22 +883,27 @@ impl Randomizer {
         lang: Language,
     ) {
         let dial_id: u16 = id.into();
-        if let Some(asset_data) = self.assets.get_mut(dial_id as usize) {
-            if let Asset::Dialogue(dialogue) = &mut asset_data.asset {
-                let data = dialogue.translations.get_mut(&lang).unwrap();
-                data.top = top;
-                data.bottom = bottom;
-            }
+        if let Some(asset_data) = self.assets.get_mut(dial_id as usize)
+            && let Asset::Dialogue(dialogue) = &mut asset_data.asset
+        {
+            let data = dialogue.translations.get_mut(&lang).unwrap();
+            data.top = top;
+            data.bottom = bottom;
         }
     }
 
-    fn shuffle_molehills(&mut self, order: Vec<LevelOrder>) -> Result<(), Box<dyn Error>> {
+    fn shuffle_molehills(
+        &mut self,
+        order: Vec<LevelOrder>,
+        pins: &[PlandoMolehill],
+    ) -> Result<(), Box<dyn Error>> {
         let mut molehills = vec![];
         for level in &order {
             for mole in LEVELS_INFO[*level].molehills {
                 molehills.push(mole);
             }
         }
+        let originals = molehills.clone();
 
         let l0 = order[0].molehill_count(); // MM
         let l1 = order[1].molehill_count(); // TTC
@@ -526,8 +914,17 @@ impl Randomizer {
         let beak_buster_max_pos = l0 + l1;
         let shock_jump_max_pos = l0 + l1 + l2 + l3;
 
+        let mut attempts = 0;
         loop {
-            molehills.shuffle(&mut rng());
+            molehills.shuffle(&mut self.rng);
+
+            for pin in pins {
+                let slot = originals.iter().position(|m| m.ability == pin.original);
+                let current = molehills.iter().position(|m| m.ability == pin.ability);
+                if let (Some(slot), Some(current)) = (slot, current) {
+                    molehills.swap(slot, current);
+                }
+            }
 
             // since the first world needs talon trot
             if molehills[0..talon_trot_max_pos]
@@ -544,14 +941,19 @@ impl Randomizer {
             {
                 break;
             }
+
+            attempts += 1;
+            if attempts == 1000 {
+                return Err(
+                    "Plando: those molehills don't give the moves needed to reach every world"
+                        .into(),
+                );
+            }
         }
 
-        let mut xex = OpenOptions::new()
-            .read(true)
-            .write(true)
-            .open("default.xex")
-            .expect("Can't open default.xex, missing?");
+        let xex = &mut self.xex;
 
+        self.progression.molehills.clear();
         let mut mole_index = 0;
         for level in &order {
             for mole in LEVELS_INFO[*level].molehills {
@@ -563,6 +965,16 @@ impl Randomizer {
                 xex.write_u16::<BigEndian>(molehills[mole_index].refresher_text_id.into())?;
                 xex.read_u8()?; // shorter than "skip"
                 xex.write_u8(molehills[mole_index].ability.into())?;
+
+                self.progression
+                    .molehills
+                    .push((*level, molehills[mole_index].ability));
+                self.spoiler.molehills.push(MolehillEntry {
+                    level: level.to_string(),
+                    original: mole.ability.to_string(),
+                    ability: molehills[mole_index].ability.to_string(),
+                });
+
                 mole_index += 1;
             }
         }
@@ -573,10 +985,19 @@ impl Randomizer {
     fn set_world_order(&mut self, order: Vec<LevelOrder>) -> Result<(), Box<dyn Error>> {
         let mut order = order;
         order.insert(LevelOrder::Lair.into(), LevelOrder::Lair);
+        self.progression.level_order = order.clone();
 
         for (id, level) in order.iter().enumerate() {
             self.set_level_art(id.into(), *level);
             self.change_level_warp(id.into(), *level)?;
+
+            if *level != LevelOrder::Lair {
+                let entrance: LevelOrder = id.into();
+                self.spoiler.world_order.push(WorldEntry {
+                    entrance: entrance.to_string(),
+                    world: level.to_string(),
+                });
+            }
         }
 
         Ok(())
@@ -590,15 +1011,18 @@ impl Randomizer {
         let old_level = &LEVELS_INFO[old];
         let new_level = &LEVELS_INFO[new];
 
+        let textures = &mut self.textures.records;
+        let original = &self.original_textures;
+
         for (o, n) in old_level.painting.iter().zip(new_level.painting) {
-            self.textures[o].edited = self.textures[n].address;
+            textures[o] = original[n];
         }
 
-        self.textures[old_level.sign_left].edited = self.textures[new_level.sign_left].address;
-        self.textures[old_level.sign_right].edited = self.textures[new_level.sign_right].address;
+        textures[old_level.sign_left] = original[new_level.sign_left];
+        textures[old_level.sign_right] = original[new_level.sign_right];
 
         for id in 0..4 {
-            self.textures[old_level.label[id]].edited = self.textures[new_level.label[id]].address;
+            textures[old_level.label[id]] = original[new_level.label[id]];
         }
     }
 
@@ -620,19 +1044,16 @@ impl Randomizer {
         if let Asset::MapSetup(map_setup) = asset {
             for c in &mut map_setup.cubes {
                 for o in &mut c.props_1 {
-                    if let Category::WarpOrTrigger(id) = o.category {
-                        if id == old_level.warp_lair.warp_id {
-                            o.category = Category::WarpOrTrigger(new_level.warp_lair.warp_id);
-                        }
+                    if let Category::WarpOrTrigger(id) = o.category
+                        && id == old_level.warp_lair.warp_id
+                    {
+                        o.category = Category::WarpOrTrigger(new_level.warp_lair.warp_id);
                     }
                 }
             }
         }
 
-        let mut xex = OpenOptions::new()
-            .write(true)
-            .open("default.xex")
-            .expect("Can't open default.xex, missing?");
+        let xex = &mut self.xex;
         let new_order: usize = new.into();
         xex.seek(SeekFrom::Start(LAIR_WARPS_TARGET + new_order as u64 * 4))?;
         xex.write_u16::<BigEndian>(old_level.warp_lair.map_id.into())?;
@@ -641,12 +1062,283 @@ impl Randomizer {
         Ok(())
     }
 
-    pub fn shuffle_entities(&mut self, actors: &Vec<ActorId>, sprites: &Vec<SpritePropId>) {
+    pub fn shuffle_entities(
+        &mut self,
+        config: &Config,
+        actors: &[ActorId],
+        sprites: &[SpritePropId],
+    ) -> Result<(), Box<dyn Error>> {
         println!("shuffle entities");
 
+        let mut pools = vec![];
+        for levels in pool_levels(&config.pool) {
+            let mut shared = vec![];
+            for level in &levels {
+                let mut own = vec![];
+                for map in level.maps() {
+                    for location in self.grab_entities_from_map(actors, sprites, map)? {
+                        if levels.len() > 1 && location.prop.stays_in_world() {
+                            own.push(location);
+                        } else {
+                            shared.push(location);
+                        }
+                    }
+                }
+
+                if !own.is_empty() {
+                    pools.push(own);
+                }
+            }
+            pools.push(shared);
+        }
+
+        let mut originals = pools
+            .iter()
+            .map(|p| p.iter().map(|l| l.prop).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+
+        // the plando items take the place of one of the same kind from their pool,
+        // and are left out of the fill
+        let mut pinned = vec![];
+        let mut pinned_originals = vec![];
+        for pin in &config.plando.items {
+            let shuffled = match pin.item {
+                PlandoProp::Actor(id) => actors.contains(&id),
+                PlandoProp::Sprite(id) => sprites.contains(&id),
+            };
+            if !shuffled {
+                continue;
+            }
+
+            let found = pools.iter().enumerate().find_map(|(pool_id, pool)| {
+                pool.iter()
+                    .position(|l| {
+                        l.map_id == pin.map && l.cube_id == pin.cube && l.position == pin.position
+                    })
+                    .map(|slot| (pool_id, slot))
+            });
+            let Some((pool_id, slot)) = found else {
+                return Err(format!(
+                    "Plando: no shuffled item at {:?} in {} (cube {})",
+                    pin.position, pin.map, pin.cube
+                )
+                .into());
+            };
+
+            let pool = &mut pools[pool_id];
+            let Some(other) = pool.iter().position(|l| is_plando_item(pin.item, &l.prop)) else {
+                return Err(format!(
+                    "Plando: no {} left in the pool of {} to move in cube {}",
+                    pin.item, pin.map, pin.cube
+                )
+                .into());
+            };
+
+            let (prop, linked) = (pool[other].prop, pool[other].linked);
+            pool[other].prop = pool[slot].prop;
+            pool[other].linked = pool[slot].linked;
+            pool[slot].prop = prop;
+            pool[slot].linked = linked;
+
+            pinned.push(pool.remove(slot));
+            pinned_originals.push(originals[pool_id].remove(slot));
+        }
+
+        let mut fixed = self.get_progression_items();
+        for location in &pinned {
+            if location.prop.item().is_progression() {
+                fixed.push((location.map_id, location.prop.item()));
+            }
+        }
+        fill(&mut self.rng, &self.progression, config, &mut pools, &fixed)?;
+
+        pools.push(pinned);
+        originals.push(pinned_originals);
+
+        for (pool, originals) in pools.iter().zip(originals) {
+            for (location, original) in pool.iter().zip(originals) {
+                let item = location.prop.item();
+                if item.is_progression() && original.to_string() != location.prop.to_string() {
+                    self.moved_items.push((location.map_id, item));
+                }
+
+                self.spoiler.locations.push(LocationEntry {
+                    map: location.map_id.to_string(),
+                    cube: location.cube_id,
+                    position: location.position,
+                    original: original.to_string(),
+                    item: location.prop.to_string(),
+                });
+            }
+        }
+
+        for pool in &pools {
+            let mut maps = vec![];
+            for location in pool {
+                if !maps.contains(&location.map_id) {
+                    maps.push(location.map_id);
+                }
+            }
+            for map in &maps {
+                self.insert_grabbed_entities_from_map(pool, map);
+            }
+        }
+
+        Ok(())
+    }
+
+    // progression items still in the maps
+    fn get_progression_items(&self) -> Vec<(MapSetupId, Item)> {
+        self.get_collectibles()
+            .iter()
+            .map(|l| (l.map_id, l.prop.item()))
+            .collect()
+    }
+
+    fn get_collectibles(&self) -> Vec<Location> {
+        let mut collectibles = vec![];
+
+        let maps = LEVELS_INFO.iter().flat_map(|l| l.maps).chain(&LAIR_MAPS);
+        for map_id in maps {
+            let id: u16 = (*map_id).into();
+            let Asset::MapSetup(map) = &self.assets[id as usize].asset else {
+                unreachable!();
+            };
+
+            for (cube_id, cube) in map.cubes.iter().enumerate() {
+                let props_1 = cube.props_1.iter().map(|p| Props::Prop1(*p));
+                let props_2 = cube.props_2.iter().map(|p| Props::Prop2(*p));
+
+                for prop in props_1.chain(props_2) {
+                    if prop.item().is_progression() {
+                        collectibles.push(Location {
+                            map_id: *map_id,
+                            cube_id,
+                            position: prop.position(),
+                            prop,
+                            linked: None,
+                        });
+                    }
+                }
+            }
+        }
+
+        collectibles
+    }
+
+    // every world has 10 jiggies, plus the one from its witch switch
+    // and there's the one you find in the lair before MM
+    fn count_event_jiggies(&self) -> Vec<(MapSetupId, u32)> {
+        let items = self.get_progression_items();
+        let count = |maps: &[MapSetupId]| {
+            items
+                .iter()
+                .filter(|(m, i)| maps.contains(m) && *i == Item::Jiggy)
+                .count() as u32
+        };
+
+        let mut events = vec![];
         for level in &LEVELS_INFO {
-            self.shuffle_entities_for_level(actors, sprites, level);
+            if let Some(hub) = level.maps.first() {
+                events.push((*hub, 11u32.saturating_sub(count(level.maps))));
+            }
+        }
+
+        events.push((
+            MapSetupId::GlMmLobby,
+            1u32.saturating_sub(count(&LAIR_MAPS)),
+        ));
+
+        events
+    }
+
+    /// Plays the game as it is now: molehills and note doors are read back from the xex,
+    /// the world order from the lobbies and the items from the maps.
+    pub fn check_progression(&self, config: &Config) -> Result<Report, Box<dyn Error>> {
+        let mut progression = self.progression.clone();
+        progression.level_order = self.read_world_order()?;
+        progression.molehills = self.read_molehills()?;
+        progression.note_doors_costs = self.read_note_doors_costs()?;
+
+        // the maps missing from `MAPS` can't be reached anyway
+        let collectibles = self
+            .get_collectibles()
+            .into_iter()
+            .filter(|l| {
+                get_map(l.map_id).is_some()
+                    || LEVELS_INFO
+                        .iter()
+                        .any(|level| level.maps.contains(&l.map_id))
+            })
+            .collect::<Vec<_>>();
+
+        Ok(check(&progression, config, &collectibles))
+    }
+
+    // the world each lobby warps to
+    fn read_world_order(&self) -> Result<Vec<LevelOrder>, Box<dyn Error>> {
+        let mut order = vec![];
+
+        for (id, entrance) in LEVELS_INFO.iter().enumerate() {
+            let entrance_id: LevelOrder = id.into();
+            if entrance_id == LevelOrder::Lair {
+                order.push(LevelOrder::Lair);
+                continue;
+            }
+
+            let map_setup: u16 = entrance.warp_lair.map_setup.into();
+            let Asset::MapSetup(map) = &self.assets[map_setup as usize].asset else {
+                unreachable!();
+            };
+
+            let warps = map
+                .cubes
+                .iter()
+                .flat_map(|c| &c.props_1)
+                .filter_map(|p| match p.category {
+                    Category::WarpOrTrigger(id) => Some(id),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+
+            let world = (0..LEVELS_INFO.len())
+                .map(LevelOrder::from)
+                .filter(|l| *l != LevelOrder::Lair)
+                .find(|l| warps.contains(&LEVELS_INFO[*l].warp_lair.warp_id))
+                .ok_or(format!("No world warp in {}", entrance.warp_lair.map_setup))?;
+            order.push(world);
+        }
+
+        Ok(order)
+    }
+
+    fn read_molehills(&self) -> Result<Vec<(LevelOrder, Ability)>, Box<dyn Error>> {
+        let mut xex = Cursor::new(self.xex.get_ref());
+        let mut molehills = vec![];
+
+        for (id, level) in LEVELS_INFO.iter().enumerate() {
+            for mole in level.molehills {
+                // skip the text ids
+                xex.seek(SeekFrom::Start(
+                    MOLEHILLS_MOVES_DATA + mole.table_index as u64 * 6 + 5,
+                ))?;
+                molehills.push((id.into(), xex.read_u8()?.into()));
+            }
+        }
+
+        Ok(molehills)
+    }
+
+    fn read_note_doors_costs(&self) -> Result<[u32; 12], Box<dyn Error>> {
+        let mut xex = Cursor::new(self.xex.get_ref());
+        let mut costs = [0; 12];
+
+        xex.seek(SeekFrom::Start(NOTE_DOORS_COSTS_ADDRESS))?;
+        for cost in &mut costs {
+            *cost = xex.read_u16::<BigEndian>()? as u32;
         }
+
+        Ok(costs)
     }
 
     pub fn randomize_enemies(&mut self) {
@@ -666,56 +1358,31 @@ impl Randomizer {
     }
 
     fn randomize_enemies_for_map(&mut self, map_id: &MapSetupId) {
-        let map = self.get_map_setup(*map_id);
+        let id: u16 = (*map_id).into();
+        let Asset::MapSetup(map) = &mut self.assets[id as usize].asset else {
+            unreachable!();
+        };
 
-        for cube in map.cubes.iter_mut() {
+        for (cube_id, cube) in map.cubes.iter_mut().enumerate() {
             for prop in cube.props_1.iter_mut() {
-                if let Category::Actor(actor_id) = prop.category {
-                    if actor_id.is_enemy() {
-                        prop.category = Category::Actor(ActorId::random_enemy());
-                    }
+                if let Category::Actor(actor_id) = prop.category
+                    && actor_id.is_enemy()
+                {
+                    let enemy = ActorId::random_enemy(&mut self.rng);
+                    prop.category = Category::Actor(enemy);
+
+                    self.spoiler.enemies.push(EnemyEntry {
+                        map: map_id.to_string(),
+                        cube: cube_id,
+                        position: prop.position,
+                        original: actor_id.to_string(),
+                        enemy: enemy.to_string(),
+                    });
                 }
             }
         }
     }
 
-    fn shuffle_entities_for_level(
-        &mut self,
-        actors: &Vec<ActorId>,
-        sprites: &Vec<SpritePropId>,
-        level: &LevelInfo,
-    ) {
-        // it's the lair
-        if level.maps.len() == 0 {
-            return;
-        }
-
-        let mut grabbed_entities = vec![];
-
-        for map in level.maps {
-            let mut grabbed = self.grab_entities_from_map(&actors, &sprites, map);
-            grabbed_entities.append(&mut grabbed);
-        }
-
-        let rng = &mut rng();
-        for _ in 0..(grabbed_entities.len() * 4) {
-            let a = rng.random_range(..grabbed_entities.len());
-            let b = rng.random_range(..grabbed_entities.len());
-            if a != b {
-                let prop = grabbed_entities[a].prop;
-                grabbed_entities[a].prop = grabbed_entities[b].prop;
-                grabbed_entities[b].prop = prop;
-                let linked = grabbed_entities[a].linked;
-                grabbed_entities[a].linked = grabbed_entities[b].linked;
-                grabbed_entities[b].linked = linked;
-            }
-        }
-
-        for map in level.maps {
-            self.insert_grabbed_entities_from_map(&grabbed_entities, map);
-        }
-    }
-
     fn insert_grabbed_entities_from_map(&mut self, entities: &Vec<Location>, map_id: &MapSetupId) {
         let id: u16 = (*map_id).into();
         let map = &mut self.assets[id as usize].asset;
@@ -756,10 +1423,10 @@ impl Randomizer {
                         };
 
                         map.cubes[entity.cube_id].props_2.push(Prop2::Sprite {
-                            id: id,
-                            flags: flags,
+                            id,
+                            flags,
                             position: entity.position,
-                            bitfield_0a: bitfield_0a,
+                            bitfield_0a,
                         });
                     }
                 }
@@ -769,10 +1436,10 @@ impl Randomizer {
 
     fn grab_entities_from_map(
         &mut self,
-        actors: &Vec<ActorId>,
-        sprites: &Vec<SpritePropId>,
+        actors: &[ActorId],
+        sprites: &[SpritePropId],
         map_id: &MapSetupId,
-    ) -> Vec<Location> {
+    ) -> Result<Vec<Location>, Box<dyn Error>> {
         let map = self.get_map_setup(*map_id);
 
         let mut locations = vec![];
@@ -785,13 +1452,8 @@ impl Randomizer {
                 }
             }
 
-            cube.props_1.retain(|p| {
-                if let Category::Flags(_) = p.category {
-                    false
-                } else {
-                    true
-                }
-            });
+            cube.props_1
+                .retain(|p| !matches!(p.category, Category::Flags(_)));
         }
 
         for (cube_id, cube) in map.cubes.iter_mut().enumerate() {
@@ -801,7 +1463,7 @@ impl Randomizer {
                 if let Category::Actor(actor_id) = prop.category {
                     if actors.contains(&actor_id) {
                         let linked = if actor_id.needs_flag() {
-                            let f = find_closest_flag(&prop.position, &mut saved_flags);
+                            let f = find_closest_flag(&prop.position, &mut saved_flags)?;
                             Some(f)
                         } else {
                             None
@@ -811,14 +1473,14 @@ impl Randomizer {
                             map_id: *map_id,
                             cube_id,
                             position: prop.position,
-                            prop: Props::Prop1(prop.clone()),
+                            prop: Props::Prop1(*prop),
                             linked,
                         });
                     } else {
-                        saved_props.push(prop.clone());
+                        saved_props.push(*prop);
                     }
                 } else {
-                    saved_props.push(prop.clone());
+                    saved_props.push(*prop);
                 }
             }
 
@@ -868,12 +1530,17 @@ impl Randomizer {
                 }
 
                 if !inserted {
-                    unreachable!();
+                    return Err(RandoError::unexpected(
+                        "cube of a flag",
+                        "a cube around it",
+                        format!("none in {map_id}"),
+                    )
+                    .into());
                 }
             }
         }
 
-        locations
+        Ok(locations)
     }
 
     pub fn remove_specific_actors(&mut self) -> Result<(), Box<dyn Error>> {
@@ -884,20 +1551,14 @@ impl Randomizer {
                 Asset::MapSetup(map_setup) => {
                     for c in &mut map_setup.cubes {
                         c.props_1.retain(|o| {
-                            if let Category::Actor(actor_id) = o.category {
-                                match actor_id {
-                                    ActorId::Unknown(id) => id != 0x373,
-                                    _ => true,
-                                }
-                            } else {
-                                true
-                            }
+                            !matches!(o.category, Category::Actor(ActorId::Unknown(0x373)))
                         });
                     }
                 }
                 Asset::Question(_question) => {}
                 Asset::Unknown(_unknown) => {}
                 Asset::Sprite(_sprite) => {}
+                Asset::N64Sprite(_sprite) => {}
                 Asset::Model(_model) => {}
                 Asset::Midi(_midi) => {}
                 Asset::Empty => {}
@@ -907,365 +1568,502 @@ impl Randomizer {
         Ok(())
     }
 
-    pub fn shuffle_maps(&mut self) -> Result<(), Box<dyn Error>> {
-        #[derive(Debug)]
-        struct BuildingBlock {
-            id: MapSetupId,
-            links: Vec<Option<MapSetupId>>,
-        }
+    pub fn set_costs(&mut self, config: &Config) -> Result<(), Box<dyn Error>> {
+        println!("set note doors costs");
 
-        let mut current_maps = vec![];
+        let items = self.get_progression_items();
+        let random = matches!(config.notedoor_costs, Some(Costs::Random { .. }));
 
-        'outer: loop {
-            let mut maps_left = vec![];
+        for _ in 0..1000 {
+            if let Some(costs) = &config.notedoor_costs {
+                let costs = pick_costs(&mut self.rng, costs, NOTE_DOORS_COSTS.len(), 900)?;
+                self.progression.note_doors_costs.copy_from_slice(&costs);
+            }
 
-            for map in MAPS {
-                maps_left.push(BuildingBlock {
-                    id: map.id,
-                    links: vec![None; map.entrances.len()],
-                });
+            if self.progression.is_beatable(config, &items) {
+                self.write_note_doors_costs()?;
+                self.replace_note_doors_dialogues();
+                return Ok(());
             }
 
-            current_maps = vec![maps_left.swap_remove(0)];
-            let rng = &mut rng();
-            while maps_left.len() > 0 {
-                let index = rng.random_range(..maps_left.len());
-                let mut map = maps_left.swap_remove(index);
-
-                let mut free = vec![];
-                // find maps with free entrance
-                for (i, m) in current_maps.iter().enumerate() {
-                    if m.links.iter().any(|l| l.is_none()) {
-                        free.push(i);
-                    }
-                }
+            if !random {
+                break;
+            }
+        }
 
-                if free.len() == 0 {
-                    // restart
-                    continue 'outer;
-                }
+        Err("Those note doors costs make the game impossible to finish".into())
+    }
 
-                let link_to = rng.random_range(..free.len());
-                let link_to = free[link_to];
+    fn write_note_doors_costs(&mut self) -> Result<(), Box<dyn Error>> {
+        if self.progression.note_doors_costs == NOTE_DOORS_COSTS {
+            return Ok(());
+        }
 
-                let mut free_links = map
-                    .links
-                    .iter()
-                    .enumerate()
-                    .filter(|(_, l)| l.is_none())
-                    .map(|(i, _)| i)
-                    .collect::<Vec<_>>();
+        let xex = &mut self.xex;
 
-                free_links.shuffle(rng);
+        xex.seek(SeekFrom::Start(NOTE_DOORS_COSTS_ADDRESS))?;
+        for cost in NOTE_DOORS_COSTS {
+            let current = xex.read_u16::<BigEndian>()?;
+            if current as u32 != cost {
+                return Err(format!("Unexpected note door cost {current} in default.xex").into());
+            }
+        }
 
-                map.links[free_links[0]] = Some(current_maps[link_to].id);
+        xex.seek(SeekFrom::Start(NOTE_DOORS_COSTS_ADDRESS))?;
+        for cost in self.progression.note_doors_costs {
+            xex.write_u16::<BigEndian>(cost as u16)?;
+        }
 
-                let mut free_links = current_maps[link_to]
-                    .links
-                    .iter()
-                    .enumerate()
-                    .filter(|(_, l)| l.is_none())
-                    .map(|(i, _)| i)
-                    .collect::<Vec<_>>();
+        self.spoiler.note_doors_costs = self.progression.note_doors_costs.to_vec();
+        println!("the doors still display their original costs, see the spoiler log");
 
-                free_links.shuffle(rng);
+        Ok(())
+    }
 
-                current_maps[link_to].links[free_links[0]] = Some(map.id);
+    // "50 NOTES" -> "XX NOTES"
+    fn replace_note_doors_dialogues(&mut self) {
+        let costs = NOTE_DOORS_COSTS
+            .iter()
+            .zip(self.progression.note_doors_costs)
+            .map(|(o, n)| (*o, n))
+            .collect::<Vec<_>>();
 
-                current_maps.push(map);
-            }
+        for asset in &mut self.assets {
+            let Asset::Dialogue(dialogue) = &mut asset.asset else {
+                continue;
+            };
 
-            let mut free = vec![];
-            // find maps with free entrance
-            for (i, m) in current_maps.iter().enumerate() {
-                if m.links.iter().any(|l| l.is_none()) {
-                    free.push(i);
+            for (lang, dial) in dialogue.translations.iter_mut() {
+                for command in dial.top.iter_mut().chain(dial.bottom.iter_mut()) {
+                    if let DialogueCommand::Speak(_, text) = command {
+                        *text = replace_note_counts(text, &costs, *lang);
+                    }
                 }
             }
+        }
+    }
 
-            if free.len() != 2 {
-                continue;
-            }
+    /// Shuffles the doors between the rooms of the lair.
+    /// The entrances of the worlds are left to `shuffle_world_order`.
+    pub fn shuffle_maps(&mut self, config: &Config) -> Result<(), Box<dyn Error>> {
+        println!("shuffle entrances");
 
-            // process 2 remaining unset entrances
+        let items = self.get_progression_items();
+        let movable = self.movable_doors();
 
-            let link_1 = free[0];
-            let link_2 = free[1];
-            let map_1 = current_maps[link_1].id;
-            let map_2 = current_maps[link_2].id;
+        // most layouts lock something behind a note door or a water switch
+        for _ in 0..10_000 {
+            let Some(doors) = link_lair_doors(&mut self.rng, &movable) else {
+                continue;
+            };
 
-            if let Some(map) = current_maps.get_mut(link_1) {
-                let Some(index) = map.links.iter().position(|l| l.is_none()) else {
-                    unreachable!();
-                };
+            self.progression.doors = doors;
 
-                map.links[index] = Some(map_2);
-            } else {
-                unreachable!();
+            // every room must stay reachable, not just Furnace Fun
+            let inventory = self.progression.start_inventory(config);
+            let (_, maps) = self.progression.sweep(inventory, &items);
+            if MAPS.iter().all(|m| maps.contains(&m.id)) {
+                return self.set_lair_doors();
             }
+        }
 
-            if let Some(map) = current_maps.get_mut(link_2) {
-                let Some(index) = map.links.iter().position(|l| l.is_none()) else {
-                    unreachable!();
-                };
+        self.progression.doors.clear();
 
-                map.links[index] = Some(map_1);
-            } else {
-                unreachable!();
-            }
+        Err("Couldn't find an entrance layout where everything can be reached".into())
+    }
 
-            break;
-        }
+    // a door is moved by giving its warp prop the warp of its new destination. the warps
+    // themselves (which room and which exit they lead to) are a table of default.xex that
+    // isn't changed, so doors without their own prop, on either side, stay where they are
+    fn movable_doors(&mut self) -> Vec<Door> {
+        let mut doors = vec![];
 
         for map in MAPS {
-            let asset = self.get_map_setup(map.id);
-            let data = current_maps.iter().find(|m| m.id == map.id).unwrap();
+            if !LAIR_MAPS.contains(&map.id) {
+                continue;
+            }
+
+            let map_setup = self.get_map_setup(map.id);
+            let warps = map_setup
+                .cubes
+                .iter()
+                .flat_map(|c| &c.props_1)
+                .filter_map(|p| match p.category {
+                    Category::WarpOrTrigger(id) => Some(id),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+
+            for (door, entrance) in map.entrances.iter().enumerate() {
+                let shared = map
+                    .entrances
+                    .iter()
+                    .filter(|e| e.warp == entrance.warp)
+                    .count()
+                    > 1;
 
-            for (entry, new_entry) in map.entrances.iter().zip(data.links.clone()) {
-                println!("{} / {}", entry.from, new_entry.unwrap());
+                // doors to Spiral Mountain and to the worlds don't move
+                if LAIR_MAPS.contains(&entrance.from) && !shared && warps.contains(&entrance.warp) {
+                    doors.push((map.id, door));
+                }
             }
         }
 
-        Ok(())
+        doors
+            .iter()
+            .filter(|d| vanilla_door(d.0, d.1).is_some_and(|other| doors.contains(&other)))
+            .copied()
+            .collect()
     }
 
-    fn write_db360(&self) -> Result<(), Box<dyn Error>> {
-        let entry_count = self.assets.len();
+    fn set_lair_doors(&mut self) -> Result<(), Box<dyn Error>> {
+        let mut changes = vec![];
 
-        let mut patched = OpenOptions::new()
-            .create(true)
-            .write(true)
-            .truncate(true)
-            .open("db360.cmp")
-            .expect("Can't write db360.cmp");
+        for &((map_a, door_a), (map_b, door_b)) in &self.progression.doors {
+            changes.push((map_a, door_a, arrival_warp(map_b, door_b)?));
+            changes.push((map_b, door_b, arrival_warp(map_a, door_a)?));
 
-        patched.write_u32::<BigEndian>(entry_count as u32)?;
-        patched.write_u32::<BigEndian>(0xCDCDCDCD)?;
-        for _ in 0..entry_count {
-            patched.write_u32::<BigEndian>(0)?;
-            patched.write_u32::<BigEndian>(0)?;
+            for (map, door, target, target_door) in [
+                (map_a, door_a, map_b, door_b),
+                (map_b, door_b, map_a, door_a),
+            ] {
+                let from = |map: MapSetupId, door: usize| {
+                    get_map(map).unwrap().entrances[door].from.to_string()
+                };
+                self.spoiler.entrances.push(EntranceEntry {
+                    map: map.to_string(),
+                    original: from(map, door),
+                    target: target.to_string(),
+                    target_door: from(target, target_door),
+                });
+            }
         }
 
-        let mut offsets = vec![];
-        let header = patched.seek(SeekFrom::Current(0))?;
+        // all at once, so a changed warp isn't changed again
+        for map in MAPS {
+            let warps = changes
+                .iter()
+                .filter(|(m, _, _)| *m == map.id)
+                .map(|(_, door, warp)| (map.entrances[*door].warp, *warp))
+                .collect::<Vec<_>>();
+
+            if warps.is_empty() {
+                continue;
+            }
 
-        for i in 0..entry_count {
-            let current_offset = patched.seek(SeekFrom::Current(0))? - header;
-            offsets.push(current_offset as u32);
+            let map_setup = self.get_map_setup(map.id);
+            for cube in &mut map_setup.cubes {
+                for prop in &mut cube.props_1 {
+                    let Category::WarpOrTrigger(id) = prop.category else {
+                        continue;
+                    };
 
-            match &self.assets[i].asset {
-                Asset::Animation(animation) => {
-                    animation.write(&mut patched)?;
-                }
-                Asset::Dialogue(dialogue) => {
-                    dialogue.write(&mut patched)?;
-                }
-                Asset::MapSetup(map_setup) => {
-                    map_setup.write(&mut patched)?;
-                }
-                Asset::Question(question) => {
-                    question.write(&mut patched)?;
-                }
-                Asset::Sprite(sprite) => {
-                    sprite.write(&mut patched)?;
-                }
-                Asset::Model(model) => {
-                    model.write(&mut patched)?;
-                }
-                Asset::Midi(midi) => {
-                    midi.write(&mut patched)?;
-                }
-                Asset::Unknown(unknown) => {
-                    unknown.write(&mut patched)?;
+                    if let Some((_, warp)) = warps.iter().find(|(old, _)| *old == id) {
+                        prop.category = Category::WarpOrTrigger(*warp);
+                    }
                 }
-                Asset::Empty => {}
             }
-
-            align_writer(&mut patched)?;
         }
 
-        patched.seek(SeekFrom::Start(8))?;
+        Ok(())
+    }
 
-        for id in 0..ASSETS.len() {
-            patched.write_u32::<BigEndian>(offsets[id])?;
-            patched.write_u32::<BigEndian>(self.assets[id].flag)?;
-        }
+    pub fn write_db360<W: Write + Seek>(&self, patched: &mut W) -> Result<(), Box<dyn Error>> {
+        write_db360(&self.assets, patched)
+    }
 
-        Ok(())
+    pub fn write_textures<W: Write>(&self, writer: &mut W) -> Result<(), Box<dyn Error>> {
+        self.textures.write(writer)
     }
+}
 
-    fn write_textures(&self) -> Result<(), Box<dyn Error>> {
-        let mut file = OpenOptions::new()
-            .write(true)
-            .open("db360.textures.cmp")
-            .expect("Can't write db360.textures.cmp");
-        let metadata_size = 20;
+fn open(input: &Path, name: &'static str) -> Result<File, RandoError> {
+    File::open(input.join(name)).map_err(|source| RandoError::MissingFile { name, source })
+}
 
-        file.seek_relative(4)?;
+fn link_lair_doors<R: Rng + ?Sized>(rng: &mut R, movable: &[Door]) -> Option<Vec<(Door, Door)>> {
+    struct BuildingBlock {
+        id: MapSetupId,
+        free: Vec<usize>,
+    }
 
-        for data in &self.textures {
-            file.write_u32::<BigEndian>(data.edited)?;
-            file.seek_relative(metadata_size - 4)?;
+    let mut maps_left = vec![];
+    for map in MAPS {
+        if !LAIR_MAPS.contains(&map.id) {
+            continue;
         }
 
-        Ok(())
+        let free = movable
+            .iter()
+            .filter(|(m, _)| *m == map.id)
+            .map(|(_, door)| *door)
+            .collect::<Vec<_>>();
+
+        if !free.is_empty() {
+            maps_left.push(BuildingBlock { id: map.id, free });
+        }
     }
-}
 
-fn read_db360() -> Result<Vec<AssetData>, Box<dyn Error>> {
-    let mut file = File::open("db360.cmp").expect("Can't open db360.cmp, missing?");
+    let mut links = vec![];
+
+    // build a tree from the entrance of the lair so that everything is connected
+    let start = maps_left
+        .iter()
+        .position(|m| m.id == MapSetupId::GlMmLobby)?;
+    let mut current_maps = vec![maps_left.swap_remove(start)];
+
+    while !maps_left.is_empty() {
+        // a dead end can only be added if there's still a free door afterward
+        let free_doors = current_maps.iter().map(|m| m.free.len()).sum::<usize>();
+        let candidates = (0..maps_left.len())
+            .filter(|&i| maps_left.len() == 1 || free_doors + maps_left[i].free.len() > 2)
+            .collect::<Vec<_>>();
+
+        let &index = candidates.choose(rng)?;
+        let mut map = maps_left.swap_remove(index);
+
+        let free = current_maps
+            .iter()
+            .enumerate()
+            .filter(|(_, m)| !m.free.is_empty())
+            .map(|(i, _)| i)
+            .collect::<Vec<_>>();
+
+        let &link_to = free.choose(rng)?;
+        let link_to = &mut current_maps[link_to];
+
+        let a = link_to
+            .free
+            .swap_remove(rng.random_range(..link_to.free.len()));
+        let b = map.free.swap_remove(rng.random_range(..map.free.len()));
+        links.push(((link_to.id, a), (map.id, b)));
+
+        current_maps.push(map);
+    }
 
-    let entry_count = file.read_u32::<BigEndian>()?;
-    assert_eq!(entry_count, 3701);
-    let _padding = file.read_u32::<BigEndian>()?;
+    // then link the remaining doors together
+    let mut remaining = current_maps
+        .iter()
+        .flat_map(|m| m.free.iter().map(|d| (m.id, *d)))
+        .collect::<Vec<_>>();
+    remaining.shuffle(rng);
+
+    while let Some(a) = remaining.pop() {
+        let b = remaining.pop()?;
+        if a.0 == b.0 {
+            return None;
+        }
 
-    let mut sizes = vec![];
-    let mut flags = vec![];
+        links.push((a, b));
+    }
 
-    let mut curr_offset = file.read_u32::<BigEndian>()?;
-    let flag = file.read_u32::<BigEndian>()?;
-    flags.push(flag);
+    Some(links)
+}
 
-    for _ in 1..entry_count {
-        let offset = file.read_u32::<BigEndian>()?;
-        let flag = file.read_u32::<BigEndian>()?;
-        flags.push(flag);
+// each line of Brentilda gets the next hint, from one dialogue to the next
+fn write_hints(assets: &mut [AssetData], dialogues: &[usize], hints: &[Hint]) {
+    let mut ids = HashMap::new();
 
-        sizes.push((offset - curr_offset) as usize);
-        curr_offset = offset;
+    for id in dialogues {
+        let Some(Asset::Dialogue(dialogue)) = assets.get_mut(*id).map(|a| &mut a.asset) else {
+            continue;
+        };
+
+        for (lang, data) in dialogue.translations.iter_mut() {
+            let id = ids.entry(*lang).or_insert(0);
+            for command in data.top.iter_mut().chain(data.bottom.iter_mut()) {
+                if let DialogueCommand::Speak(Speaker::Brentilda, text) = command {
+                    *text = hints[*id % hints.len()].text(*lang);
+                    *id += 1;
+                }
+            }
+        }
     }
+}
 
-    let file_size = file.metadata()?.len() as usize;
-    sizes.push(file_size - curr_offset as usize);
+fn brentilda_lines(dialogue: &Dialogue, lang: Language) -> impl Iterator<Item = &String> {
+    dialogue
+        .translations
+        .get(&lang)
+        .into_iter()
+        .flat_map(|data| data.top.iter().chain(&data.bottom))
+        .filter_map(|c| match c {
+            DialogueCommand::Speak(Speaker::Brentilda, text) => Some(text),
+            _ => None,
+        })
+}
 
-    assert_eq!(sizes.len(), entry_count as usize);
+// puts the plando worlds behind their entrance (`order` doesn't have the lair)
+fn pin_worlds(order: &mut [LevelOrder], pins: &[PlandoWorld]) {
+    let lair: usize = LevelOrder::Lair.into();
+    for pin in pins {
+        let entrance: usize = pin.entrance.into();
+        let slot = if entrance > lair {
+            entrance - 1
+        } else {
+            entrance
+        };
 
-    let mut loaded_assets = vec![];
+        if let Some(current) = order.iter().position(|l| *l == pin.world) {
+            order.swap(slot, current);
+        }
+    }
+}
 
-    for (id, asset) in ASSETS.iter().enumerate() {
-        let _pos = file.seek(SeekFrom::Current(0))?;
+fn is_plando_item(item: PlandoProp, prop: &Props) -> bool {
+    match (item, prop) {
+        (
+            PlandoProp::Actor(id),
+            Props::Prop1(Prop1 {
+                category: Category::Actor(actor),
+                ..
+            }),
+        ) => id == *actor,
+        (PlandoProp::Sprite(id), Props::Prop2(Prop2::Sprite { id: sprite, .. })) => id == *sprite,
+        _ => false,
+    }
+}
 
-        let asset = match asset {
-            AssetId::Empty => Asset::Empty,
-            AssetId::Animation(_animation_id) => {
-                let data = Animation::new(&mut file)?;
-                Asset::Animation(data)
-            }
-            AssetId::Midi(_midi_id) => {
-                let data = Midi::new(&mut file, sizes[id])?;
-                Asset::Midi(data)
-            }
-            AssetId::Model(_model_id) => {
-                let data = Model::new(&mut file, sizes[id])?;
-                Asset::Model(data)
-            }
-            AssetId::MapSetup(_map_setup_id) => {
-                let map = MapSetup::new(&mut file)?;
-                Asset::MapSetup(map)
-            }
-            AssetId::Dialogue(_dialogue_id) => {
-                let data = Dialogue::new(&mut file)?;
-                Asset::Dialogue(data)
-            }
-            AssetId::Credits(_credits_id) => {
-                let data = Dialogue::new(&mut file)?;
-                Asset::Dialogue(data)
-            }
-            AssetId::Sprite(sprite_id) => {
-                match sprite_id {
-                    // these sprites have a "strange" format (closer to N64 or something else)
-                    SpriteId::Sprite0064D520
-                    | SpriteId::Sprite0064E8D8
-                    | SpriteId::Sprite0064EB58
-                    | SpriteId::Sprite006536C8
-                    | SpriteId::Sprite006546D8
-                    | SpriteId::Sprite0064ECC8 => {
-                        let data = Unknown::new(&mut file, sizes[id])?;
-                        Asset::Unknown(data)
-                    }
-                    _ => {
-                        let data = Sprite::new(&mut file)?;
-                        Asset::Sprite(data)
-                    }
+// the worlds of each pool, the lair is only shuffled when it's in a group
+fn pool_levels(pool: &Pool) -> Vec<Vec<LevelOrder>> {
+    let levels = (0..LEVELS_INFO.len()).map(LevelOrder::from);
+
+    match pool {
+        Pool::Level => levels
+            .filter(|l| *l != LevelOrder::Lair)
+            .map(|l| vec![l])
+            .collect(),
+        Pool::Groups(groups) => {
+            let mut pools = groups.clone();
+            for level in levels {
+                if level != LevelOrder::Lair && !groups.iter().any(|g| g.contains(&level)) {
+                    pools.push(vec![level]);
                 }
             }
-            AssetId::Question(_question_id) => {
-                let data = Question::new(&mut file)?;
-                Asset::Question(data)
-            }
-            AssetId::Xbox(_xbox_id) => {
-                let data = Dialogue::new(&mut file)?;
-                Asset::Dialogue(data)
-            }
-            AssetId::Unknown(_unknown_id) => {
-                let data = Unknown::new(&mut file, sizes[id])?;
-                Asset::Unknown(data)
-            }
-        };
-
-        loaded_assets.push(AssetData {
-            asset,
-            flag: flags[id],
-        });
+            pools
+        }
+        Pool::Global => vec![levels.collect()],
+    }
+}
 
-        // for some reason, some files are aligned on 8 bytes and some aren't
-        while let Ok(byte) = file.read_u8() {
-            if byte != 0xCD {
-                break;
+// sorted, so it still gets more expensive as you go
+fn pick_costs<R: Rng + ?Sized>(
+    rng: &mut R,
+    costs: &Costs,
+    count: usize,
+    max: u32,
+) -> Result<Vec<u32>, Box<dyn Error>> {
+    let costs = match costs {
+        Costs::Fixed(costs) => {
+            if costs.len() != count {
+                return Err(format!("Expected {count} costs, got {}", costs.len()).into());
             }
+
+            costs.clone()
+        }
+        Costs::Random { min, max } => {
+            let mut costs = (0..count)
+                .map(|_| rng.random_range(*min..=*max))
+                .collect::<Vec<_>>();
+            costs.sort();
+            costs
         }
+    };
 
-        file.seek_relative(-1)?;
+    if costs.iter().any(|c| *c > max) {
+        return Err(format!("Costs can't be higher than {max}").into());
     }
 
-    assert_eq!(entry_count as usize, loaded_assets.len());
-
-    Ok(loaded_assets)
+    Ok(costs)
 }
 
-fn read_textures() -> Result<Vec<TextureData>, Box<dyn Error>> {
-    let mut file =
-        File::open("db360.textures.cmp").expect("Can't open db360.textures.cmp, missing?");
-    let entry_count = file.read_u32::<BigEndian>()?;
-    assert_eq!(entry_count, 6576);
-    let metadata_size = 20;
-
-    let mut addresses = vec![];
-    for _ in 0..entry_count {
-        let address = file.read_u32::<BigEndian>()?;
-        addresses.push(TextureData {
-            address,
-            edited: address,
-        });
-        file.seek_relative(metadata_size - 4)?;
+// only numbers followed by "NOTE" (NOTES, NOTEN) are changed, and each only once.
+// the Japanese lines don't put the word right after the number: every count in a line
+// about notes (おんぷ) is changed
+fn replace_note_counts(text: &str, costs: &[(u32, u32)], lang: Language) -> String {
+    let about_notes = text.contains("おんぷ") || text.contains("オンプ");
+    let mut result = String::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find(|c: char| c.is_ascii_digit()) {
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let end = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let (number, after) = rest.split_at(end);
+
+        let replacement = number
+            .parse::<u32>()
+            .ok()
+            .filter(|_| match lang {
+                Language::Japanese => about_notes,
+                _ => after.starts_with(" NOTE"),
+            })
+            .and_then(|n| costs.iter().find(|(old, _)| *old == n))
+            .map(|(_, new)| new.to_string());
+
+        result.push_str(replacement.as_deref().unwrap_or(number));
+        rest = after;
     }
 
-    Ok(addresses)
+    result.push_str(rest);
+    result
 }
 
-fn find_closest_flag(position: &Vector3<i16>, flags: &mut Vec<Prop1>) -> Prop1 {
-    let mut closest = (99999999, None);
-    for (idx, f) in flags.iter().enumerate() {
-        let dist = distance(position, &f.position);
+// the door on the other side of `door`, in the vanilla game
+fn vanilla_door(map: MapSetupId, door: usize) -> Option<Door> {
+    let entrances = get_map(map)?.entrances;
+    let from = entrances.get(door)?.from;
+
+    // with 2 doors between the same maps, the nth door of one side matches the nth of the other
+    let nth = entrances[..door].iter().filter(|e| e.from == from).count();
+    let mut doors = get_map(from)?
+        .entrances
+        .iter()
+        .enumerate()
+        .filter(|(_, e)| e.from == map)
+        .map(|(i, _)| i);
+
+    let other = doors.clone().nth(nth).or(doors.next())?;
+    Some((from, other))
+}
 
-        if dist < closest.0 {
-            closest.0 = dist;
-            closest.1 = Some(idx);
-        }
-    }
+// the warp that makes you come out of `door`
+fn arrival_warp(map: MapSetupId, door: usize) -> Result<WarpOrTriggerId, Box<dyn Error>> {
+    let (from, other) = vanilla_door(map, door)
+        .ok_or_else(|| format!("There are no warps to door {door} of {map}"))?;
 
-    if closest.1.is_none() {
-        panic!("{}", closest.0);
-    }
+    Ok(get_map(from).unwrap().entrances[other].warp)
+}
 
-    assert!(closest.0.isqrt() < 150);
-    flags.remove(closest.1.unwrap())
+fn find_closest_flag(position: &Vector3<i16>, flags: &mut Vec<Prop1>) -> Result<Prop1, RandoError> {
+    let closest = flags
+        .iter()
+        .enumerate()
+        .map(|(idx, f)| (distance(position, &f.position), idx))
+        .min();
+
+    match closest {
+        Some((dist, idx)) if dist.isqrt() < 150 => Ok(flags.remove(idx)),
+        Some((dist, _)) => Err(RandoError::unexpected(
+            "distance to the flag of an actor",
+            "less than 150",
+            dist.isqrt(),
+        )),
+        None => Err(RandoError::unexpected(
+            "flags of an actor",
+            "one near it",
+            "none",
+        )),
+    }
 }
 
 fn distance(a: &Vector3<i16>, b: &Vector3<i16>) -> u32 {
-    let x = (a.x - b.x).abs() as u32;
+    let x = (a.x - b.x).unsigned_abs() as u32;
     let y = 0; //(a.y - b.y).abs() as u32;
-    let z = (a.z - b.z).abs() as u32;
+    let z = (a.z - b.z).unsigned_abs() as u32;
 
     x * x + y * y + z * z
 }
@@ -1294,3 +2092,46 @@ fn compare_position(position: i16, cube: i32) -> bool {
 
     cube <= position && position < (cube + 1000)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assets::dialogue::DialogueData;
+
+    fn brentilda_dialogue() -> AssetData {
+        let data = DialogueData {
+            top: vec![],
+            bottom: vec![DialogueCommand::Speak(Speaker::Brentilda, "TRIVIA".into())],
+        };
+
+        AssetData {
+            asset: Asset::Dialogue(Dialogue {
+                translations: HashMap::from([(Language::English, data)]),
+            }),
+            flag: 0,
+        }
+    }
+
+    #[test]
+    fn hints_across_dialogues() {
+        let hints = [
+            Hint::World(LevelOrder::MumbosMountain, LevelOrder::GobisValley),
+            Hint::World(LevelOrder::GobisValley, LevelOrder::MumbosMountain),
+        ];
+        let mut assets = vec![brentilda_dialogue(), brentilda_dialogue()];
+
+        write_hints(&mut assets, &[0, 1], &hints);
+
+        let lines = assets
+            .iter()
+            .map(|a| match &a.asset {
+                Asset::Dialogue(dialogue) => brentilda_lines(dialogue, Language::English)
+                    .cloned()
+                    .collect::<Vec<_>>(),
+                _ => unreachable!(),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(lines[0], [hints[0].text(Language::English)]);
+        assert_eq!(lines[1], [hints[1].text(Language::English)]);
+    }
+}
@@ -0,0 +1,170 @@
+use crate::utils::Vector3;
+use serde::Serialize;
+use std::error::Error;
+use std::fmt;
+use std::path::Path;
+
+#[derive(Default, Serialize)]
+pub struct Spoiler {
+    pub seed: u64,
+    pub world_order: Vec<WorldEntry>,
+    pub molehills: Vec<MolehillEntry>,
+    pub entrances: Vec<EntranceEntry>,
+    pub note_doors_costs: Vec<u32>,
+    pub locations: Vec<LocationEntry>,
+    pub enemies: Vec<EnemyEntry>,
+    /// what Brentilda says, in English
+    pub hints: Vec<String>,
+    pub music: Vec<MusicEntry>,
+}
+
+#[derive(Serialize)]
+pub struct WorldEntry {
+    /// the world whose entrance (painting, lobby, puzzle) is used
+    pub entrance: String,
+    pub world: String,
+}
+
+#[derive(Serialize)]
+pub struct MolehillEntry {
+    pub level: String,
+    /// the ability taught by this molehill in the original game
+    pub original: String,
+    pub ability: String,
+}
+
+#[derive(Serialize)]
+pub struct EntranceEntry {
+    pub map: String,
+    /// where the door led in the original game
+    pub original: String,
+    pub target: String,
+    /// the door of `target` you come out of, named after where it originally led
+    pub target_door: String,
+}
+
+#[derive(Serialize)]
+pub struct LocationEntry {
+    pub map: String,
+    pub cube: usize,
+    pub position: Vector3<i16>,
+    pub original: String,
+    pub item: String,
+}
+
+#[derive(Serialize)]
+pub struct MusicEntry {
+    pub track: String,
+    /// the track or the file played instead
+    pub song: String,
+}
+
+#[derive(Serialize)]
+pub struct EnemyEntry {
+    pub map: String,
+    pub cube: usize,
+    pub position: Vector3<i16>,
+    pub original: String,
+    pub enemy: String,
+}
+
+impl Spoiler {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            ..Default::default()
+        }
+    }
+
+    pub fn write(&self, output: &Path) -> Result<(), Box<dyn Error>> {
+        let json = serde_json::to_string_pretty(self)?;
+        std::fs::write(output.join("spoiler.json"), json)?;
+        std::fs::write(output.join("spoiler.txt"), self.to_string())?;
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for Spoiler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Seed: {}", self.seed)?;
+
+        if !self.world_order.is_empty() {
+            writeln!(f, "\nWorld order:")?;
+            for entry in &self.world_order {
+                writeln!(f, "    {} -> {}", entry.entrance, entry.world)?;
+            }
+        }
+
+        if !self.molehills.is_empty() {
+            writeln!(f, "\nMolehills:")?;
+            for entry in &self.molehills {
+                writeln!(
+                    f,
+                    "    {} ({}) -> {}",
+                    entry.level, entry.original, entry.ability
+                )?;
+            }
+        }
+
+        if !self.entrances.is_empty() {
+            writeln!(f, "\nEntrances:")?;
+            for entry in &self.entrances {
+                writeln!(
+                    f,
+                    "    {} (to {}) -> {} (from {})",
+                    entry.map, entry.original, entry.target, entry.target_door
+                )?;
+            }
+        }
+
+        if !self.note_doors_costs.is_empty() {
+            let costs = self
+                .note_doors_costs
+                .iter()
+                .map(|c| c.to_string())
+                .collect::<Vec<_>>();
+            writeln!(f, "\nNote doors: {}", costs.join(", "))?;
+        }
+
+        if !self.locations.is_empty() {
+            writeln!(f, "\nLocations:")?;
+            for entry in &self.locations {
+                let Vector3 { x, y, z } = entry.position;
+                writeln!(
+                    f,
+                    "    {} [cube {}] ({x}, {y}, {z}): {} -> {}",
+                    entry.map, entry.cube, entry.original, entry.item
+                )?;
+            }
+        }
+
+        if !self.hints.is_empty() {
+            writeln!(f, "\nHints:")?;
+            for hint in &self.hints {
+                writeln!(f, "    {hint}")?;
+            }
+        }
+
+        if !self.music.is_empty() {
+            writeln!(f, "\nMusic:")?;
+            for entry in &self.music {
+                writeln!(f, "    {} -> {}", entry.track, entry.song)?;
+            }
+        }
+
+        if !self.enemies.is_empty() {
+            writeln!(f, "\nEnemies:")?;
+            for entry in &self.enemies {
+                let Vector3 { x, y, z } = entry.position;
+                writeln!(
+                    f,
+                    "    {} [cube {}] ({x}, {y}, {z}): {} -> {}",
+                    entry.map, entry.cube, entry.original, entry.enemy
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
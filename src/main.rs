@@ -1,91 +1,632 @@
-#![allow(unused)]
-
-use crate::data::NOTE_DOORS_COSTS;
-use crate::enums::ActorId;
-use enums::SpritePropId;
-use logic::randomizer::Randomizer;
-use serde::Deserialize;
+use bk_rando::Config;
+use bk_rando::Costs;
+use bk_rando::CustomMusic;
+use bk_rando::CustomQuestion;
+use bk_rando::CustomTexture;
+use bk_rando::Hints;
+use bk_rando::LevelOrder;
+use bk_rando::Plando;
+use bk_rando::Pool;
+use bk_rando::RandoError;
+use bk_rando::Randomizer;
+use bk_rando::assets::Asset;
+use bk_rando::assets::db360;
+use bk_rando::assets::db360::DB360_FILENAME;
+use bk_rando::assets::dump;
+use bk_rando::assets::midi::Midi;
+use bk_rando::assets::obj::Obj;
+use bk_rando::enums::ActorId;
+use bk_rando::enums::MapSetupId;
+use bk_rando::enums::ModelId;
+use bk_rando::enums::SpritePropId;
+use bk_rando::enums::midis::MidiId;
+use bk_rando::strings::STRINGS_FILENAME;
+use bk_rando::strings::Strings;
+use bk_rando::textures::Image;
+use bk_rando::textures::TEXTURES_FILENAME;
+use bk_rando::textures::Textures;
+use clap::Args;
+use clap::Parser;
+use clap::Subcommand;
+use serde::de::DeserializeOwned;
 use std::error::Error;
+use std::fs::File;
+use std::io::BufReader;
+use std::io::BufWriter;
+use std::io::Cursor;
+use std::path::Path;
+use std::path::PathBuf;
 
-mod assets;
-mod data;
-mod enums;
-mod logic;
-mod strings;
-mod utils;
+#[derive(Parser)]
+#[command(version, about = "Banjo-Kazooie randomizer for the Xbox 360")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
 
-#[derive(Debug, Default, Deserialize)]
-struct Config {
-    #[serde(default)]
-    actors: Vec<ActorId>,
+#[derive(Subcommand)]
+enum Command {
+    /// Randomize a copy of the game
+    Randomize(Box<RandomizeArgs>),
+    /// Check that the game files can be loaded
+    Verify(VerifyArgs),
+    /// Check that every asset of db360.cmp is written back exactly as it was read
+    VerifyRoundtrip(GameArgs),
+    /// Write every asset of db360.cmp to its own file
+    Dump(DumpArgs),
+    /// Rebuild db360.cmp from a directory written by `dump`
+    Pack(PackArgs),
+    /// Write models, and the props of a map, as a Wavefront OBJ
+    Export(ExportArgs),
+}
+
+#[derive(Args)]
+struct GameArgs {
+    /// Directory containing default.xex, db360.cmp and db360.textures.cmp
+    #[arg(short, long, default_value = ".")]
+    input: PathBuf,
+}
 
-    #[serde(default)]
-    sprites: Vec<SpritePropId>,
+#[derive(Args)]
+struct VerifyArgs {
+    #[command(flatten)]
+    game: GameArgs,
 
-    #[serde(default)]
-    mix: bool,
+    /// Config the game was randomized with, for what can't be read back from the files
+    #[arg(short, long)]
+    config: Option<PathBuf>,
+}
 
-    #[serde(default)]
-    worlds: bool,
+#[derive(Args)]
+struct DumpArgs {
+    #[command(flatten)]
+    game: GameArgs,
 
-    #[serde(default)]
-    moves: bool,
+    /// Directory where the assets are written
+    #[arg(short, long)]
+    output: PathBuf,
+}
 
-    #[serde(default)]
-    notedoors: Vec<u32>,
+#[derive(Args)]
+struct PackArgs {
+    /// Directory written by `dump`
+    #[arg(short, long)]
+    input: PathBuf,
 
-    #[serde(default)]
-    pipes: bool,
+    /// Directory where db360.cmp is written
+    #[arg(short, long)]
+    output: PathBuf,
+}
 
-    #[serde(default)]
-    cauldrons: bool,
+#[derive(Args)]
+struct ExportArgs {
+    #[command(flatten)]
+    game: GameArgs,
 
-    #[serde(default)]
-    skip_furnace_fun: bool,
+    /// OBJ file to write, its MTL and textures are written next to it
+    #[arg(short, long)]
+    output: PathBuf,
 
-    #[serde(default)]
-    enemies: bool,
+    /// Models to draw, by name (e.g. Model001D0758) or by index in db360.cmp
+    #[arg(short, long, value_delimiter = ',', value_parser = parse_model)]
+    models: Vec<ModelId>,
 
-    #[serde(default)]
-    easy_talon_trot: bool,
+    /// Map whose props are drawn as markers (e.g. TreasureTroveCove)
+    #[arg(long, value_parser = parse_enum::<MapSetupId>)]
+    map: Option<MapSetupId>,
 }
 
-fn main() -> Result<(), Box<dyn Error>> {
-    let strbuf = std::fs::read_to_string("config.json").unwrap();
-    let Ok(config) = serde_json::from_str::<Config>(&strbuf) else {
-        eprintln!("config.json is malformed!");
-        return Ok(());
+#[derive(Args)]
+struct RandomizeArgs {
+    #[command(flatten)]
+    game: GameArgs,
+
+    /// Directory where the randomized files are written (must differ from the input directory)
+    #[arg(short, long)]
+    output: PathBuf,
+
+    /// Path to the configuration file
+    #[arg(short, long, default_value = "config.json")]
+    config: PathBuf,
+
+    /// Seed to use instead of the one in the configuration file
+    #[arg(short, long)]
+    seed: Option<u64>,
+
+    /// File of placements to force, replaces the `plando` of the configuration file
+    #[arg(short, long)]
+    plando: Option<PathBuf>,
+
+    /// File of Furnace Fun questions, replaces the `questions` of the configuration file
+    #[arg(short, long)]
+    questions: Option<PathBuf>,
+
+    #[command(flatten)]
+    overrides: ConfigOverrides,
+}
+
+/// Options overriding the ones read from the configuration file.
+#[derive(Args)]
+struct ConfigOverrides {
+    #[arg(long, value_delimiter = ',', value_parser = parse_enum::<ActorId>)]
+    actors: Option<Vec<ActorId>>,
+
+    #[arg(long, value_delimiter = ',', value_parser = parse_enum::<SpritePropId>)]
+    sprites: Option<Vec<SpritePropId>>,
+
+    #[arg(long)]
+    mix: Option<bool>,
+
+    /// "level", "global", or groups of worlds ("MumbosMountain,TreasureTroveCove;ClankersCavern,...")
+    #[arg(long, value_parser = parse_pool)]
+    pool: Option<Pool>,
+
+    #[arg(long)]
+    worlds: Option<bool>,
+
+    #[arg(long)]
+    entrances: Option<bool>,
+
+    #[arg(long)]
+    moves: Option<bool>,
+
+    #[arg(long, value_delimiter = ',')]
+    notedoors: Option<Vec<u32>>,
+
+    /// either every cost ("50,180,...") or a range ("100..800")
+    #[arg(long, value_parser = parse_costs)]
+    notedoor_costs: Option<Costs>,
+
+    #[arg(long)]
+    pipes: Option<bool>,
+
+    #[arg(long)]
+    cauldrons: Option<bool>,
+
+    #[arg(long)]
+    skip_furnace_fun: Option<bool>,
+
+    #[arg(long)]
+    mix_questions: Option<bool>,
+
+    #[arg(long)]
+    seed_questions: Option<usize>,
+
+    #[arg(long)]
+    enemies: Option<bool>,
+
+    #[arg(long)]
+    music: Option<bool>,
+
+    #[arg(long)]
+    jingles: Option<bool>,
+
+    /// tracks and the MIDI files replacing them ("MumbosMountain=song.mid,...")
+    #[arg(long, value_delimiter = ',', value_parser = parse_custom_music)]
+    custom_music: Option<Vec<CustomMusic>>,
+
+    /// textures and the PNG files replacing them ("2071=sign.png,...")
+    #[arg(long, value_delimiter = ',', value_parser = parse_custom_texture)]
+    custom_textures: Option<Vec<CustomTexture>>,
+
+    #[arg(long)]
+    easy_talon_trot: Option<bool>,
+
+    /// weight of each kind of hint ("items=2,moves=1,worlds=1")
+    #[arg(long, value_parser = parse_hints)]
+    hints: Option<Hints>,
+}
+
+impl ConfigOverrides {
+    fn apply(self, config: &mut Config) {
+        if let Some(actors) = self.actors {
+            config.actors = actors;
+        }
+        if let Some(sprites) = self.sprites {
+            config.sprites = sprites;
+        }
+        if let Some(mix) = self.mix {
+            config.mix = mix;
+        }
+        if let Some(pool) = self.pool {
+            config.pool = pool;
+        }
+        if let Some(worlds) = self.worlds {
+            config.worlds = worlds;
+        }
+        if let Some(entrances) = self.entrances {
+            config.entrances = entrances;
+        }
+        if let Some(moves) = self.moves {
+            config.moves = moves;
+        }
+        if let Some(notedoors) = self.notedoors {
+            config.notedoors = notedoors;
+        }
+        if let Some(notedoor_costs) = self.notedoor_costs {
+            config.notedoor_costs = Some(notedoor_costs);
+        }
+        if let Some(pipes) = self.pipes {
+            config.pipes = pipes;
+        }
+        if let Some(cauldrons) = self.cauldrons {
+            config.cauldrons = cauldrons;
+        }
+        if let Some(skip_furnace_fun) = self.skip_furnace_fun {
+            config.skip_furnace_fun = skip_furnace_fun;
+        }
+        if let Some(mix_questions) = self.mix_questions {
+            config.mix_questions = mix_questions;
+        }
+        if let Some(seed_questions) = self.seed_questions {
+            config.seed_questions = seed_questions;
+        }
+        if let Some(enemies) = self.enemies {
+            config.enemies = enemies;
+        }
+        if let Some(music) = self.music {
+            config.music = music;
+        }
+        if let Some(jingles) = self.jingles {
+            config.jingles = jingles;
+        }
+        if let Some(custom_music) = self.custom_music {
+            config.custom_music = custom_music;
+        }
+        if let Some(custom_textures) = self.custom_textures {
+            config.custom_textures = custom_textures;
+        }
+        if let Some(easy_talon_trot) = self.easy_talon_trot {
+            config.easy_talon_trot = easy_talon_trot;
+        }
+        if let Some(hints) = self.hints {
+            config.hints = Some(hints);
+        }
+    }
+}
+
+// reuse the names accepted in config.json
+fn parse_enum<T: DeserializeOwned>(name: &str) -> Result<T, String> {
+    serde_json::from_value(serde_json::Value::String(name.into()))
+        .map_err(|_| format!("unknown value `{name}`"))
+}
+
+fn parse_model(value: &str) -> Result<ModelId, String> {
+    match value.parse::<u16>() {
+        Ok(index) => Ok(ModelId::from(index)),
+        Err(_) => parse_enum(value),
+    }
+}
+
+fn parse_pool(value: &str) -> Result<Pool, String> {
+    match value {
+        "level" => Ok(Pool::Level),
+        "global" => Ok(Pool::Global),
+        _ => value
+            .split(';')
+            .map(|group| group.split(',').map(parse_enum::<LevelOrder>).collect())
+            .collect::<Result<_, _>>()
+            .map(Pool::Groups),
+    }
+}
+
+fn parse_costs(value: &str) -> Result<Costs, String> {
+    if let Some((min, max)) = value.split_once("..") {
+        let min = min
+            .parse()
+            .map_err(|_| format!("invalid minimum `{min}`"))?;
+        let max = max
+            .parse()
+            .map_err(|_| format!("invalid maximum `{max}`"))?;
+        if min > max {
+            return Err(format!("the minimum {min} is more than the maximum {max}"));
+        }
+        return Ok(Costs::Random { min, max });
+    }
+
+    value
+        .split(',')
+        .map(|c| c.parse().map_err(|_| format!("invalid cost `{c}`")))
+        .collect::<Result<Vec<_>, _>>()
+        .map(Costs::Fixed)
+}
+
+fn parse_custom_music(value: &str) -> Result<CustomMusic, String> {
+    let Some((track, file)) = value.split_once('=') else {
+        return Err(format!("expected `track=file`, found `{value}`"));
+    };
+
+    Ok(CustomMusic {
+        track: parse_enum::<MidiId>(track)?,
+        file: file.into(),
+    })
+}
+
+fn parse_custom_texture(value: &str) -> Result<CustomTexture, String> {
+    let Some((texture, file)) = value.split_once('=') else {
+        return Err(format!("expected `texture=file`, found `{value}`"));
+    };
+
+    Ok(CustomTexture {
+        texture: texture
+            .parse()
+            .map_err(|_| format!("invalid texture `{texture}`"))?,
+        file: file.into(),
+    })
+}
+
+fn parse_hints(value: &str) -> Result<Hints, String> {
+    let mut hints = Hints {
+        items: 0,
+        moves: 0,
+        worlds: 0,
+    };
+
+    for kind in value.split(',') {
+        let Some((name, weight)) = kind.split_once('=') else {
+            return Err(format!("expected `kind=weight`, found `{kind}`"));
+        };
+        let weight = weight
+            .parse()
+            .map_err(|_| format!("invalid weight `{weight}`"))?;
+
+        match name {
+            "items" => hints.items = weight,
+            "moves" => hints.moves = weight,
+            "worlds" => hints.worlds = weight,
+            _ => return Err(format!("unknown kind of hint `{name}`")),
+        }
+    }
+
+    Ok(hints)
+}
+
+fn main() {
+    let cli = Cli::parse();
+
+    let result = match cli.command {
+        Command::Randomize(args) => randomize(*args),
+        Command::Verify(args) => verify(args),
+        Command::VerifyRoundtrip(args) => verify_roundtrip(args),
+        Command::Dump(args) => dump(args),
+        Command::Pack(args) => pack(args),
+        Command::Export(args) => export(args),
+    };
+
+    // Display is a lot more readable than the Debug `main` would print
+    if let Err(e) = result {
+        eprintln!("Error: {e}");
+        std::process::exit(1);
+    }
+}
+
+fn verify(args: VerifyArgs) -> Result<(), Box<dyn Error>> {
+    let rando = Randomizer::new(&args.game.input, 0)?;
+    println!("{} is a valid game directory", args.game.input.display());
+
+    if rando.is_patched()? {
+        println!("It has already been randomized");
+    }
+
+    let config = match &args.config {
+        Some(path) => read_json::<Config>(path)?,
+        None => Config::default(),
     };
+    let report = rando.check_progression(&config)?;
+    print!("{report}");
+    if !report.is_ok() {
+        return Err("This game can't be finished!".into());
+    }
+
+    Ok(())
+}
+
+fn open_db360(input: &Path) -> Result<BufReader<File>, Box<dyn Error>> {
+    let file =
+        File::open(input.join(DB360_FILENAME)).map_err(|source| RandoError::MissingFile {
+            name: DB360_FILENAME,
+            source,
+        })?;
+    Ok(BufReader::new(file))
+}
 
-    if !config
-        .notedoors
-        .iter()
-        .all(|c| NOTE_DOORS_COSTS.contains(c))
-    {
-        eprintln!("Invalid note door!");
+fn verify_roundtrip(args: GameArgs) -> Result<(), Box<dyn Error>> {
+    let db360::Roundtrip { mismatches, unread } =
+        db360::verify_roundtrip(&mut open_db360(&args.input)?)?;
+
+    if !unread.is_empty() {
+        println!("{} assets can't be read and are kept as is:", unread.len());
+        for u in &unread {
+            println!("- {} ({}): {}", u.id, u.asset, u.error);
+        }
+    }
+
+    if mismatches.is_empty() {
+        println!("Every asset of {DB360_FILENAME} is written back identically");
         return Ok(());
     }
 
-    if config.actors.iter().any(|c| c.is_enemy()) {
-        eprintln!("Can't shuffle enemies with other actors!");
+    println!(
+        "{} assets are not written back identically:",
+        mismatches.len()
+    );
+    for m in &mismatches {
+        println!(
+            "- {} ({}): differs at 0x{:X}, {} bytes read, {} bytes written",
+            m.id, m.asset, m.offset, m.original_size, m.written_size
+        );
+    }
+
+    Err(format!("{} assets differ from {DB360_FILENAME}", mismatches.len()).into())
+}
+
+fn dump(args: DumpArgs) -> Result<(), Box<dyn Error>> {
+    let assets = db360::read_db360(&mut open_db360(&args.game.input)?)?;
+    let count = dump::dump_db360(&assets, &args.output)?;
+    println!("{count} assets written in {}", args.output.display());
+
+    let path = args.game.input.join(TEXTURES_FILENAME);
+    if !path.exists() {
+        println!("{TEXTURES_FILENAME} not found, the textures won't be written");
         return Ok(());
     }
 
-    let mut rando = Randomizer::new()?;
+    let textures = Textures::new(&std::fs::read(path)?)?;
+    let (decoded, raw) = dump::dump_textures(&textures, &args.output)?;
+    println!("{decoded} textures written as PNG, {raw} that can't be decoded as raw data");
+    let count = dump::dump_sprites(&assets, &textures, &args.output)?;
+    println!("{count} sprites written as PNG");
+
+    Ok(())
+}
+
+fn pack(args: PackArgs) -> Result<(), Box<dyn Error>> {
+    let assets = dump::read_dump(&args.input)?;
+
+    std::fs::create_dir_all(&args.output)?;
+    let path = args.output.join(DB360_FILENAME);
+    db360::write_db360(&assets, &mut BufWriter::new(File::create(&path)?))?;
+    println!("{} written", path.display());
+
+    Ok(())
+}
+
+fn export(args: ExportArgs) -> Result<(), Box<dyn Error>> {
+    if args.models.is_empty() && args.map.is_none() {
+        return Err("nothing to export, give --models or --map".into());
+    }
+
+    let assets = db360::read_db360(&mut open_db360(&args.game.input)?)?;
+    let path = args.game.input.join(TEXTURES_FILENAME);
+    let textures = if path.exists() {
+        Some(Textures::new(&std::fs::read(path)?)?)
+    } else {
+        println!("{TEXTURES_FILENAME} not found, the models won't be textured");
+        None
+    };
+
+    let mut obj = Obj::new();
+    for model in &args.models {
+        let id: u16 = (*model).into();
+        let Some(Asset::Model(data)) = assets.get(id as usize).map(|a| &a.asset) else {
+            return Err(format!("{model} isn't a model that can be read").into());
+        };
+        obj.add_model(&model.to_string(), data, textures.as_ref())?;
+    }
+
+    if let Some(map) = args.map {
+        let id: u16 = map.into();
+        let Some(Asset::MapSetup(map_setup)) = assets.get(id as usize).map(|a| &a.asset) else {
+            return Err(format!("{map} isn't a map setup").into());
+        };
+        obj.add_markers(map_setup)?;
+    }
+
+    obj.save(&args.output)?;
+    println!("{} written", args.output.display());
+
+    Ok(())
+}
+
+fn is_same_directory(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        // the output directory doesn't exist yet
+        _ => false,
+    }
+}
+
+fn read_json<T: DeserializeOwned>(path: &Path) -> Result<T, Box<dyn Error>> {
+    let Ok(strbuf) = std::fs::read_to_string(path) else {
+        return Err(format!("Can't read {}!", path.display()).into());
+    };
+    serde_json::from_str(&strbuf)
+        .map_err(|e| format!("{} is malformed! ({e})", path.display()).into())
+}
+
+fn randomize(args: RandomizeArgs) -> Result<(), Box<dyn Error>> {
+    let mut config = read_json::<Config>(&args.config)?;
+
+    args.overrides.apply(&mut config);
+    if let Some(path) = &args.plando {
+        config.plando = read_json::<Plando>(path)?;
+    }
+    if let Some(path) = &args.questions {
+        config.questions = read_json::<Vec<CustomQuestion>>(path)?;
+    }
+    if args.seed.is_some() {
+        config.seed = args.seed;
+    }
+
+    config.validate()?;
+
+    // checked now rather than after the whole randomization
+    let mut custom_music = vec![];
+    for custom in &config.custom_music {
+        let Ok(bytes) = std::fs::read(&custom.file) else {
+            return Err(format!("Can't read {}!", custom.file.display()).into());
+        };
+        match Midi::from_bytes(bytes) {
+            Ok(midi) => custom_music.push((custom.track, midi, custom.file.display().to_string())),
+            Err(e) => {
+                return Err(
+                    format!("{} isn't a valid MIDI file: {e}", custom.file.display()).into(),
+                );
+            }
+        }
+    }
+
+    let mut custom_textures = vec![];
+    for custom in &config.custom_textures {
+        let Ok(file) = File::open(&custom.file) else {
+            return Err(format!("Can't read {}!", custom.file.display()).into());
+        };
+        match Image::read_png(BufReader::new(file)) {
+            Ok(image) => custom_textures.push((custom.texture, image)),
+            Err(e) => {
+                return Err(
+                    format!("{} isn't a valid PNG file: {e}", custom.file.display()).into(),
+                );
+            }
+        }
+    }
+
+    let seed = config.seed.unwrap_or_else(rand::random);
+    let settings_hash = config.settings_hash()?;
+    println!("seed: {seed}");
+    println!("settings hash: {settings_hash}");
+
+    if is_same_directory(&args.game.input, &args.output) {
+        return Err("The output directory must be different from the input directory!".into());
+    }
+
+    let mut rando = Randomizer::new(&args.game.input, seed)?;
+
+    if rando.is_patched()? {
+        return Err("The input files have already been randomized, use the original ones!".into());
+    }
 
     if config.worlds {
         rando.shuffle_world_order(&config)?;
     }
 
+    if config.entrances {
+        rando.shuffle_maps(&config)?;
+    }
+
+    if config.notedoor_costs.is_some() {
+        rando.set_costs(&config)?;
+    }
+
     rando.fix_ttc_blue_egg();
     rando.remove_specific_actors()?;
     rando.change_randomizer_dialogues();
 
     if config.mix {
-        rando.shuffle_entities(&config.actors, &config.sprites);
+        rando.shuffle_entities(&config, &config.actors, &config.sprites)?;
     } else {
-        rando.shuffle_entities(&config.actors, &vec![]);
-        rando.shuffle_entities(&vec![], &config.sprites);
+        rando.shuffle_entities(&config, &config.actors, &[])?;
+        rando.shuffle_entities(&config, &[], &config.sprites)?;
     }
 
     if config.skip_furnace_fun {
@@ -97,29 +638,50 @@ fn main() -> Result<(), Box<dyn Error>> {
         rando.randomize_enemies();
     }
 
+    // once everything is in place
+    rando.set_hints(&config)?;
+    rando.set_questions(&config)?;
+
+    if config.music || config.jingles {
+        rando.shuffle_music(&config);
+    }
+    for (track, midi, name) in custom_music {
+        rando.set_music(track, midi, &name)?;
+    }
+    for (texture, image) in custom_textures {
+        rando.set_texture(texture, &image)?;
+    }
+
     rando.patch_code(&config)?;
 
+    // last chance to catch a seed that can't be finished
+    let report = rando.check_progression(&config)?;
+    if !report.is_ok() {
+        eprint!("{report}");
+        return Err("This seed can't be finished, nothing was written!".into());
+    }
+
+    // the title screen shows the seed, if the strings are there
+    let strings_path = args.game.input.join(STRINGS_FILENAME);
+    let strings = if strings_path.exists() {
+        let mut strings = Strings::new(&mut BufReader::new(File::open(&strings_path)?))?;
+        strings.stamp(seed, &settings_hash)?;
+
+        // encoded now so that a string that can't be written fails before anything is
+        let mut bytes = Cursor::new(vec![]);
+        strings.write(&mut bytes)?;
+        Some(bytes.into_inner())
+    } else {
+        println!("{STRINGS_FILENAME} not found, the seed won't be shown in game");
+        None
+    };
+
     println!("write everything");
-    rando.save()?;
-
-    // check the rando can read what it wrote
-    // let _rando = Randomizer::new()?;
-
-    // For some reason, doesn't work (strings point to the wrong offset)
-    // let mut reader = File::open("X360_strings.dat.bin")?;
-    // let mut strings = Strings::new(&mut reader)?;
-
-    // *strings.strings[167]
-    //     .translations
-    //     .get_mut(&Language::English)
-    //     .unwrap() = "RANDOMIZER BY MINIROP".to_owned();
-    // *strings.strings[167]
-    //     .translations
-    //     .get_mut(&Language::French)
-    //     .unwrap() = "RANDOMIZER PAR MINIROP".to_owned();
-
-    // let mut writer = File::create("X360_strings.dat")?;
-    // strings.write(&mut writer)?;
+    rando.save(&args.output)?;
+
+    if let Some(strings) = strings {
+        std::fs::write(args.output.join(STRINGS_FILENAME), strings)?;
+    }
 
     Ok(())
 }
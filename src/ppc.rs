@@ -0,0 +1,136 @@
+use byteorder::BigEndian;
+use byteorder::WriteBytesExt;
+use std::error::Error;
+use std::io::Write;
+
+// only what the custom code needs, encoded as the PowerPC manual says
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[repr(u32)]
+pub enum Register {
+    R0 = 0,
+    R1 = 1,
+    R3 = 3,
+    R4 = 4,
+    R5 = 5,
+    R12 = 12,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Instruction {
+    Addi(Register, Register, i16),
+    /// Absolute address of the target, the offset is computed when written.
+    B(u32),
+    /// Same as `B`, and sets the link register.
+    Bl(u32),
+    Blr,
+    Li(Register, u16),
+    Lwz(Register, Register, i16),
+    Mflr(Register),
+    Mtlr(Register),
+    Ori(Register, Register, u16),
+    Stw(Register, Register, i16),
+    Stwu(Register, Register, i16),
+}
+
+impl Instruction {
+    /// `address` is where the instruction is once loaded, only branches need it.
+    pub fn encode(&self, address: u32) -> u32 {
+        match *self {
+            Self::Addi(rd, ra, simm) => d_form(14, rd, ra, simm as u16),
+            Self::B(target) => branch(address, target),
+            Self::Bl(target) => branch(address, target) | 1,
+            Self::Blr => 0x4E800020,
+            // addi rd, 0, value
+            Self::Li(rd, value) => d_form(14, rd, Register::R0, value),
+            Self::Lwz(rd, ra, offset) => d_form(32, rd, ra, offset as u16),
+            Self::Mflr(rd) => 0x7C0802A6 | (rd as u32) << 21,
+            Self::Mtlr(rs) => 0x7C0803A6 | (rs as u32) << 21,
+            Self::Ori(ra, rs, value) => d_form(24, rs, ra, value),
+            Self::Stw(rs, ra, offset) => d_form(36, rs, ra, offset as u16),
+            Self::Stwu(rs, ra, offset) => d_form(37, rs, ra, offset as u16),
+        }
+    }
+
+    pub fn write<W: Write>(&self, writer: &mut W, address: u32) -> Result<(), Box<dyn Error>> {
+        writer.write_u32::<BigEndian>(self.encode(address))?;
+        Ok(())
+    }
+}
+
+/// Instructions following each other from `address`.
+pub struct Block {
+    address: u32,
+    instructions: Vec<Instruction>,
+}
+
+impl Block {
+    pub fn new(address: u32) -> Self {
+        Self {
+            address,
+            instructions: vec![],
+        }
+    }
+
+    pub fn add(&mut self, instruction: Instruction) {
+        self.instructions.push(instruction);
+    }
+
+    /// In bytes.
+    pub fn size(&self) -> usize {
+        self.instructions.len() * 4
+    }
+
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<(), Box<dyn Error>> {
+        for (i, instruction) in self.instructions.iter().enumerate() {
+            instruction.write(writer, self.address + 4 * i as u32)?;
+        }
+        Ok(())
+    }
+}
+
+// opcode, 2 registers and a 16 bits immediate
+fn d_form(opcode: u32, a: Register, b: Register, immediate: u16) -> u32 {
+    opcode << 26 | (a as u32) << 21 | (b as u32) << 16 | immediate as u32
+}
+
+fn branch(address: u32, target: u32) -> u32 {
+    let offset = target.wrapping_sub(address);
+    0x48000000 | (offset & 0x03FFFFFC)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodings() {
+        let mut block = Block::new(0x82440cf4);
+        block.add(Instruction::Mflr(Register::R12));
+        block.add(Instruction::Stw(Register::R12, Register::R1, -0x08));
+        block.add(Instruction::Stwu(Register::R1, Register::R1, -0x60));
+        block.add(Instruction::Li(Register::R3, 0xffff));
+        block.add(Instruction::Bl(0x82440cf4));
+        block.add(Instruction::Addi(Register::R1, Register::R1, 0x60));
+        block.add(Instruction::Lwz(Register::R12, Register::R1, -0x08));
+        block.add(Instruction::Mtlr(Register::R12));
+        block.add(Instruction::Ori(Register::R0, Register::R0, 0));
+        block.add(Instruction::Blr);
+
+        let mut bytes = vec![];
+        block.write(&mut bytes).unwrap();
+        let words = bytes
+            .chunks(4)
+            .map(|w| u32::from_be_bytes(w.try_into().unwrap()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            words,
+            [
+                0x7D8802A6, 0x9181FFF8, 0x9421FFA0, 0x3860FFFF,
+                // back to the start of the block
+                0x4BFFFFF1, 0x38210060, 0x8181FFF8, 0x7D8803A6, 0x60000000, 0x4E800020,
+            ]
+        );
+        assert_eq!(block.size(), bytes.len());
+    }
+}
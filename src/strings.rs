@@ -1,8 +1,13 @@
+use crate::assets::db360::XMEM_MAGIC;
 use crate::enums::Language;
+use crate::error::RandoError;
+use crate::error::expect_value;
 use crate::utils::convert_from_banjo_string;
 use crate::utils::convert_from_iso_8859_1;
 use crate::utils::convert_to_banjo_string;
 use crate::utils::convert_to_iso_8859_1;
+use crate::utils::convert_to_japanese_string;
+use byteorder::BigEndian;
 use byteorder::LittleEndian;
 use byteorder::ReadBytesExt;
 use byteorder::WriteBytesExt;
@@ -12,10 +17,13 @@ use std::io::Read;
 use std::io::Seek;
 use std::io::Write;
 
-enum StringId {}
+pub const STRINGS_FILENAME: &str = "X360_strings.dat";
 
 pub struct TranslatableString {
     pub translations: HashMap<Language, String>,
+    // what was read, written back as is while the translation hasn't changed
+    // (some characters have several codes, and Japanese can't always be decoded)
+    read: HashMap<Language, (String, Vec<u8>)>,
 }
 pub struct Strings {
     pub strings: Vec<TranslatableString>,
@@ -28,10 +36,38 @@ const LANGUAGE_MAPPING: [Language; 4] = [
     Language::Japanese,
 ];
 
+// shown on the title screen
+const TITLE_STRING: usize = 167;
+
 impl Strings {
     pub fn new<R: Read + Seek>(reader: &mut R) -> Result<Self, Box<dyn Error>> {
+        let magic = reader.read_u32::<BigEndian>()?;
+        if XMEM_MAGIC.contains(&magic) {
+            return Err(RandoError::Compressed {
+                name: STRINGS_FILENAME,
+            }
+            .into());
+        }
+        reader.seek_relative(-4)?;
+
         let number_of_strings = reader.read_u16::<LittleEndian>()? as usize;
         let number_of_languages = reader.read_u16::<LittleEndian>()? as usize;
+        if number_of_languages > LANGUAGE_MAPPING.len() {
+            return Err(RandoError::unexpected(
+                "language count",
+                format!("at most {}", LANGUAGE_MAPPING.len()),
+                number_of_languages,
+            )
+            .into());
+        }
+        if number_of_strings > IS_BANJO_STRING.len() {
+            return Err(RandoError::unexpected(
+                "string count",
+                format!("at most {}", IS_BANJO_STRING.len()),
+                number_of_strings,
+            )
+            .into());
+        }
 
         // sizes of languages
         for _ in 0..number_of_languages {
@@ -47,37 +83,44 @@ impl Strings {
             sizes_per_language.push(sizes);
         }
 
-        let mut strings = vec![];
+        let mut strings: Vec<_> = (0..number_of_strings)
+            .map(|_| TranslatableString {
+                translations: HashMap::new(),
+                read: HashMap::new(),
+            })
+            .collect();
+
         for lang in 0..number_of_languages {
             let language = LANGUAGE_MAPPING[lang];
 
             for str_id in 0..number_of_strings {
                 let len = sizes_per_language[lang][str_id];
+                if len == 0 {
+                    return Err(RandoError::unexpected("string size", "at least 1", 0).into());
+                }
 
                 let mut buffer = vec![0u8; len - 1];
-                reader.read(&mut buffer)?;
-                assert_eq!(reader.read_u8()?, 0);
+                reader.read_exact(&mut buffer)?;
+                expect_value("null terminator", 0, reader.read_u8()?)?;
 
                 let s = match language {
                     Language::English | Language::French | Language::German => {
                         if IS_BANJO_STRING[str_id] {
-                            convert_from_banjo_string(buffer)
+                            convert_from_banjo_string(buffer.clone())?
                         } else {
-                            convert_from_iso_8859_1(buffer)
+                            convert_from_iso_8859_1(buffer.clone())?
                         }
                     }
-                    Language::Japanese => "".into(),
+                    // the ones that can't be decoded are kept as they are
+                    Language::Japanese => {
+                        convert_from_banjo_string(buffer.clone()).unwrap_or_default()
+                    }
                     Language::Unknown(_) => unreachable!(),
                 };
 
-                if lang == 0 {
-                    let mut translations = HashMap::new();
-                    translations.insert(Language::English, s);
-                    strings.push(TranslatableString { translations });
-                } else {
-                    let data = strings.get_mut(str_id).unwrap();
-                    data.translations.insert(LANGUAGE_MAPPING[lang], s);
-                }
+                let string = &mut strings[str_id];
+                string.translations.insert(language, s.clone());
+                string.read.insert(language, (s, buffer));
             }
         }
 
@@ -87,58 +130,84 @@ impl Strings {
     pub fn write<W: Write + Seek>(&self, writer: &mut W) -> Result<(), Box<dyn Error>> {
         let number_of_strings = self.strings.len();
         let number_of_languages = self.strings[0].translations.len();
+
+        // the sizes are the ones of the encoded strings, not of the `String`s
+        let mut encoded = vec![];
+        for lang in &LANGUAGE_MAPPING[..number_of_languages] {
+            let mut strings = vec![];
+            for (str_id, string) in self.strings.iter().enumerate() {
+                strings.push(string.encode(str_id, *lang)?);
+            }
+            encoded.push(strings);
+        }
+
         writer.write_u16::<LittleEndian>(number_of_strings as u16)?;
         writer.write_u16::<LittleEndian>(number_of_languages as u16)?;
 
-        let sizes_pos = writer.seek(std::io::SeekFrom::Current(0))?;
-        for _ in 0..number_of_languages {
-            writer.write_u32::<LittleEndian>(0)?;
+        for strings in &encoded {
+            let size: usize = strings.iter().map(|s| s.len() + 1).sum();
+            writer.write_u32::<LittleEndian>(size as u32)?;
         }
 
-        for lang_id in 0..number_of_languages {
-            let lang = LANGUAGE_MAPPING[lang_id];
-            for string in &self.strings {
-                writer.write_u32::<LittleEndian>(string.translations[&lang].len() as u32 + 1)?;
+        for strings in &encoded {
+            for s in strings {
+                writer.write_u32::<LittleEndian>(s.len() as u32 + 1)?;
             }
         }
 
-        let mut sizes_by_languages = vec![];
-        for lang_id in 0..number_of_languages {
-            let lang = LANGUAGE_MAPPING[lang_id];
-
-            let begin = writer.seek(std::io::SeekFrom::Current(0))?;
-            for (str_id, string) in self.strings.iter().enumerate() {
-                match lang {
-                    Language::Japanese => {
-                        // use English until JP encoding has been understood
-                        writer.write(string.translations[&Language::English].as_bytes())?;
-                    }
-                    Language::English | Language::French | Language::German => {
-                        let buffer = if IS_BANJO_STRING[str_id] {
-                            convert_to_banjo_string(&string.translations[&lang])
-                        } else {
-                            convert_to_iso_8859_1(&string.translations[&lang])
-                        };
-
-                        writer.write(&buffer)?;
-                    }
-                    Language::Unknown(_) => unreachable!(),
-                }
+        for strings in &encoded {
+            for s in strings {
+                writer.write_all(s)?;
                 writer.write_u8(0)?;
             }
-            let end = writer.seek(std::io::SeekFrom::Current(0))?;
-            sizes_by_languages.push(end - begin);
         }
 
-        writer.seek(std::io::SeekFrom::Start(sizes_pos))?;
-        for i in 0..number_of_languages {
-            writer.write_u32::<LittleEndian>(sizes_by_languages[i] as u32)?;
+        Ok(())
+    }
+
+    /// Puts the seed and the settings hash on the title screen, in every language.
+    pub fn stamp(&mut self, seed: u64, settings_hash: &str) -> Result<(), Box<dyn Error>> {
+        let Some(title) = self.strings.get_mut(TITLE_STRING) else {
+            return Err(RandoError::unexpected(
+                "string count",
+                format!("at least {}", TITLE_STRING + 1),
+                self.strings.len(),
+            )
+            .into());
+        };
+
+        let text = format!("RANDOMIZER SEED: {seed} HASH: {settings_hash}");
+        for translation in title.translations.values_mut() {
+            *translation = text.clone();
         }
 
         Ok(())
     }
 }
 
+impl TranslatableString {
+    fn encode(&self, str_id: usize, lang: Language) -> Result<Vec<u8>, RandoError> {
+        let text = &self.translations[&lang];
+        if let Some((read, bytes)) = self.read.get(&lang)
+            && read == text
+        {
+            return Ok(bytes.clone());
+        }
+
+        match lang {
+            Language::English | Language::French | Language::German => {
+                if IS_BANJO_STRING[str_id] {
+                    convert_to_banjo_string(text)
+                } else {
+                    convert_to_iso_8859_1(text)
+                }
+            }
+            Language::Japanese => convert_to_japanese_string(text),
+            Language::Unknown(_) => unreachable!(),
+        }
+    }
+}
+
 #[rustfmt::skip]
 const IS_BANJO_STRING: [bool; 169] = [
 true,  true,  true,  true,  true,  true,  true,  true,  true,  true,
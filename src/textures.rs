@@ -0,0 +1,533 @@
+use crate::assets::db360::check_entry_count;
+use crate::enum_builder;
+use crate::error::RandoError;
+use byteorder::BigEndian;
+use byteorder::ReadBytesExt;
+use byteorder::WriteBytesExt;
+use std::error::Error;
+use std::io::Cursor;
+use std::io::Read;
+use std::io::Write;
+
+pub const TEXTURES_FILENAME: &str = "db360.textures.cmp";
+
+const ENTRY_COUNT: u32 = 6576;
+const RECORD_SIZE: usize = 20;
+
+// textures start on 4 KB boundaries on the 360
+const TEXTURE_ALIGNMENT: usize = 0x1000;
+
+enum_builder! {
+    #[repr(u8)]
+    // same values as the GPU's (GPUTEXTUREFORMAT)
+    pub enum TextureFormat {
+        L8       = 2,
+        A1R5G5B5 = 3,
+        R5G6B5   = 4,
+        A8R8G8B8 = 6,
+        A4R4G4B4 = 15,
+        Dxt1     = 18,
+        Dxt3     = 19,
+        Dxt5     = 20,
+    }
+}
+
+impl TextureFormat {
+    // width (and height) of a block in pixels, and its size in bytes
+    fn block(&self) -> Option<(u32, usize)> {
+        match self {
+            TextureFormat::L8 => Some((1, 1)),
+            TextureFormat::A1R5G5B5 | TextureFormat::R5G6B5 | TextureFormat::A4R4G4B4 => {
+                Some((1, 2))
+            }
+            TextureFormat::A8R8G8B8 => Some((1, 4)),
+            TextureFormat::Dxt1 => Some((4, 8)),
+            TextureFormat::Dxt3 | TextureFormat::Dxt5 => Some((4, 16)),
+            TextureFormat::Unknown(_) => None,
+        }
+    }
+}
+
+/// An entry of the table of db360.textures.cmp. Only the address is known for sure, the rest of
+/// the layout is a best guess.
+#[derive(Copy, Clone, Debug)]
+pub struct TextureRecord {
+    pub address: u32,
+    pub size: u32,
+    pub width: u16,
+    pub height: u16,
+    pub format: TextureFormat,
+    pub mips: u8,
+    pub unk_0e: u16,
+    pub unk_10: u32,
+}
+
+impl TextureRecord {
+    fn new<R: Read>(reader: &mut R) -> Result<Self, Box<dyn Error>> {
+        Ok(Self {
+            address: reader.read_u32::<BigEndian>()?,
+            size: reader.read_u32::<BigEndian>()?,
+            width: reader.read_u16::<BigEndian>()?,
+            height: reader.read_u16::<BigEndian>()?,
+            format: reader.read_u8()?.into(),
+            mips: reader.read_u8()?,
+            unk_0e: reader.read_u16::<BigEndian>()?,
+            unk_10: reader.read_u32::<BigEndian>()?,
+        })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<(), Box<dyn Error>> {
+        writer.write_u32::<BigEndian>(self.address)?;
+        writer.write_u32::<BigEndian>(self.size)?;
+        writer.write_u16::<BigEndian>(self.width)?;
+        writer.write_u16::<BigEndian>(self.height)?;
+        writer.write_u8(self.format.into())?;
+        writer.write_u8(self.mips)?;
+        writer.write_u16::<BigEndian>(self.unk_0e)?;
+        writer.write_u32::<BigEndian>(self.unk_10)?;
+        Ok(())
+    }
+}
+
+/// Pixels in RGBA, 4 bytes each, row after row.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+impl Image {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![0; (width * height * 4) as usize],
+        }
+    }
+
+    /// Any PNG, converted to RGBA.
+    pub fn read_png<R: Read>(reader: R) -> Result<Self, Box<dyn Error>> {
+        let mut decoder = png::Decoder::new(reader);
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+        let mut reader = decoder.read_info()?;
+        let mut buffer = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buffer)?;
+        buffer.truncate(info.buffer_size());
+
+        let pixels = match info.color_type {
+            png::ColorType::Rgba => buffer,
+            png::ColorType::Rgb => buffer
+                .chunks(3)
+                .flat_map(|p| [p[0], p[1], p[2], 0xFF])
+                .collect(),
+            png::ColorType::GrayscaleAlpha => buffer
+                .chunks(2)
+                .flat_map(|p| [p[0], p[0], p[0], p[1]])
+                .collect(),
+            png::ColorType::Grayscale => buffer.iter().flat_map(|&p| [p, p, p, 0xFF]).collect(),
+            // expanded by the transformations
+            png::ColorType::Indexed => {
+                return Err(RandoError::unexpected("PNG colors", "RGBA", "indexed").into());
+            }
+        };
+
+        Ok(Self {
+            width: info.width,
+            height: info.height,
+            pixels,
+        })
+    }
+
+    pub fn write_png<W: Write>(&self, writer: W) -> Result<(), Box<dyn Error>> {
+        let mut encoder = png::Encoder::new(writer, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&self.pixels)?;
+        Ok(())
+    }
+
+    /// Images side by side, from left to right, as high as the highest one.
+    pub fn sheet(images: &[Image]) -> Self {
+        let width = images.iter().map(|i| i.width).sum();
+        let height = images.iter().map(|i| i.height).max().unwrap_or(0);
+
+        let mut sheet = Self::new(width, height);
+        let mut left = 0;
+        for image in images {
+            sheet.paste(image, left, 0);
+            left += image.width;
+        }
+
+        sheet
+    }
+
+    /// Frames of an animation, `delay` is in hundredths of a second.
+    pub fn write_gif<W: Write>(
+        frames: &[Image],
+        writer: W,
+        delay: u16,
+    ) -> Result<(), Box<dyn Error>> {
+        let width = frames.iter().map(|i| i.width).max().unwrap_or(0);
+        let height = frames.iter().map(|i| i.height).max().unwrap_or(0);
+
+        let mut encoder = gif::Encoder::new(writer, width as u16, height as u16, &[])?;
+        encoder.set_repeat(gif::Repeat::Infinite)?;
+        for image in frames {
+            let mut pixels = image.pixels.clone();
+            let mut frame = gif::Frame::from_rgba_speed(
+                image.width as u16,
+                image.height as u16,
+                &mut pixels,
+                10,
+            );
+            frame.delay = delay;
+            frame.dispose = gif::DisposalMethod::Background;
+            encoder.write_frame(&frame)?;
+        }
+
+        Ok(())
+    }
+
+    /// The top left of the image.
+    pub fn crop(&self, width: u32, height: u32) -> Self {
+        let mut image = Self::new(width, height);
+        image.paste(self, 0, 0);
+        image
+    }
+
+    /// Draws `image` with its top left at `x`, `y`, what's outside is cut.
+    pub fn paste(&mut self, image: &Image, x: u32, y: u32) {
+        for j in 0..image.height {
+            for i in 0..image.width {
+                self.set_pixel(x + i, y + j, image.pixel(i, j));
+            }
+        }
+    }
+
+    /// Does nothing outside of the image.
+    pub fn set_pixel(&mut self, x: u32, y: u32, rgba: [u8; 4]) {
+        if x < self.width && y < self.height {
+            let i = ((y * self.width + x) * 4) as usize;
+            self.pixels[i..i + 4].copy_from_slice(&rgba);
+        }
+    }
+
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        let i = ((y * self.width + x) * 4) as usize;
+        [
+            self.pixels[i],
+            self.pixels[i + 1],
+            self.pixels[i + 2],
+            self.pixels[i + 3],
+        ]
+    }
+}
+
+pub struct Textures {
+    pub records: Vec<TextureRecord>,
+    // everything after the table, with the pixels added by `replace`
+    data: Vec<u8>,
+    // where the addresses start from, either the start of the file or the end of the table
+    base: usize,
+}
+
+impl Textures {
+    pub fn new(bytes: &[u8]) -> Result<Self, Box<dyn Error>> {
+        let mut reader = Cursor::new(bytes);
+        let entry_count = reader.read_u32::<BigEndian>()?;
+        check_entry_count(TEXTURES_FILENAME, ENTRY_COUNT, entry_count)?;
+
+        let mut records = vec![];
+        for _ in 0..entry_count {
+            records.push(TextureRecord::new(&mut reader)?);
+        }
+
+        let table_end = reader.position() as usize;
+        // the first texture is right after the table, wherever the addresses start from
+        let base = if records.iter().all(|r| r.address as usize >= table_end) {
+            0
+        } else {
+            table_end
+        };
+
+        Ok(Self {
+            records,
+            data: bytes[table_end..].to_vec(),
+            base,
+        })
+    }
+
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<(), Box<dyn Error>> {
+        writer.write_u32::<BigEndian>(self.records.len() as u32)?;
+        for record in &self.records {
+            record.write(writer)?;
+        }
+        writer.write_all(&self.data)?;
+        Ok(())
+    }
+
+    fn table_end(&self) -> usize {
+        4 + self.records.len() * RECORD_SIZE
+    }
+
+    /// The pixels of a texture, as the GPU reads them.
+    pub fn raw(&self, id: usize) -> Result<&[u8], Box<dyn Error>> {
+        let record = &self.records[id];
+        let start = (record.address as usize + self.base).wrapping_sub(self.table_end());
+        let end = start.wrapping_add(record.size as usize);
+
+        self.data.get(start..end).ok_or_else(|| {
+            RandoError::unexpected(
+                "texture address",
+                format!("within the {} bytes of data", self.data.len()),
+                format!("0x{:X} (0x{:X} bytes)", record.address, record.size),
+            )
+            .into()
+        })
+    }
+
+    /// Decodes the biggest mip of a texture.
+    pub fn decode(&self, id: usize) -> Result<Image, Box<dyn Error>> {
+        let Some(record) = self.records.get(id) else {
+            return Err(RandoError::unexpected(
+                "texture",
+                format!("less than {}", self.records.len()),
+                id,
+            )
+            .into());
+        };
+        let Some((block_size, block_bytes)) = record.format.block() else {
+            return Err(
+                RandoError::unexpected("texture format", "a known format", record.format).into(),
+            );
+        };
+
+        let raw = self.raw(id)?;
+        let width = record.width as u32;
+        let height = record.height as u32;
+        let blocks_x = width.div_ceil(block_size);
+        let blocks_y = height.div_ceil(block_size);
+        let pitch = blocks_x.next_multiple_of(32);
+
+        let mut image = Image::new(width, height);
+        for by in 0..blocks_y {
+            for bx in 0..blocks_x {
+                let offset = tiled_offset(bx, by, pitch, block_bytes) * block_bytes;
+                let Some(block) = raw.get(offset..offset + block_bytes) else {
+                    return Err(RandoError::unexpected(
+                        "texture size",
+                        format!("at least 0x{:X}", offset + block_bytes),
+                        format!("0x{:X}", raw.len()),
+                    )
+                    .into());
+                };
+
+                let texels = decode_block(record.format, block);
+                for (i, rgba) in texels.iter().enumerate() {
+                    let x = bx * block_size + i as u32 % block_size;
+                    let y = by * block_size + i as u32 / block_size;
+                    image.set_pixel(x, y, *rgba);
+                }
+            }
+        }
+
+        Ok(image)
+    }
+
+    /// Appends `image` (as A8R8G8B8, without mips) and points the texture at it.
+    pub fn replace(&mut self, id: usize, image: &Image) -> Result<(), Box<dyn Error>> {
+        let (Ok(width), Ok(height)) = (u16::try_from(image.width), u16::try_from(image.height))
+        else {
+            return Err(RandoError::unexpected(
+                "texture size",
+                "at most 65535x65535",
+                format!("{}x{}", image.width, image.height),
+            )
+            .into());
+        };
+        if width == 0 || height == 0 {
+            return Err(RandoError::unexpected("texture size", "at least 1x1", "0").into());
+        }
+
+        let pixels = encode_a8r8g8b8(image);
+
+        let table_end = self.table_end();
+        let padding = (table_end + self.data.len()).next_multiple_of(TEXTURE_ALIGNMENT)
+            - (table_end + self.data.len());
+        self.data.resize(self.data.len() + padding, 0);
+        let address = table_end + self.data.len() - self.base;
+        self.data.extend(&pixels);
+
+        let record = &mut self.records[id];
+        record.address = address as u32;
+        record.size = pixels.len() as u32;
+        record.width = width;
+        record.height = height;
+        record.format = TextureFormat::A8R8G8B8;
+        record.mips = 1;
+
+        Ok(())
+    }
+}
+
+// offset of a block in a tiled texture, in blocks (`pitch` is the width in blocks, rounded up to
+// 32), from the XGAddress2DTiledOffset formula
+fn tiled_offset(x: u32, y: u32, pitch: u32, block_bytes: usize) -> usize {
+    let log_bpp = block_bytes.trailing_zeros();
+    let (x, y, pitch) = (x as usize, y as usize, pitch as usize);
+
+    let macro_offset = ((x >> 5) + (y >> 5) * (pitch >> 5)) << (log_bpp + 7);
+    let micro_offset = ((x & 7) + ((y & 6) << 2)) << log_bpp;
+    let offset = macro_offset
+        + ((micro_offset & !0xF) << 1)
+        + (micro_offset & 0xF)
+        + ((y & 8) << (3 + log_bpp))
+        + ((y & 1) << 4);
+
+    (((offset & !0x1FF) << 3)
+        + ((offset & 0x1C0) << 2)
+        + (offset & 0x3F)
+        + ((y & 16) << 7)
+        + (((((y & 8) >> 2) + (x >> 3)) & 3) << 6))
+        >> log_bpp
+}
+
+// the texels of a block, row after row
+fn decode_block(format: TextureFormat, block: &[u8]) -> Vec<[u8; 4]> {
+    match format {
+        TextureFormat::L8 => vec![[block[0], block[0], block[0], 0xFF]],
+        TextureFormat::A1R5G5B5 => {
+            let v = u16::from_be_bytes([block[0], block[1]]);
+            let a = if v & 0x8000 != 0 { 0xFF } else { 0 };
+            vec![[expand(v >> 10, 5), expand(v >> 5, 5), expand(v, 5), a]]
+        }
+        TextureFormat::R5G6B5 => {
+            let v = u16::from_be_bytes([block[0], block[1]]);
+            let [r, g, b] = rgb565(v);
+            vec![[r, g, b, 0xFF]]
+        }
+        TextureFormat::A4R4G4B4 => {
+            let v = u16::from_be_bytes([block[0], block[1]]);
+            vec![[
+                expand(v >> 8, 4),
+                expand(v >> 4, 4),
+                expand(v, 4),
+                expand(v >> 12, 4),
+            ]]
+        }
+        TextureFormat::A8R8G8B8 => vec![[block[1], block[2], block[3], block[0]]],
+        TextureFormat::Dxt1 | TextureFormat::Dxt3 | TextureFormat::Dxt5 => {
+            // the 360 swaps the bytes of every u16
+            let block = block
+                .chunks(2)
+                .flat_map(|w| [w[1], w[0]])
+                .collect::<Vec<_>>();
+            decode_dxt(format, &block)
+        }
+        TextureFormat::Unknown(_) => vec![],
+    }
+}
+
+fn decode_dxt(format: TextureFormat, block: &[u8]) -> Vec<[u8; 4]> {
+    let (alphas, color) = match format {
+        TextureFormat::Dxt3 => {
+            let bits = u64::from_le_bytes(block[..8].try_into().unwrap());
+            let alphas = (0..16)
+                .map(|i| expand((bits >> (4 * i)) as u16, 4))
+                .collect::<Vec<_>>();
+            (Some(alphas), &block[8..])
+        }
+        TextureFormat::Dxt5 => {
+            let (a0, a1) = (block[0] as u32, block[1] as u32);
+            let mut palette = [a0, a1, 0, 0, 0, 0, 0, 0xFF];
+            if a0 > a1 {
+                for i in 1..7 {
+                    palette[i + 1] = ((7 - i as u32) * a0 + i as u32 * a1) / 7;
+                }
+            } else {
+                for i in 1..5 {
+                    palette[i + 1] = ((5 - i as u32) * a0 + i as u32 * a1) / 5;
+                }
+                palette[6] = 0;
+            }
+
+            let mut bits = [0u8; 8];
+            bits[..6].copy_from_slice(&block[2..8]);
+            let bits = u64::from_le_bytes(bits);
+            let alphas = (0..16)
+                .map(|i| palette[((bits >> (3 * i)) & 7) as usize] as u8)
+                .collect::<Vec<_>>();
+            (Some(alphas), &block[8..])
+        }
+        _ => (None, block),
+    };
+
+    let c0 = u16::from_le_bytes([color[0], color[1]]);
+    let c1 = u16::from_le_bytes([color[2], color[3]]);
+    let [r0, g0, b0] = rgb565(c0).map(|c| c as u32);
+    let [r1, g1, b1] = rgb565(c1).map(|c| c as u32);
+
+    let mut palette = [
+        [r0, g0, b0, 0xFF],
+        [r1, g1, b1, 0xFF],
+        [0, 0, 0, 0xFF],
+        [0, 0, 0, 0xFF],
+    ];
+    // only DXT1 has the 3 colors mode, with transparency
+    if c0 > c1 || alphas.is_some() {
+        palette[2] = [
+            (2 * r0 + r1) / 3,
+            (2 * g0 + g1) / 3,
+            (2 * b0 + b1) / 3,
+            0xFF,
+        ];
+        palette[3] = [
+            (r0 + 2 * r1) / 3,
+            (g0 + 2 * g1) / 3,
+            (b0 + 2 * b1) / 3,
+            0xFF,
+        ];
+    } else {
+        palette[2] = [(r0 + r1) / 2, (g0 + g1) / 2, (b0 + b1) / 2, 0xFF];
+        palette[3] = [0, 0, 0, 0];
+    }
+
+    let indices = u32::from_le_bytes(color[4..8].try_into().unwrap());
+    (0..16)
+        .map(|i| {
+            let [r, g, b, a] = palette[((indices >> (2 * i)) & 3) as usize].map(|c| c as u8);
+            match &alphas {
+                Some(alphas) => [r, g, b, alphas[i]],
+                None => [r, g, b, a],
+            }
+        })
+        .collect()
+}
+
+fn encode_a8r8g8b8(image: &Image) -> Vec<u8> {
+    let pitch = image.width.next_multiple_of(32);
+    let rows = image.height.next_multiple_of(32);
+    let mut pixels = vec![0u8; (pitch * rows * 4) as usize];
+
+    for y in 0..image.height {
+        for x in 0..image.width {
+            let [r, g, b, a] = image.pixel(x, y);
+            let offset = tiled_offset(x, y, pitch, 4) * 4;
+            pixels[offset..offset + 4].copy_from_slice(&[a, r, g, b]);
+        }
+    }
+
+    pixels
+}
+
+fn rgb565(v: u16) -> [u8; 3] {
+    [expand(v >> 11, 5), expand(v >> 5, 6), expand(v, 5)]
+}
+
+// the lowest `bits` bits of `v`, from 0 to 255
+fn expand(v: u16, bits: u32) -> u8 {
+    let max = (1 << bits) - 1;
+    ((v & max) as u32 * 255 / max as u32) as u8
+}
@@ -1,20 +1,24 @@
+use crate::error::RandoError;
+use crate::error::expect_value;
 use byteorder::BigEndian;
 use byteorder::ReadBytesExt;
 use byteorder::WriteBytesExt;
+use serde::Deserialize;
+use serde::Serialize;
 use std::error::Error;
 use std::io;
 use std::io::Read;
 use std::io::Seek;
 use std::io::Write;
 
-#[derive(Default, Copy, Clone, Debug, PartialEq)]
+#[derive(Default, Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Vector3<T> {
     pub x: T,
     pub y: T,
     pub z: T,
 }
 
-#[derive(Default, Copy, Clone, Debug, PartialEq)]
+#[derive(Default, Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Vector2<T> {
     pub x: T,
     pub y: T,
@@ -60,13 +64,6 @@ pub fn read_2_i16<R: Read>(reader: &mut R) -> Result<Vector2<i16>, Box<dyn Error
     Ok(Vector2 { x, y })
 }
 
-pub fn read_3_u8<R: Read>(reader: &mut R) -> Result<Vector3<u8>, Box<dyn Error>> {
-    let x = reader.read_u8()?;
-    let y = reader.read_u8()?;
-    let z = reader.read_u8()?;
-    Ok(Vector3 { x, y, z })
-}
-
 pub fn write_2_floats<W: Write>(writer: &mut W, vec: &Vector2<f32>) -> Result<(), Box<dyn Error>> {
     writer.write_f32::<BigEndian>(vec.x)?;
     writer.write_f32::<BigEndian>(vec.y)?;
@@ -100,21 +97,7 @@ pub fn write_2_i16<W: Write>(writer: &mut W, vec: &Vector2<i16>) -> Result<(), B
     Ok(())
 }
 
-pub fn write_3_u32<W: Write>(writer: &mut W, vec: &Vector3<u32>) -> Result<(), Box<dyn Error>> {
-    writer.write_u32::<BigEndian>(vec.x)?;
-    writer.write_u32::<BigEndian>(vec.y)?;
-    writer.write_u32::<BigEndian>(vec.z)?;
-    Ok(())
-}
-
-pub fn write_3_u8<W: Write>(writer: &mut W, vec: &Vector3<u8>) -> Result<(), Box<dyn Error>> {
-    writer.write_u8(vec.x)?;
-    writer.write_u8(vec.y)?;
-    writer.write_u8(vec.z)?;
-    Ok(())
-}
-
-pub fn fixed_to_float(fixed: u16) -> f32 {
+pub fn fixed_to_float(fixed: u16) -> Result<f32, RandoError> {
     let mut float = (fixed >> 6) as f32;
     let mut decimal = fixed & 0b111111;
 
@@ -128,9 +111,15 @@ pub fn fixed_to_float(fixed: u16) -> f32 {
     }
 
     let other_way = float_to_fixed(float);
-    assert_eq!(fixed, other_way);
+    if fixed != other_way {
+        return Err(RandoError::unexpected(
+            "fixed point value",
+            format!("0x{fixed:04X}"),
+            format!("0x{other_way:04X} once converted back"),
+        ));
+    }
 
-    float
+    Ok(float)
 }
 
 pub fn float_to_fixed(float: f32) -> u16 {
@@ -148,29 +137,13 @@ pub fn float_to_fixed(float: f32) -> u16 {
     fixed
 }
 
-pub fn align_reader<R: Read + Seek>(reader: &mut R) -> Result<(), Box<dyn Error>> {
-    let pos = reader.seek(std::io::SeekFrom::Current(0))?;
-    match pos % 8 {
-        0 => {}
-        4 => {
-            reader.read_u32::<BigEndian>()?;
-        }
-        _ => {
-            println!("align_reader: {pos:X} -> {}", pos % 8);
-            unreachable!();
-        }
-    }
-
-    Ok(())
-}
-
 pub fn align_writer<W: Write + Seek>(writer: &mut W) -> Result<(), Box<dyn Error>> {
-    let pos = writer.seek(std::io::SeekFrom::Current(0))?;
+    let pos = writer.stream_position()?;
     let modulo = (pos % 8) as usize;
     if modulo != 0 {
         let mut buffer = vec![0; 8 - modulo];
         io::repeat(0xCD).read_exact(&mut buffer).unwrap();
-        writer.write(&buffer)?;
+        writer.write_all(&buffer)?;
     }
 
     Ok(())
@@ -213,9 +186,9 @@ macro_rules! enum_builder {
             }
         }
 
-        impl Into<$typ> for $name {
-            fn into(self) -> $typ {
-                match self {
+        impl From<$name> for $typ {
+            fn from(value: $name) -> Self {
+                match value {
                     $($name::$arm => $val ,)*
                     $name::Unknown(v) => v,
                 }
@@ -227,41 +200,50 @@ macro_rules! enum_builder {
 pub fn read_string<R: Read>(reader: &mut R) -> Result<String, Box<dyn Error>> {
     let len = reader.read_u8()?;
     if len == 1 {
-        assert_eq!(reader.read_u8()?, 0);
+        expect_value("null terminator", 0, reader.read_u8()?)?;
         return Ok("".into());
     }
 
     let mut buffer = vec![0u8; len as usize - 1];
-    reader.read(&mut buffer)?;
-    assert_eq!(reader.read_u8()?, 0);
+    reader.read_exact(&mut buffer)?;
+    expect_value("null terminator", 0, reader.read_u8()?)?;
 
-    let s = convert_from_banjo_string(buffer);
+    let s = convert_from_banjo_string(buffer)?;
     Ok(s)
 }
 
 pub fn write_string<W: Write>(writer: &mut W, string: &str) -> Result<(), Box<dyn Error>> {
-    let buffer = convert_to_banjo_string(string);
+    let buffer = convert_to_banjo_string(string)?;
     writer.write_u8(1 + buffer.len() as u8)?;
-    if buffer.len() > 0 {
-        writer.write(&buffer)?;
+    if !buffer.is_empty() {
+        writer.write_all(&buffer)?;
     }
     writer.write_u8(0)?;
 
     Ok(())
 }
 
-pub fn convert_to_banjo_string(string: &str) -> Vec<u8> {
-    let mut buffer = vec![0xFD, 0x6A];
-    let mut mapping = JAPANESE_CHARACTERS;
-
+pub fn convert_to_banjo_string(string: &str) -> Result<Vec<u8>, RandoError> {
     if string.chars().all(|c| match c {
         '⸾' | '⸽' => true,
         _ => CHARACTERS.contains(&c),
     }) {
-        mapping = CHARACTERS;
-        buffer.clear();
+        encode_characters(string, &CHARACTERS, vec![])
+    } else {
+        convert_to_japanese_string(string)
     }
+}
+
+// the Japanese strings always start with the switch to the Japanese characters
+pub fn convert_to_japanese_string(string: &str) -> Result<Vec<u8>, RandoError> {
+    encode_characters(string, &JAPANESE_CHARACTERS, vec![0xFD, 0x6A])
+}
 
+fn encode_characters(
+    string: &str,
+    mapping: &[char; 256],
+    mut buffer: Vec<u8>,
+) -> Result<Vec<u8>, RandoError> {
     for c in string.chars() {
         if c == '⸾' {
             buffer.push(0xFD);
@@ -269,19 +251,21 @@ pub fn convert_to_banjo_string(string: &str) -> Vec<u8> {
         } else if c == '⸽' {
             buffer.push(0xFD);
             buffer.push(0x6C);
+        } else if let Some(index) = mapping.iter().position(|k| *k == c) {
+            buffer.push(index as u8);
         } else {
-            if let Some(index) = mapping.iter().position(|k| *k == c) {
-                buffer.push(index as u8);
-            } else {
-                unreachable!();
-            }
+            return Err(RandoError::unexpected(
+                "a character the game can display",
+                string,
+                format!("'{c}'"),
+            ));
         }
     }
 
-    buffer
+    Ok(buffer)
 }
 
-pub fn convert_from_banjo_string(buffer: Vec<u8>) -> String {
+pub fn convert_from_banjo_string(buffer: Vec<u8>) -> Result<String, RandoError> {
     let mut index = 0;
     let mut japanese = false;
     let mut target_buffer = vec![];
@@ -291,7 +275,13 @@ pub fn convert_from_banjo_string(buffer: Vec<u8>) -> String {
 
         // control character
         if c == 0xFD {
-            let code = buffer[index];
+            let Some(&code) = buffer.get(index) else {
+                return Err(RandoError::unexpected(
+                    "control character after 0xFD",
+                    "0x68, 0x6A or 0x6C",
+                    "the end of the string",
+                ));
+            };
             index += 1;
 
             match code {
@@ -304,63 +294,91 @@ pub fn convert_from_banjo_string(buffer: Vec<u8>) -> String {
                     /*wiggle stop?*/
                     target_buffer.push('⸽');
                 }
-                _ => panic!("Unknown control character {code:X}"),
+                _ => {
+                    return Err(RandoError::unexpected(
+                        "control character",
+                        "0x68, 0x6A or 0x6C",
+                        format!("0x{code:X}"),
+                    ));
+                }
             }
         } else if japanese {
             let target = JAPANESE_CHARACTERS[c as usize];
             target_buffer.push(target);
         } else {
             let target = CHARACTERS[c as usize];
-            assert_ne!(target, '_');
+            if target == '_' {
+                return Err(RandoError::unexpected(
+                    "character",
+                    "a displayable one",
+                    format!("0x{c:X}"),
+                ));
+            }
             target_buffer.push(target);
         }
     }
 
-    target_buffer.iter().collect::<String>()
+    Ok(target_buffer.iter().collect::<String>())
 }
 
-pub fn convert_to_iso_8859_1(string: &String) -> Vec<u8> {
+pub fn convert_to_iso_8859_1(string: &String) -> Result<Vec<u8>, RandoError> {
     string
         .chars()
-        .map(|b| match b {
-            '\n' => b'\n',
-            ' '..='~' => b as u8,
-            '\u{00a0}' => b as u8, // NBSP
-            'É' => 0xC9,
-            'Ü' => 0xDC,
-            'ß' => 0xDF,
-            'à' => 0xE0,
-            'ä' => 0xE4,
-            'è' => 0xE8,
-            'é' => 0xE9,
-            'ê' => 0xEA,
-            'ö' => 0xF6,
-            'ü' => 0xFC,
-            _ => panic!("unhandled char '{b}'"),
+        .map(|b| {
+            Ok(match b {
+                '\n' => b'\n',
+                ' '..='~' => b as u8,
+                '\u{00a0}' => b as u8, // NBSP
+                'É' => 0xC9,
+                'Ü' => 0xDC,
+                'ß' => 0xDF,
+                'à' => 0xE0,
+                'ä' => 0xE4,
+                'è' => 0xE8,
+                'é' => 0xE9,
+                'ê' => 0xEA,
+                'ö' => 0xF6,
+                'ü' => 0xFC,
+                _ => {
+                    return Err(RandoError::unexpected(
+                        "a character the game can display",
+                        string,
+                        format!("'{b}'"),
+                    ));
+                }
+            })
         })
-        .collect::<Vec<_>>()
+        .collect()
 }
 
-pub fn convert_from_iso_8859_1(buffer: Vec<u8>) -> String {
+pub fn convert_from_iso_8859_1(buffer: Vec<u8>) -> Result<String, RandoError> {
     buffer
         .iter()
-        .map(|b| match b {
-            0x0A => '\n',
-            0x20..=0x7E => *b as char,
-            0xA0 => *b as char, // NBSP
-            0xC9 => 'É',
-            0xDC => 'Ü',
-            0xDF => 'ß',
-            0xE0 => 'à',
-            0xE4 => 'ä',
-            0xE8 => 'è',
-            0xE9 => 'é',
-            0xEA => 'ê',
-            0xF6 => 'ö',
-            0xFC => 'ü',
-            _ => panic!("unhandled byte {b:X}"),
+        .map(|b| {
+            Ok(match b {
+                0x0A => '\n',
+                0x20..=0x7E => *b as char,
+                0xA0 => *b as char, // NBSP
+                0xC9 => 'É',
+                0xDC => 'Ü',
+                0xDF => 'ß',
+                0xE0 => 'à',
+                0xE4 => 'ä',
+                0xE8 => 'è',
+                0xE9 => 'é',
+                0xEA => 'ê',
+                0xF6 => 'ö',
+                0xFC => 'ü',
+                _ => {
+                    return Err(RandoError::unexpected(
+                        "ISO-8859-1 character",
+                        "a displayable one",
+                        format!("0x{b:X}"),
+                    ));
+                }
+            })
         })
-        .collect::<String>()
+        .collect()
 }
 
 #[rustfmt::skip]
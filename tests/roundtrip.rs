@@ -0,0 +1,1007 @@
+// every format is read from a hand-made fixture and written back, it must come out byte for byte
+// (also after going through the JSON of `dump`, for the ones that have it)
+
+use bk_rando::Config;
+use bk_rando::CustomMusic;
+use bk_rando::Randomizer;
+use bk_rando::assets::animation::Animation;
+use bk_rando::assets::db360::ASSETS;
+use bk_rando::assets::db360::verify_roundtrip;
+use bk_rando::assets::dialogue::Dialogue;
+use bk_rando::assets::map_setup::MapSetup;
+use bk_rando::assets::midi::Midi;
+use bk_rando::assets::model::DisplayList;
+use bk_rando::assets::model::GeometryLayout;
+use bk_rando::assets::model::Model;
+use bk_rando::assets::model::SectionData;
+use bk_rando::assets::model::SectionId;
+use bk_rando::assets::n64_sprite::N64Sprite;
+use bk_rando::assets::obj::Obj;
+use bk_rando::assets::question::Question;
+use bk_rando::assets::sprite::Sprite;
+use bk_rando::enums::AssetId;
+use bk_rando::enums::Language;
+use bk_rando::enums::midis::MidiId;
+use bk_rando::strings::Strings;
+use bk_rando::textures::Image;
+use bk_rando::textures::Textures;
+use std::io::Cursor;
+
+macro_rules! assert_roundtrip {
+    ($format:ty, $bytes:expr) => {{
+        let bytes: Vec<u8> = $bytes;
+        let mut reader = Cursor::new(&bytes[..]);
+        let asset = <$format>::new(&mut reader).unwrap();
+        assert_eq!(
+            reader.position() as usize,
+            bytes.len(),
+            "the fixture isn't read entirely"
+        );
+
+        let mut writer = Cursor::new(vec![]);
+        asset.write(&mut writer).unwrap();
+        assert_eq!(writer.into_inner(), bytes);
+    }};
+}
+
+macro_rules! assert_json_roundtrip {
+    ($format:ty, $bytes:expr) => {{
+        let bytes: Vec<u8> = $bytes;
+        let asset = <$format>::new(&mut Cursor::new(&bytes[..])).unwrap();
+        let json = serde_json::to_string_pretty(&asset).unwrap();
+        let asset: $format = serde_json::from_str(&json).unwrap();
+
+        let mut writer = Cursor::new(vec![]);
+        asset.write(&mut writer).unwrap();
+        assert_eq!(writer.into_inner(), bytes);
+    }};
+}
+
+/// Big endian unless said otherwise, like the game.
+#[derive(Default)]
+struct Fixture(Vec<u8>);
+
+impl Fixture {
+    fn u8(mut self, v: u8) -> Self {
+        self.0.push(v);
+        self
+    }
+
+    fn u16(mut self, v: u16) -> Self {
+        self.0.extend(v.to_be_bytes());
+        self
+    }
+
+    fn u16_le(mut self, v: u16) -> Self {
+        self.0.extend(v.to_le_bytes());
+        self
+    }
+
+    fn u32(mut self, v: u32) -> Self {
+        self.0.extend(v.to_be_bytes());
+        self
+    }
+
+    fn u32_le(mut self, v: u32) -> Self {
+        self.0.extend(v.to_le_bytes());
+        self
+    }
+
+    fn i16(mut self, v: i16) -> Self {
+        self.0.extend(v.to_be_bytes());
+        self
+    }
+
+    fn i32(mut self, v: i32) -> Self {
+        self.0.extend(v.to_be_bytes());
+        self
+    }
+
+    fn f32(mut self, v: f32) -> Self {
+        self.0.extend(v.to_be_bytes());
+        self
+    }
+
+    fn bytes(mut self, v: &[u8]) -> Self {
+        self.0.extend(v);
+        self
+    }
+
+    /// Length (with the null terminator), text, null terminator.
+    fn string(self, text: &[u8]) -> Self {
+        self.u8(text.len() as u8 + 1).bytes(text).u8(0)
+    }
+
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+}
+
+// "バンジョー" with the Japanese character set
+const BANJO_JP: &[u8] = &[0xFD, 0x6A, 0x8B, 0x4F, 0x82, 0x4D, 0x3B];
+
+#[test]
+fn animation() {
+    let bytes = Fixture::default()
+        // start frame, end frame, transforms, padding
+        .u16(1)
+        .u16(40)
+        .u16(2)
+        .u16(0)
+        // bone 3, YRotation
+        .u16(0x31)
+        .u16(2)
+        .u16(0x0001)
+        .u16(0x0040) // 1.0
+        .u16(0xC028) // both unknown bits, frame 40
+        .u16(0x16A0) // 90.5
+        // bone 5, YTranslation
+        .u16(0x57)
+        .u16(1)
+        .u16(0x0014)
+        .u16(0x0003)
+        .0;
+
+    assert_roundtrip!(Animation, bytes);
+}
+
+#[test]
+fn dialogue() {
+    let languages = [
+        Fixture::default()
+            // bottom
+            .u8(3)
+            .u8(0x80) // Banjo
+            .string(b"HELLO, MUMBO!")
+            .u8(0x07) // Trigger
+            .bytes(&[2, 5, 0])
+            .u8(0x04) // EndOfSection
+            .string(b"")
+            // top
+            .u8(6)
+            .u8(0x01)
+            .string(b"")
+            .u8(0x02)
+            .string(b"")
+            .u8(0x03)
+            .string(b"")
+            .u8(0x06)
+            .string(b"")
+            .u8(0x09)
+            .string(b"")
+            .u8(0x08) // Selection
+            .string(b"YES"),
+        Fixture::default()
+            .u8(1)
+            .u8(0x84) // Mumbo
+            .string(&[b'S', b'A', b'L', b'U', b'T', b' ', 0x62]) // 0x62 is É
+            .u8(0),
+        Fixture::default()
+            .u8(0)
+            .u8(1)
+            .u8(0xDB) // Lockup
+            .string(b"HALLO"),
+        Fixture::default().u8(1).u8(0x80).string(BANJO_JP).u8(0),
+    ];
+
+    // offsets are from the start of the dialogue, in little endian
+    let mut fixture = Fixture::default().u8(4);
+    let mut offset = 1 + 2 * languages.len();
+    for lang in &languages {
+        fixture = fixture.u16_le(offset as u16);
+        offset += lang.len();
+    }
+    for lang in languages {
+        fixture = fixture.bytes(&lang.0);
+    }
+
+    assert_roundtrip!(Dialogue, fixture.0.clone());
+    assert_json_roundtrip!(Dialogue, fixture.0);
+}
+
+#[test]
+fn question() {
+    let languages = [
+        Fixture::default()
+            .u8(5)
+            .u8(0x80)
+            .string(b"WHAT IS THE NAME")
+            .u8(0x80)
+            .string(b"OF BANJO'S SISTER?")
+            .u8(0x81)
+            .string(b"TOOTY")
+            .u8(0x82)
+            .string(b"BOTTLES")
+            .u8(0x83)
+            .string(b"MUMBO"),
+        Fixture::default()
+            .u8(4)
+            .u8(0x80)
+            .string(b"QUESTION?")
+            .u8(0x81)
+            .string(b"A")
+            .u8(0x82)
+            .string(b"B")
+            .u8(0x83)
+            .string(b"C"),
+        Fixture::default().u8(0),
+        Fixture::default()
+            .u8(4)
+            .u8(0x80)
+            .string(BANJO_JP)
+            .u8(0x81)
+            .string(b"1")
+            .u8(0x82)
+            .string(b"2")
+            .u8(0x83)
+            .string(b"3"),
+    ];
+
+    let fixture = question_fixture(languages);
+    assert_roundtrip!(Question, fixture.clone());
+    assert_json_roundtrip!(Question, fixture);
+}
+
+fn question_fixture(languages: [Fixture; 4]) -> Vec<u8> {
+    // offsets are from the end of the offsets, in big endian
+    let mut fixture = Fixture::default().u8(4).u16(0x0201);
+    let mut offset = 0;
+    for lang in &languages {
+        fixture = fixture.u16(offset as u16);
+        offset += lang.len();
+    }
+    for lang in languages {
+        fixture = fixture.bytes(&lang.0);
+    }
+
+    fixture.0
+}
+
+fn simple_question(question: &[u8], answers: [&[u8]; 3]) -> Fixture {
+    Fixture::default()
+        .u8(4)
+        .u8(0x80)
+        .string(question)
+        .u8(0x81)
+        .string(answers[0])
+        .u8(0x82)
+        .string(answers[1])
+        .u8(0x83)
+        .string(answers[2])
+}
+
+#[test]
+fn edited_question() {
+    let bytes = question_fixture([
+        simple_question(b"QUESTION?", [b"A", b"B", b"C"]),
+        simple_question(BANJO_JP, [b"1", b"2", b"3"]),
+        simple_question(b"QUESTION?", [b"A", b"B", b"C"]),
+        simple_question(b"FRAGE?", [b"A", b"B", b"C"]),
+    ]);
+    let mut question = Question::new(&mut Cursor::new(&bytes[..])).unwrap();
+
+    let french = question.translations.get_mut(&Language::French).unwrap();
+    french.question = vec!["ÉCOLE?".into()];
+    french.answer_1 = vec!["OUI".into()];
+
+    let mut writer = Cursor::new(vec![]);
+    question.write(&mut writer).unwrap();
+
+    let expected = question_fixture([
+        simple_question(b"QUESTION?", [b"A", b"B", b"C"]),
+        simple_question(BANJO_JP, [b"1", b"2", b"3"]),
+        simple_question(&[0x62, b'C', b'O', b'L', b'E', b'?'], [b"OUI", b"B", b"C"]),
+        simple_question(b"FRAGE?", [b"A", b"B", b"C"]),
+    ]);
+    assert_eq!(writer.into_inner(), expected);
+}
+
+#[test]
+fn midi() {
+    // one note, then "end of track"
+    let track = [
+        0x00, 0x90, 0x3C, 0x40, 0x60, 0x80, 0x3C, 0x00, 0x00, 0xFF, 0x2F, 0x00,
+    ];
+    let file = Fixture::default()
+        .bytes(b"MThd")
+        .u32(6)
+        .u16(0)
+        .u16(1)
+        .u16(96)
+        .bytes(b"MTrk")
+        .u32(track.len() as u32)
+        .bytes(&track);
+
+    let midi = Midi::from_bytes(file.0.clone()).unwrap();
+    let mut writer = Cursor::new(vec![]);
+    midi.write(&mut writer).unwrap();
+    assert_eq!(writer.into_inner(), file.0);
+
+    // cut in the middle of the track
+    assert!(Midi::from_bytes(file.0[..file.len() - 4].to_vec()).is_err());
+    // not a MIDI file
+    assert!(Midi::from_bytes(b"RIFF\0\0\0\0WAVE".to_vec()).is_err());
+}
+
+fn sprite_frame(fixture: Fixture, texture_id: u16, width: u16, height: u16) -> Fixture {
+    fixture
+        .u16(0)
+        .u16(0)
+        .u16(width)
+        .u16(height)
+        .u16(0x0100)
+        .u16(texture_id)
+        .i16(-16)
+        .i16(-32)
+        .i16(16)
+        .i16(0)
+        .u16(0)
+        .u16(0)
+        .u16(width) // same as unk04
+        .u16(height) // same as unk06
+}
+
+#[test]
+fn sprite() {
+    let mut fixture = Fixture::default()
+        .u16(2)
+        .u16(0x1000)
+        .u16(32)
+        .u16(64)
+        .u16(1)
+        .u16(2)
+        .u16(3)
+        .u16(4)
+        // offsets
+        .u32(0)
+        .u32(0x1C);
+    fixture = sprite_frame(fixture, 0x0123, 32, 64);
+    fixture = sprite_frame(fixture, 0x0124, 16, 16);
+
+    assert_roundtrip!(Sprite, fixture.0);
+}
+
+fn prop_1(fixture: Fixture, category: u16, id: u16) -> Fixture {
+    fixture
+        .i16(-1200)
+        .i16(300)
+        .i16(4500)
+        // selector/radius, category, unknown bit
+        .u16((500 << 7) | (category << 1) | 1)
+        .u16(id)
+        .u8(0x12)
+        .u8(0x34)
+        .u32(0x0040_0000)
+        .u32(0x8000_0001)
+}
+
+#[test]
+fn map_setup() {
+    let mut fixture = Fixture::default()
+        .u16(0x0101)
+        // x from -1 to 0, y 0, z from 0 to 1
+        .i32(-1)
+        .i32(0)
+        .i32(0)
+        .i32(0)
+        .i32(0)
+        .i32(1)
+        // missing cube
+        .u8(1)
+        // empty cube
+        .bytes(&[3, 10, 0, 8, 0, 1])
+        // 2 props 1 and 2 props 2
+        .bytes(&[3, 10, 2, 11]);
+    fixture = prop_1(fixture, 6, 0x0046); // Actor
+    fixture = prop_1(fixture, 3, 0x0001); // WarpOrTrigger
+    fixture = fixture
+        .bytes(&[8, 2, 9])
+        // sprite, the id is in the top 12 bits of the flags
+        .u32((0x6D6 << 20) | 0x12345)
+        .i16(-10)
+        .i16(20)
+        .i16(-30)
+        .u16(0x1F00)
+        // actor, scale 100
+        .u32(0x00C8_0011)
+        .i16(1000)
+        .i16(0)
+        .i16(-1000)
+        .u16(0x6402)
+        .u8(1)
+        // props 2 only
+        .bytes(&[3, 10, 0, 8, 1, 9])
+        .u32(0x6D6 << 20)
+        .i16(0)
+        .i16(0)
+        .i16(0)
+        .u16(0)
+        .u8(1)
+        // end of cubes, cameras
+        .u8(0)
+        .u8(3)
+        // empty
+        .u8(1)
+        .u16(1)
+        .bytes(&[2, 0])
+        // pivot
+        .u8(1)
+        .u16(2)
+        .bytes(&[2, 1])
+        .u8(1)
+        .f32(100.0)
+        .f32(-200.5)
+        .f32(300.25)
+        .u8(2)
+        .f32(1.5)
+        .f32(2.5)
+        .u8(3)
+        .f32(90.0)
+        .f32(0.75)
+        .u8(4)
+        .f32(10.0)
+        .f32(20.0)
+        .f32(30.0)
+        .u8(5)
+        .u32(0xDEADBEEF)
+        .u8(0)
+        // static
+        .u8(1)
+        .u16(3)
+        .bytes(&[2, 2])
+        .u8(1)
+        .f32(-1.0)
+        .f32(-2.0)
+        .f32(-3.0)
+        .u8(2)
+        .f32(45.0)
+        .f32(0.0)
+        .f32(180.0)
+        .u8(0)
+        // zoom
+        .u8(1)
+        .u16(4)
+        .bytes(&[2, 3])
+        .u8(1)
+        .f32(1.0)
+        .f32(2.0)
+        .f32(3.0)
+        .u8(2)
+        .f32(4.0)
+        .f32(5.0)
+        .u8(3)
+        .f32(6.0)
+        .f32(7.0)
+        .u8(4)
+        .f32(8.0)
+        .f32(9.0)
+        .f32(10.0)
+        .u8(5)
+        .u32(1)
+        .u8(6)
+        .f32(500.0)
+        .f32(1500.0)
+        .u8(0)
+        // random
+        .u8(1)
+        .u16(5)
+        .bytes(&[2, 4])
+        .u8(1)
+        .u32(3)
+        .u8(0)
+        // end of cameras, lightings
+        .u8(0)
+        .u8(4)
+        .u8(1)
+        .u8(2)
+        .f32(0.0)
+        .f32(512.0)
+        .f32(-512.0)
+        .u8(3)
+        .f32(100.0)
+        .f32(800.0)
+        .u8(4)
+        .u32(255)
+        .u32(128)
+        .u32(0)
+        // end of lightings, end of map setup
+        .u8(0)
+        .u8(0);
+
+    assert_roundtrip!(MapSetup, fixture.0.clone());
+    assert_json_roundtrip!(MapSetup, fixture.0);
+}
+
+// [language][string], in the game's character sets
+fn strings_fixture(strings: &[[&[u8]; 2]; 4]) -> Vec<u8> {
+    let mut fixture = Fixture::default().u16_le(2).u16_le(4);
+    for lang in strings {
+        let total: usize = lang.iter().map(|s| s.len() + 1).sum();
+        fixture = fixture.u32_le(total as u32);
+    }
+    for lang in strings {
+        for s in lang {
+            fixture = fixture.u32_le(s.len() as u32 + 1);
+        }
+    }
+    for lang in strings {
+        for s in lang {
+            fixture = fixture.bytes(s).u8(0);
+        }
+    }
+
+    fixture.0
+}
+
+fn n64_frame(fixture: Fixture, chunks: u16) -> Fixture {
+    let mut fixture = fixture
+        .i16(0)
+        .i16(0)
+        .u16(2)
+        .u16(2)
+        .u16(chunks)
+        .bytes(&[0; 10])
+        // the palette starts on 8 bytes
+        .u32(0)
+        // red and green
+        .u16(0xF801)
+        .u16(0x07C1);
+    fixture = fixture.bytes(&[0; 28]);
+    if chunks > 0 {
+        fixture = fixture.i16(0).i16(0).u16(2).u16(2).bytes(&[0x01, 0x10]);
+    }
+    fixture
+}
+
+#[test]
+fn n64_sprite() {
+    let mut fixture = Fixture::default()
+        .u16(2)
+        .u16(0x0001) // CI4
+        .bytes(&[0; 12])
+        .u32(0)
+        .u32(0x48);
+    fixture = n64_frame(fixture, 1);
+    // the second frame starts on 8 bytes
+    fixture = n64_frame(fixture.bytes(&[0; 6]), 0);
+    // zeros, then the alignment of db360.cmp
+    fixture = fixture.u16(0);
+    let bytes = fixture.0.clone();
+    fixture = fixture.bytes(&[0xCD; 4]);
+
+    let sprite = N64Sprite::new(&mut &fixture.0[..], fixture.len()).unwrap();
+    let mut writer = Cursor::new(vec![]);
+    sprite.write(&mut writer).unwrap();
+    assert_eq!(writer.into_inner(), bytes);
+
+    let red = [0xFF, 0, 0, 0xFF];
+    let green = [0, 0xFF, 0, 0xFF];
+    let image = sprite.frame_image(0);
+    assert_eq!(image.pixels, [red, green, green, red].concat());
+
+    // anything else after the frames isn't a sprite of this kind
+    let mut longer = bytes.clone();
+    longer.extend([1; 8]);
+    assert!(N64Sprite::new(&mut &longer[..], longer.len()).is_err());
+}
+
+#[test]
+fn strings() {
+    let bytes = strings_fixture(&[
+        [b"HELLO", b"BANJO"],
+        [b"SALUT", &[0x62]], // É
+        [b"HALLO", b"KAZOOIE"],
+        [BANJO_JP, b""],
+    ]);
+
+    assert_roundtrip!(Strings, bytes);
+}
+
+#[test]
+fn edited_strings() {
+    let bytes = strings_fixture(&[
+        [b"HELLO", b"BANJO"],
+        [b"SALUT", b"BANJO"],
+        [b"HALLO", b"BANJO"],
+        [BANJO_JP, b""],
+    ]);
+    let mut strings = Strings::new(&mut Cursor::new(&bytes[..])).unwrap();
+
+    // the sizes must be the ones of the encoded strings, not of the UTF-8 ones
+    let translations = &mut strings.strings[1].translations;
+    translations.insert(Language::French, "ÉCOLE".into());
+    translations.insert(Language::Japanese, "バンジョー".into());
+
+    let mut writer = Cursor::new(vec![]);
+    strings.write(&mut writer).unwrap();
+
+    let expected = strings_fixture(&[
+        [b"HELLO", b"BANJO"],
+        [b"SALUT", &[0x62, b'C', b'O', b'L', b'E']],
+        [b"HALLO", b"BANJO"],
+        [BANJO_JP, BANJO_JP],
+    ]);
+    assert_eq!(writer.into_inner(), expected);
+}
+
+// a red DXT1 texture (4x4) for the first record, the others are empty
+fn textures_fixture() -> Vec<u8> {
+    let mut fixture = Fixture::default()
+        .u32(6576)
+        .u32(0)
+        .u32(0x2000)
+        .u16(4)
+        .u16(4)
+        .u8(18)
+        .u8(1)
+        .u16(0)
+        .u32(0);
+    for _ in 1..6576 {
+        fixture = fixture.bytes(&[0; 20]);
+    }
+
+    // the bytes of every u16 are swapped, the whole 32x32 blocks tile is there
+    fixture = fixture.bytes(&[0xF8, 0x00, 0x00, 0x1F, 0, 0, 0, 0]);
+    fixture.bytes(&[0; 0x2000 - 8]).0
+}
+
+#[test]
+fn textures() {
+    let bytes = textures_fixture();
+    let mut textures = Textures::new(&bytes).unwrap();
+
+    let mut writer = Cursor::new(vec![]);
+    textures.write(&mut writer).unwrap();
+    assert_eq!(writer.into_inner(), bytes);
+
+    let red = textures.decode(0).unwrap();
+    assert_eq!((red.width, red.height), (4, 4));
+    assert!(red.pixels.chunks(4).all(|p| p == [0xFF, 0, 0, 0xFF]));
+
+    // not a multiple of the tiles, nor of 4
+    let mut image = Image::new(37, 3);
+    for (i, pixel) in image.pixels.iter_mut().enumerate() {
+        *pixel = (i * 7) as u8;
+    }
+    textures.replace(1, &image).unwrap();
+    assert_eq!(textures.decode(1).unwrap(), image);
+
+    // found again once written
+    let mut writer = Cursor::new(vec![]);
+    textures.write(&mut writer).unwrap();
+    let textures = Textures::new(&writer.into_inner()).unwrap();
+    assert_eq!(textures.decode(0).unwrap(), red);
+    assert_eq!(textures.decode(1).unwrap(), image);
+
+    let mut png = vec![];
+    image.write_png(&mut png).unwrap();
+    assert_eq!(Image::read_png(&png[..]).unwrap(), image);
+}
+
+#[test]
+fn sprite_frames() {
+    let mut fixture = Fixture::default().u16(1).u16(0x1000).bytes(&[0; 12]).u32(0);
+    fixture = sprite_frame(fixture, 0, 2, 3);
+    let sprite = Sprite::new(&mut Cursor::new(&fixture.0[..])).unwrap();
+    let mut textures = Textures::new(&textures_fixture()).unwrap();
+
+    assert_eq!(sprite.frames().len(), 1);
+    assert_eq!(sprite.frames()[0].texture_id(), 0);
+    let frames = sprite.frame_images(&textures).unwrap();
+    assert_eq!((frames[0].width, frames[0].height), (2, 3));
+    assert!(frames[0].pixels.chunks(4).all(|p| p == [0xFF, 0, 0, 0xFF]));
+
+    // the rest of the texture is kept
+    let mut blue = Image::new(2, 3);
+    for pixel in blue.pixels.chunks_mut(4) {
+        pixel.copy_from_slice(&[0, 0, 0xFF, 0xFF]);
+    }
+    assert!(
+        sprite
+            .replace_frame(0, &mut textures, &Image::new(4, 4))
+            .is_err()
+    );
+    sprite.replace_frame(0, &mut textures, &blue).unwrap();
+    assert_eq!(sprite.frame_images(&textures).unwrap()[0], blue);
+    let texture = textures.decode(0).unwrap();
+    assert_eq!((texture.width, texture.height), (4, 4));
+    assert_eq!(texture.pixel(3, 3), [0xFF, 0, 0, 0xFF]);
+
+    let mut gif = vec![];
+    sprite.write_gif(&textures, &mut gif).unwrap();
+    assert!(gif.starts_with(b"GIF89a"));
+}
+
+fn model_fixture() -> Vec<u8> {
+    let header = Fixture::default()
+        .u32(0xB)
+        // geometry layout, texture setup, geo type
+        .u32(0xC0)
+        .u16(0x38)
+        .u16(2)
+        // display list, vertex store, unknown, animation setup, collision setup
+        .u32(0x50)
+        .u32(0x68)
+        .u32(0)
+        .u32(0)
+        .u32(0x90)
+        // unknown, effects, unknown, animated textures
+        .u32(0)
+        .u32(0)
+        .u32(0)
+        .u32(0x108)
+        .u16(1)
+        .u16(1)
+        .f32(1.0);
+
+    let texture_setup = Fixture::default()
+        .u32(0x18)
+        .u16(1)
+        .u16(0)
+        .u32(0)
+        .u16(4)
+        .u16(0)
+        .u8(32)
+        .u8(32)
+        .u16(0x0123)
+        .u32(0);
+
+    let display_list = Fixture::default()
+        .u32(2)
+        .u32(0)
+        .bytes(&[0x04, 0x00, 0x00, 0x0F, 0x01, 0x00, 0x00, 0x00])
+        .bytes(&[0xB8, 0, 0, 0, 0, 0, 0, 0]);
+
+    let vertex_store = Fixture::default()
+        .bytes(&[0; 18])
+        .i16(100)
+        .u16(1)
+        .i16(100)
+        .i16(-10)
+        .i16(20)
+        .i16(30)
+        .u16(0)
+        .i16(32)
+        .i16(64)
+        .bytes(&[0xFF, 0x80, 0x00, 0xFF]);
+
+    // two cells, one triangle, then 4 bytes of alignment
+    let collision_setup = Fixture::default()
+        .bytes(&[0; 16])
+        .u16(2)
+        .u16(1)
+        .u16(1)
+        .u16(0)
+        .u16(0)
+        .u16(1)
+        .u16(1)
+        .u16(0)
+        .u16(0)
+        .u16(0)
+        .u16(0)
+        .u16(0)
+        .u32(0x0080_0000)
+        .u32(0);
+
+    // a LOD drawing a display list, then a reference point
+    let geometry_layout = Fixture::default()
+        .u32(8)
+        .u32(0x30)
+        .f32(1000.0)
+        .f32(0.0)
+        .f32(0.0)
+        .f32(0.0)
+        .f32(0.0)
+        .u32(0x20)
+        .u32(3)
+        .u32(0)
+        .u16(0)
+        .u16(1)
+        .u32(0)
+        .u32(10)
+        .u32(0)
+        .u16(1)
+        .u16(2)
+        .f32(0.0)
+        .f32(50.0)
+        .f32(0.0);
+
+    let mut bytes = header.0;
+    for section in [
+        texture_setup,
+        display_list,
+        vertex_store,
+        collision_setup,
+        geometry_layout,
+    ] {
+        bytes.extend(section.0);
+    }
+    // not understood, with the alignment of db360.cmp
+    bytes.extend([1, 2, 3, 4, 5, 6, 7, 8, 0xCD, 0xCD, 0xCD]);
+    bytes
+}
+
+#[test]
+fn model() {
+    let bytes = model_fixture();
+    let mut model = Model::new(&mut &bytes[..], bytes.len()).unwrap();
+
+    let mut writer = Cursor::new(vec![]);
+    model.write(&mut writer).unwrap();
+    assert_eq!(writer.into_inner(), bytes);
+
+    assert_eq!(model.texture_setup().unwrap().textures[0].width, 32);
+    assert_eq!(model.display_list().unwrap().commands.len(), 2);
+    assert_eq!(model.collision_setup().unwrap().triangles.len(), 1);
+    assert!(matches!(
+        model.geometry_layout().unwrap(),
+        [
+            GeometryLayout::Lod { children, .. },
+            GeometryLayout::ReferencePoint { bone: 2, .. },
+        ] if matches!(children[..], [GeometryLayout::LoadDl { tri_count: 1, .. }])
+    ));
+    assert!(matches!(
+        model.section(SectionId::AnimatedTextures),
+        Some(SectionData::Raw(raw)) if raw.len() == 11
+    ));
+
+    // the sections after the vertex store move, and are found again
+    let Some(SectionData::VertexStore(store)) = model.section_mut(SectionId::VertexStore) else {
+        panic!("no vertex store");
+    };
+    store.vertices.push(store.vertices[0].clone());
+    model.vertex_count = 2;
+
+    let mut writer = Cursor::new(vec![]);
+    model.write(&mut writer).unwrap();
+    let written = writer.into_inner();
+    assert_eq!(written.len(), bytes.len() + 16);
+
+    let model = Model::new(&mut &written[..], written.len()).unwrap();
+    assert_eq!(model.vertex_store().unwrap().vertices.len(), 2);
+    assert_eq!(model.collision_setup().unwrap().triangles.len(), 1);
+    assert_eq!(model.geometry_layout().unwrap().len(), 2);
+}
+
+#[test]
+fn model_export() {
+    let bytes = Fixture::default()
+        .u32(5)
+        .u32(0)
+        // G_SETTIMG, G_VTX of 3 vertices from the second one, G_TRI1, G_TRI2, G_ENDDL
+        .bytes(&[0xFD, 0x10, 0, 0, 0, 0, 0, 0x40])
+        .bytes(&[0x04, 0, 0x0C, 0x2F, 0x01, 0, 0, 0x10])
+        .bytes(&[0xBF, 0, 0, 0, 0, 0, 0x02, 0x04])
+        .bytes(&[0xB1, 0x04, 0x02, 0, 0, 0x04, 0, 0x02])
+        .bytes(&[0xB8, 0, 0, 0, 0, 0, 0, 0]);
+    let display_list = DisplayList::new(&mut Cursor::new(&bytes.0[..])).unwrap();
+
+    let triangles = display_list.triangles();
+    let vertices = triangles.iter().map(|t| t.vertices).collect::<Vec<_>>();
+    assert_eq!(vertices, [[1, 2, 3], [3, 2, 1], [3, 1, 2]]);
+    assert!(triangles.iter().all(|t| t.texture == Some(0x40)));
+
+    let bytes = model_fixture();
+    let model = Model::new(&mut &bytes[..], bytes.len()).unwrap();
+    let mut obj = Obj::new();
+    obj.add_model("Model", &model, None).unwrap();
+
+    let path = std::env::temp_dir().join("bk-rando-model-export/model.obj");
+    obj.save(&path).unwrap();
+    let text = std::fs::read_to_string(&path).unwrap();
+    assert!(text.starts_with("mtllib model.mtl\no Model\nv -10 20 30\n"));
+    assert!(path.with_extension("mtl").exists());
+}
+
+// the smallest asset of each kind that reads, a db360.cmp with nothing in it
+fn game_fixture() -> Vec<u8> {
+    let empty_dialogue = Fixture::default()
+        .u8(4)
+        .u16_le(9)
+        .u16_le(11)
+        .u16_le(13)
+        .u16_le(15)
+        .bytes(&[0; 8]);
+    let empty_question = question_fixture([(); 4].map(|_| Fixture::default().u8(0)));
+    let empty_map_setup = Fixture::default()
+        .u16(0x0101)
+        .bytes(&[0; 24])
+        // one missing cube, no camera, no lighting
+        .bytes(&[1, 0, 3, 0, 4, 0, 0]);
+    let empty_sprite = Fixture::default().u16(0).u16(0x1000).bytes(&[0; 12]);
+
+    let mut offsets = Fixture::default();
+    let mut assets = Fixture::default();
+    for asset_id in &ASSETS {
+        offsets = offsets.u32(assets.len() as u32).u32(0);
+        assets = match asset_id {
+            AssetId::Animation(_) => assets.bytes(&[0, 1, 0, 1, 0, 0, 0, 0]),
+            AssetId::Dialogue(_) | AssetId::Credits(_) | AssetId::Xbox(_) => {
+                assets.bytes(&empty_dialogue.0)
+            }
+            AssetId::Question(_) => assets.bytes(&empty_question),
+            AssetId::MapSetup(_) => assets.bytes(&empty_map_setup.0),
+            // the six with the N64 layout are kept raw
+            AssetId::Sprite(_) => assets.bytes(&empty_sprite.0),
+            _ => assets,
+        };
+    }
+
+    Fixture::default()
+        .u32(ASSETS.len() as u32)
+        .u32(0xCDCDCDCD)
+        .bytes(&offsets.0)
+        .bytes(&assets.0)
+        .0
+}
+
+#[test]
+fn same_seed() {
+    let db360 = game_fixture();
+    let textures = textures_fixture();
+    let config = Config {
+        music: true,
+        jingles: true,
+        ..Default::default()
+    };
+
+    let spoiler = |seed| {
+        let mut rando = Randomizer::from_bytes(&[], &db360, &textures, seed).unwrap();
+        rando.shuffle_music(&config);
+        serde_json::to_string(rando.spoiler()).unwrap()
+    };
+
+    assert_eq!(spoiler(1234), spoiler(1234));
+    assert_ne!(spoiler(1234), spoiler(1235));
+}
+
+#[test]
+fn settings_hash() {
+    let directory = std::env::temp_dir().join(format!("bk-rando-hash-{}", std::process::id()));
+    let (a, b) = (directory.join("a"), directory.join("b"));
+    for dir in [&a, &b] {
+        std::fs::create_dir_all(dir).unwrap();
+        std::fs::write(dir.join("track.mid"), b"MThd").unwrap();
+    }
+
+    let hash = |file: &std::path::Path, seed| {
+        let config = Config {
+            seed: Some(seed),
+            custom_music: vec![CustomMusic {
+                track: MidiId::MumbosMountain,
+                file: file.join("track.mid"),
+            }],
+            ..Default::default()
+        };
+        config.settings_hash().unwrap()
+    };
+
+    // the seed and where the files are don't count, what's in them does
+    let original = hash(&a, 1);
+    assert_eq!(original, hash(&b, 2));
+    std::fs::write(b.join("track.mid"), b"MThd\0").unwrap();
+    assert_ne!(original, hash(&b, 1));
+
+    // the same options must keep the same hash from one build to the next
+    assert_eq!(Config::default().settings_hash().unwrap(), "106A4704");
+
+    std::fs::remove_dir_all(directory).unwrap();
+}
+
+#[test]
+fn unread_assets() {
+    let roundtrip = verify_roundtrip(&mut Cursor::new(game_fixture())).unwrap();
+
+    // the empty sprites the fixture gives the six with the N64 layout, and the empty models
+    let sprites = roundtrip
+        .unread
+        .iter()
+        .filter(|u| matches!(ASSETS[u.id], AssetId::Sprite(_)))
+        .count();
+    assert_eq!(sprites, 6);
+    let models = ASSETS
+        .iter()
+        .filter(|a| matches!(a, AssetId::Model(_)))
+        .count();
+    assert_eq!(roundtrip.unread.len(), sprites + models);
+}